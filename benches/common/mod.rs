@@ -0,0 +1,84 @@
+// Collects the estimates that criterion wrote during this run into a single JSON file, keyed by
+// benchmark id, so that results can be compared across commits.
+
+use std::{collections::BTreeMap, fs, path::PathBuf, time::SystemTime};
+
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Serialize)]
+struct Estimate {
+    mean_ns: f64,
+    median_ns: f64,
+    std_dev_ns: f64,
+}
+
+// Where criterion stores its results, following the same lookup as criterion itself
+fn criterion_home() -> PathBuf {
+    if let Some(home) = std::env::var_os("CRITERION_HOME") {
+        return home.into();
+    }
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| "target".into())
+        .join("criterion")
+}
+
+fn read_json(path: PathBuf) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn point_estimate(estimates: &Value, statistic: &str) -> f64 {
+    estimates[statistic]["point_estimate"]
+        .as_f64()
+        .expect("criterion estimates contain every statistic")
+}
+
+// Writes <criterion home>/<bench>.json with the benchmarks of the given groups that ran since
+// started. Benchmarks filtered out of this run, and runs in --test mode, are left out.
+pub fn write_summary(bench: &str, groups: &[&str], started: SystemTime) {
+    let home = criterion_home();
+    let mut summary = BTreeMap::new();
+
+    for group in groups {
+        let Ok(entries) = fs::read_dir(home.join(group)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let new = entry.path().join("new");
+            let fresh = fs::metadata(new.join("estimates.json"))
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified >= started);
+            if !fresh {
+                continue;
+            }
+
+            let (Some(benchmark), Some(estimates)) = (
+                read_json(new.join("benchmark.json")),
+                read_json(new.join("estimates.json")),
+            ) else {
+                continue;
+            };
+            let Some(id) = benchmark["full_id"].as_str() else {
+                continue;
+            };
+
+            summary.insert(
+                id.to_string(),
+                Estimate {
+                    mean_ns: point_estimate(&estimates, "mean"),
+                    median_ns: point_estimate(&estimates, "median"),
+                    std_dev_ns: point_estimate(&estimates, "std_dev"),
+                },
+            );
+        }
+    }
+
+    if summary.is_empty() {
+        return;
+    }
+
+    let path = home.join(format!("{}.json", bench));
+    fs::write(&path, serde_json::to_string_pretty(&summary).unwrap()).unwrap();
+    println!("Wrote {}", path.display());
+}
@@ -0,0 +1,137 @@
+// Commit, prove and verify for STIR and FRI, over the default field, Merkle tree and sponge of the
+// binaries. Run with `cargo bench --bench ldt`; a summary is written to target/criterion/ldt.json.
+
+use std::time::SystemTime;
+
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use criterion::{criterion_group, BatchSize, BenchmarkId, Criterion};
+
+use stir::{
+    crypto::{
+        fields::Field192 as F,
+        fs::blake3::{default_fs_config, Sponge},
+        merkle_tree::sha3 as merkle_tree,
+    },
+    fri::Fri,
+    ldt::{LowDegreeTest, Prover, Verifier},
+    parameters::{Parameters, SoundnessType},
+    stir::Stir,
+};
+
+mod common;
+
+type MerkleConfig = merkle_tree::MerkleTreeParams<F>;
+
+// Log of the starting degree, and the folding factors to run each degree with
+const DEGREES: [usize; 3] = [12, 14, 16];
+const FOLDING_FACTORS: [usize; 3] = [4, 8, 16];
+const STOPPING_DEGREE: usize = 1 << 6;
+
+const GROUPS: [&str; 7] = [
+    "stir_commit",
+    "stir_prove",
+    "stir_verify",
+    "stir_verify_prepared",
+    "fri_commit",
+    "fri_prove",
+    "fri_verify",
+];
+
+fn parameters(log_degree: usize, folding_factor: usize) -> Parameters<F, MerkleConfig, Sponge> {
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) =
+        merkle_tree::default_config::<F>(&mut rng, folding_factor);
+
+    Parameters {
+        security_level: 128,
+        protocol_security_level: 106,
+        starting_degree: 1 << log_degree,
+        stopping_degree: STOPPING_DEGREE,
+        folding_factor,
+        starting_rate: 2,
+        soundness_type: SoundnessType::Conjecture,
+
+        leaf_hash_params,
+        two_to_one_params,
+        fiat_shamir_config: default_fs_config(),
+        _field: Default::default(),
+    }
+}
+
+fn bench_ldt<L: LowDegreeTest<F, MerkleConfig, Sponge>>(c: &mut Criterion, name: &str) {
+    for (log_degree, folding_factor) in itertools::iproduct!(DEGREES, FOLDING_FACTORS) {
+        let id = BenchmarkId::from_parameter(format!("d{}_k{}", log_degree, folding_factor));
+        let (prover, verifier) = L::instantiate(parameters(log_degree, folding_factor));
+        let poly = DensePolynomial::<F>::rand((1 << log_degree) - 1, &mut ark_std::test_rng());
+
+        let mut group = c.benchmark_group(format!("{}_commit", name));
+        group.sample_size(10);
+        group.bench_with_input(id.clone(), &poly, |b, poly| {
+            b.iter_batched(
+                || poly.clone(),
+                |poly| prover.commit(poly),
+                BatchSize::LargeInput,
+            )
+        });
+        group.finish();
+
+        let (commitment, witness) = prover.commit(poly);
+
+        let mut group = c.benchmark_group(format!("{}_prove", name));
+        group.sample_size(10);
+        group.bench_with_input(id.clone(), &witness, |b, witness| {
+            b.iter_batched(
+                || witness.clone(),
+                |witness| prover.prove(witness),
+                BatchSize::LargeInput,
+            )
+        });
+        group.finish();
+
+        let proof = prover.prove(witness);
+        assert!(verifier.verify(&commitment, &proof));
+
+        let mut group = c.benchmark_group(format!("{}_verify", name));
+        group.bench_with_input(id, &proof, |b, proof| {
+            b.iter(|| verifier.verify(&commitment, proof))
+        });
+        group.finish();
+    }
+}
+
+fn stir(c: &mut Criterion) {
+    bench_ldt::<Stir<F, MerkleConfig, Sponge>>(c, "stir");
+}
+
+// Verification with the domains of every round precomputed, to compare with stir_verify
+fn stir_prepared(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stir_verify_prepared");
+    for (log_degree, folding_factor) in itertools::iproduct!(DEGREES, FOLDING_FACTORS) {
+        let id = BenchmarkId::from_parameter(format!("d{}_k{}", log_degree, folding_factor));
+        let (prover, verifier) =
+            Stir::<F, MerkleConfig, Sponge>::instantiate(parameters(log_degree, folding_factor));
+        let poly = DensePolynomial::<F>::rand((1 << log_degree) - 1, &mut ark_std::test_rng());
+        let (commitment, witness) = prover.commit(poly);
+        let proof = prover.prove(witness);
+
+        let verifier = verifier.prepare();
+        assert!(verifier.verify_prepared(&commitment, &proof));
+        group.bench_with_input(id, &proof, |b, proof| {
+            b.iter(|| verifier.verify_prepared(&commitment, proof))
+        });
+    }
+    group.finish();
+}
+
+fn fri(c: &mut Criterion) {
+    bench_ldt::<Fri<F, MerkleConfig, Sponge>>(c, "fri");
+}
+
+criterion_group!(benches, stir, stir_prepared, fri);
+
+fn main() {
+    let started = SystemTime::now();
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    common::write_summary("ldt", &GROUPS, started);
+}
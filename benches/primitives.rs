@@ -0,0 +1,216 @@
+// The polynomial and commitment primitives that dominate the prover and verifier. Run with
+// `cargo bench --bench primitives`; a summary is written to target/criterion/primitives.json.
+
+use std::time::SystemTime;
+
+use ark_crypto_primitives::merkle_tree::{Config, LeafParam, MerkleTree, TwoToOneParam};
+use ark_ff::{FftField, Field, UniformRand};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use criterion::{
+    criterion_group, measurement::WallTime, BatchSize, BenchmarkGroup, BenchmarkId, Criterion,
+};
+
+use stir::{
+    crypto::{
+        fields::{Field192 as F, Field256},
+        merkle_tree,
+    },
+    poly_utils::{
+        folding::poly_fold, interpolation::fft_interpolate, quotient::quotient_with_hint,
+    },
+    utils::stack_evaluations,
+};
+
+mod common;
+
+// Log of the degree (or of the number of evaluations), and the folding factors to run each with
+const DEGREES: [usize; 3] = [12, 14, 16];
+const FOLDING_FACTORS: [usize; 3] = [4, 8, 16];
+// Sizes of the set a quotient is taken by, i.e. the number of queries of a round plus the OOD
+// samples
+const QUOTIENT_SET_SIZES: [usize; 3] = [8, 32, 128];
+
+const GROUPS: [&str; 5] = [
+    "poly_fold",
+    "fft_interpolate",
+    "quotient_with_hint",
+    "stack_evaluations",
+    "merkle_commit",
+];
+
+fn bench_poly_fold(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group("poly_fold");
+    for (log_degree, folding_factor) in itertools::iproduct!(DEGREES, FOLDING_FACTORS) {
+        let poly = DensePolynomial::<F>::rand((1 << log_degree) - 1, &mut rng);
+        let folding_randomness = F::rand(&mut rng);
+        let id = BenchmarkId::from_parameter(format!("d{}_k{}", log_degree, folding_factor));
+        group.bench_with_input(id, &poly, |b, poly| {
+            b.iter(|| poly_fold(poly, folding_factor, folding_randomness))
+        });
+    }
+    group.finish();
+}
+
+fn bench_fft_interpolate(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group("fft_interpolate");
+    for folding_factor in FOLDING_FACTORS {
+        let generator = F::get_root_of_unity(folding_factor as u64).unwrap();
+        let coset_offset = F::rand(&mut rng);
+        let (generator_inv, coset_offset_inv) = (
+            generator.inverse().unwrap(),
+            coset_offset.inverse().unwrap(),
+        );
+        let size_inv = F::from(folding_factor as u64).inverse().unwrap();
+        let points: Vec<_> = (0..folding_factor).map(|_| F::rand(&mut rng)).collect();
+        let id = BenchmarkId::from_parameter(format!("k{}", folding_factor));
+        group.bench_with_input(id, &points, |b, points| {
+            b.iter(|| {
+                fft_interpolate(
+                    generator,
+                    coset_offset,
+                    generator_inv,
+                    coset_offset_inv,
+                    size_inv,
+                    points,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_quotient_with_hint(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group("quotient_with_hint");
+    for set_size in QUOTIENT_SET_SIZES {
+        let quotient_set: Vec<_> = (0..set_size).map(|_| F::rand(&mut rng)).collect();
+        let evaluation_point = F::rand(&mut rng);
+        let denom_hint = quotient_set
+            .iter()
+            .map(|x| evaluation_point - x)
+            .product::<F>()
+            .inverse()
+            .unwrap();
+        let (claimed_eval, ans_eval) = (F::rand(&mut rng), F::rand(&mut rng));
+        let id = BenchmarkId::from_parameter(format!("s{}", set_size));
+        group.bench_with_input(id, &quotient_set, |b, quotient_set| {
+            b.iter(|| {
+                quotient_with_hint(
+                    claimed_eval,
+                    evaluation_point,
+                    quotient_set,
+                    denom_hint,
+                    ans_eval,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_stack_evaluations(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group("stack_evaluations");
+    for (log_size, folding_factor) in itertools::iproduct!(DEGREES, FOLDING_FACTORS) {
+        let evals: Vec<_> = (0..1 << log_size).map(|_| F::rand(&mut rng)).collect();
+        let id = BenchmarkId::from_parameter(format!("d{}_k{}", log_size, folding_factor));
+        group.bench_with_input(id, &evals, |b, evals| {
+            b.iter_batched(
+                || evals.clone(),
+                |evals| stack_evaluations(evals, folding_factor),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_merkle<M: Config<Leaf = Vec<Field256>>>(
+    group: &mut BenchmarkGroup<WallTime>,
+    backend: &str,
+    (leaf_hash_params, two_to_one_params): (LeafParam<M>, TwoToOneParam<M>),
+    leaves: &[Vec<Field256>],
+) {
+    let id = BenchmarkId::new(
+        backend,
+        format!(
+            "d{}_k{}",
+            (leaves.len() * leaves[0].len()).ilog2(),
+            leaves[0].len()
+        ),
+    );
+    group.bench_with_input(id, leaves, |b, leaves| {
+        b.iter(|| MerkleTree::<M>::new(&leaf_hash_params, &two_to_one_params, leaves).unwrap())
+    });
+}
+
+// Commits to 2^d evaluations stacked into leaves of k elements, with each backend. Field256 is
+// used since it is the one field every backend, Poseidon2 included, is instantiated for.
+fn bench_merkle_commit(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let mut group = c.benchmark_group("merkle_commit");
+    group.sample_size(10);
+    for (log_size, folding_factor) in itertools::iproduct!(DEGREES, FOLDING_FACTORS) {
+        let evals: Vec<_> = (0..1 << log_size)
+            .map(|_| Field256::rand(&mut rng))
+            .collect();
+        let leaves = stack_evaluations(evals, folding_factor);
+
+        use merkle_tree::{blake2, blake3, keccak, poseidon, poseidon2, sha3};
+        bench_merkle::<blake2::MerkleTreeParams<Field256>>(
+            &mut group,
+            "blake2",
+            blake2::default_config::<Field256>(&mut rng, folding_factor),
+            &leaves,
+        );
+        bench_merkle::<blake3::MerkleTreeParams<Field256>>(
+            &mut group,
+            "blake3",
+            blake3::default_config::<Field256>(&mut rng, folding_factor),
+            &leaves,
+        );
+        bench_merkle::<sha3::MerkleTreeParams<Field256>>(
+            &mut group,
+            "sha3",
+            sha3::default_config::<Field256>(&mut rng, folding_factor),
+            &leaves,
+        );
+        bench_merkle::<keccak::MerkleTreeParams<Field256>>(
+            &mut group,
+            "keccak",
+            keccak::default_config::<Field256>(&mut rng, folding_factor),
+            &leaves,
+        );
+        bench_merkle::<poseidon::MerkleTreeParams<Field256>>(
+            &mut group,
+            "poseidon",
+            poseidon::default_config::<Field256>(&mut rng, folding_factor),
+            &leaves,
+        );
+        bench_merkle::<poseidon2::MerkleTreeParams<Field256>>(
+            &mut group,
+            "poseidon2",
+            poseidon2::default_config::<Field256>(&mut rng, folding_factor),
+            &leaves,
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_poly_fold,
+    bench_fft_interpolate,
+    bench_quotient_with_hint,
+    bench_stack_evaluations,
+    bench_merkle_commit
+);
+
+fn main() {
+    let started = SystemTime::now();
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    common::write_summary("primitives", &GROUPS, started);
+}
@@ -1,4 +1,4 @@
 #!/bin/bash
 
-cargo run --release --bin prover -- "$@"
-cargo run --release --bin verifier -- "$@"
+cargo run --release --features metrics --bin prover -- "$@"
+cargo run --release --features metrics --bin verifier -- "$@"
@@ -0,0 +1,309 @@
+# Generates the Poseidon2 parameters in src/crypto/poseidon2/constants.rs
+#
+#     python3 scripts/poseidon2_constants.py > src/crypto/poseidon2/constants.rs
+#
+# Round numbers follow the round number script of the Poseidon reference implementation (including
+# the binomial bound of https://eprint.iacr.org/2023/537) with its security margin, and round
+# constants are sampled with the Grain LFSR of the Poseidon paper. For widths t >= 4, the diagonal
+# of the internal matrix is also sampled with the LFSR, until the characteristic polynomials of
+# M_I^k for k = 1, ..., 2t are irreducible (which rules out invariant subspace trails, see Section 5
+# of the Poseidon2 paper). For t = 2 and t = 3 the internal matrices are the fixed [[2, 1], [1, 3]] and
+# [[2, 1, 1], [1, 2, 1], [1, 1, 3]].
+#
+# These are instances of our own, not the ones of the Poseidon2 reference implementation, and the test
+# vectors only check the Rust permutation against the Python one below.
+
+import math
+
+SECURITY_LEVEL = 128
+
+FIELD64 = 18446744069414584321
+FIELD128 = 340282366920938463463374557953744961537
+FIELD256 = 0x73EDA753299D7D483339D80809A1D80553BDA402FFFE5BFEFFFFFFFF00000001
+
+INSTANCES = [
+    # (name, modulus, width, rate). The sponge instances have a capacity of 256 bits, the
+    # compression instances are twice as wide as that and have no rate. For Field64 the sponge
+    # instance is already as wide as the compression.
+    ("FIELD64", FIELD64, 8, 4),
+    ("FIELD128", FIELD128, 3, 1),
+    ("FIELD128_COMPRESSION", FIELD128, 4, 0),
+    ("FIELD256", FIELD256, 3, 2),
+    ("FIELD256_COMPRESSION", FIELD256, 2, 0),
+]
+
+
+def smallest_alpha(p):
+    alpha = 3
+    while math.gcd(alpha, p - 1) != 1:
+        alpha += 1
+    return alpha
+
+
+def log_binomial(n, k):
+    return (math.lgamma(n + 1) - math.lgamma(k + 1) - math.lgamma(n - k + 1)) / math.log(2)
+
+
+def secure(p, t, R_F, R_P, alpha, M):
+    n = p.bit_length()
+    log2_p = math.log2(p)
+    log_alpha_2 = math.log(2) / math.log(alpha)
+    R_F_1 = 6 if M <= (math.floor(log2_p - ((alpha - 1) / 2.0))) * (t + 1) else 10
+    R_F_2 = 1 + math.ceil(log_alpha_2 * min(M, n)) + math.ceil(math.log(t) / math.log(alpha)) - R_P
+    R_F_3 = log_alpha_2 * min(M, log2_p) - R_P
+    R_F_4 = t - 1 + log_alpha_2 * min(M / float(t + 1), log2_p / 2.0) - R_P
+    R_F_5 = (t - 2 + (M / float(2 * math.log2(alpha))) - R_P) / float(t - 1)
+    R_F_max = max(math.ceil(R_F_1), math.ceil(R_F_2), math.ceil(R_F_3), math.ceil(R_F_4), math.ceil(R_F_5))
+
+    r_temp = math.floor(t / 3.0)
+    over = (R_F - 1) * t + R_P + r_temp + r_temp * (R_F / 2.0) + R_P + alpha
+    under = r_temp * (R_F / 2.0) + R_P + alpha
+    cost_gb4 = math.ceil(2 * log_binomial(over, under))
+    return R_F >= R_F_max and cost_gb4 >= M
+
+
+def round_numbers(p, t, alpha, M=SECURITY_LEVEL):
+    best = None
+    for R_P in range(1, 500):
+        for R_F in range(4, 100, 2):
+            if secure(p, t, R_F, R_P, alpha, M):
+                # Security margin
+                candidate = (R_F + 2, int(math.ceil(R_P * 1.075)))
+                cost = t * candidate[0] + candidate[1]
+                if best is None or cost < best[0]:
+                    best = (cost, candidate)
+                break
+    return best[1]
+
+
+class Grain:
+    def __init__(self, p, t, R_F, R_P):
+        def bits(value, size):
+            return [int(b) for b in bin(value)[2:].zfill(size)]
+
+        # Prime field, x^alpha S-box
+        self.state = bits(1, 2) + bits(0, 4) + bits(p.bit_length(), 12) + bits(t, 12)
+        self.state += bits(R_F, 10) + bits(R_P, 10) + [1] * 30
+        for _ in range(160):
+            self.next_raw()
+
+    def next_raw(self):
+        s = self.state
+        bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0]
+        s.pop(0)
+        s.append(bit)
+        return bit
+
+    def next_bit(self):
+        while True:
+            if self.next_raw() == 1:
+                return self.next_raw()
+            self.next_raw()
+
+    def next_element(self, p):
+        n = p.bit_length()
+        while True:
+            value = int("".join(str(self.next_bit()) for _ in range(n)), 2)
+            if value < p:
+                return value
+
+
+# Polynomials over F_p are lists of coefficients, lowest degree first
+
+def poly_trim(a):
+    while a and a[-1] == 0:
+        a.pop()
+    return a
+
+
+def poly_sub(a, b, p):
+    result = [0] * max(len(a), len(b))
+    for i, c in enumerate(a):
+        result[i] = c
+    for i, c in enumerate(b):
+        result[i] = (result[i] - c) % p
+    return poly_trim(result)
+
+
+def poly_mod(a, m, p):
+    a = list(a)
+    inv = pow(m[-1], p - 2, p)
+    while len(a) >= len(m):
+        coeff = a[-1] * inv % p
+        shift = len(a) - len(m)
+        for i, c in enumerate(m):
+            a[shift + i] = (a[shift + i] - coeff * c) % p
+        poly_trim(a)
+    return a
+
+
+def poly_mulmod(a, b, m, p):
+    result = [0] * (len(a) + len(b))
+    for i, x in enumerate(a):
+        for j, y in enumerate(b):
+            result[i + j] = (result[i + j] + x * y) % p
+    return poly_mod(poly_trim(result), m, p)
+
+
+def poly_powmod(base, exponent, m, p):
+    result = [1]
+    base = poly_mod(base, m, p)
+    while exponent > 0:
+        if exponent & 1:
+            result = poly_mulmod(result, base, m, p)
+        base = poly_mulmod(base, base, m, p)
+        exponent >>= 1
+    return result
+
+
+def poly_gcd(a, b, p):
+    while b:
+        a, b = b, poly_mod(a, b, p)
+    return a
+
+
+def is_irreducible(f, p):
+    # Rabin's test
+    degree = len(f) - 1
+    x = [0, 1]
+    if poly_sub(poly_powmod(x, p**degree, f, p), x, p):
+        return False
+    for q in range(2, degree + 1):
+        if degree % q == 0 and all(q % r != 0 for r in range(2, q)):
+            h = poly_sub(poly_powmod(x, p ** (degree // q), f, p), x, p)
+            if len(poly_gcd(f, h, p)) > 1:
+                return False
+    return True
+
+
+def mat_mul(a, b, p):
+    t = len(a)
+    return [[sum(a[i][k] * b[k][j] for k in range(t)) % p for j in range(t)] for i in range(t)]
+
+
+def charpoly(m, p):
+    # Faddeev-LeVerrier
+    t = len(m)
+    coeffs = [0] * (t + 1)
+    coeffs[t] = 1
+    identity = [[int(i == j) for j in range(t)] for i in range(t)]
+    current = [[0] * t for _ in range(t)]
+    for k in range(1, t + 1):
+        current = mat_mul(m, current, p)
+        for i in range(t):
+            current[i][i] = (current[i][i] + coeffs[t - k + 1] * identity[i][i]) % p
+        product = mat_mul(m, current, p)
+        trace = sum(product[i][i] for i in range(t)) % p
+        coeffs[t - k] = (-trace * pow(k, p - 2, p)) % p
+    return coeffs
+
+
+def internal_matrix(diagonal, p):
+    t = len(diagonal)
+    return [[(1 + (diagonal[i] if i == j else 0)) % p for j in range(t)] for i in range(t)]
+
+
+def is_secure_internal(diagonal, p):
+    m = internal_matrix(diagonal, p)
+    power = m
+    for _ in range(2 * len(diagonal)):
+        if not is_irreducible(charpoly(power, p), p):
+            return False
+        power = mat_mul(power, m, p)
+    return True
+
+
+def generate(p, t):
+    alpha = smallest_alpha(p)
+    R_F, R_P = round_numbers(p, t, alpha)
+    grain = Grain(p, t, R_F, R_P)
+    constants = [grain.next_element(p) for _ in range((R_F + R_P) * t)]
+    external = [constants[r * t:(r + 1) * t] for r in range(R_F // 2)]
+    internal = [constants[r * t] for r in range(R_F // 2, R_F // 2 + R_P)]
+    external += [constants[r * t:(r + 1) * t] for r in range(R_F // 2 + R_P, R_F + R_P)]
+
+    if t == 2:
+        diagonal = [1, 2]
+    elif t == 3:
+        diagonal = [1, 1, 2]
+    else:
+        while True:
+            diagonal = [grain.next_element(p) for _ in range(t)]
+            if is_secure_internal(diagonal, p):
+                break
+
+    return alpha, R_F, R_P, external, internal, diagonal
+
+
+M4 = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]]
+
+
+def external_matrix(t):
+    if t <= 3:
+        return [[2 if i == j else 1 for j in range(t)] for i in range(t)]
+    return [[M4[i % 4][j % 4] * (2 if i // 4 == j // 4 else 1) for j in range(t)] for i in range(t)]
+
+
+def mat_vec(m, v, p):
+    return [sum(a * b for a, b in zip(row, v)) % p for row in m]
+
+
+# Reference permutation, used for the test vectors
+def permute(p, alpha, external, internal, diagonal, state):
+    t = len(state)
+    m_e = external_matrix(t)
+    m_i = internal_matrix(diagonal, p)
+    state = mat_vec(m_e, state, p)
+    half = len(external) // 2
+    for constants in external[:half]:
+        state = [pow((s + c) % p, alpha, p) for s, c in zip(state, constants)]
+        state = mat_vec(m_e, state, p)
+    for constant in internal:
+        state[0] = pow((state[0] + constant) % p, alpha, p)
+        state = mat_vec(m_i, state, p)
+    for constants in external[half:]:
+        state = [pow((s + c) % p, alpha, p) for s, c in zip(state, constants)]
+        state = mat_vec(m_e, state, p)
+    return state
+
+
+def rust_strings(values, indent):
+    return "".join("{}\"{}\",\n".format(" " * indent, v) for v in values)
+
+
+def main():
+    print("// Generated by scripts/poseidon2_constants.py, do not edit by hand.")
+    print()
+    print("use super::ConstantsTable;")
+    for name, p, t, rate in INSTANCES:
+        alpha, R_F, R_P, external, internal, diagonal = generate(p, t)
+        print()
+        print("pub const {}: ConstantsTable = ConstantsTable {{".format(name))
+        print("    width: {},".format(t))
+        print("    rate: {},".format(rate))
+        print("    alpha: {},".format(alpha))
+        print("    full_rounds: {},".format(R_F))
+        print("    partial_rounds: {},".format(R_P))
+        print("    external_constants: &[")
+        for round_constants in external:
+            print("        &[")
+            print(rust_strings(round_constants, 12), end="")
+            print("        ],")
+        print("    ],")
+        print("    internal_constants: &[")
+        print(rust_strings(internal, 8), end="")
+        print("    ],")
+        print("    internal_diagonal: &[")
+        print(rust_strings(diagonal, 8), end="")
+        print("    ],")
+        print("};")
+        print()
+        print("// Permutation of [0, 1, ..., width - 1]")
+        print("#[cfg(test)]")
+        print("pub const {}_TEST_VECTOR: &[&str] = &[".format(name))
+        print(rust_strings(permute(p, alpha, external, internal, diagonal, list(range(t))), 4), end="")
+        print("];")
+
+
+if __name__ == "__main__":
+    main()
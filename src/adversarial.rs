@@ -0,0 +1,422 @@
+// Soundness tests against malicious provers.
+//
+// Each strategy below produces a (commitment, proof) pair that the honest prover would never
+// output, and we measure how often the verifier accepts. The empirical acceptance rate is then
+// compared against the soundness error predicted by `Parameters::repetitions`. Parameters are
+// deliberately tiny so that the predicted error is large enough to be measured.
+
+use ark_crypto_primitives::merkle_tree::MerkleTree;
+use ark_ff::{Field, UniformRand};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    crypto::{fields::Field64 as TestField, fs, merkle_tree},
+    domain::Domain,
+    fri::{self, Fri},
+    ldt::{LowDegreeTest, Prover, ProverError, Verifier},
+    parameters::{Parameters, SoundnessType},
+    stir::{self, Stir},
+    utils,
+};
+
+use merkle_tree::blake3 as mt;
+
+type MerkleConfig = mt::MerkleTreeParams<TestField>;
+type FSConfig = fs::blake3::Sponge;
+type TestParameters = Parameters<TestField, MerkleConfig, FSConfig>;
+
+const TRIALS: usize = 64;
+
+fn test_parameters(folding_factor: usize) -> TestParameters {
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) =
+        mt::default_config::<TestField>(&mut rng, folding_factor);
+    Parameters {
+        // Equal security levels, so that no proof of work is required
+        security_level: 4,
+        protocol_security_level: 4,
+        starting_degree: 1 << 6,
+        stopping_degree: 1 << 2,
+        folding_factor,
+        starting_rate: 1,
+        soundness_type: SoundnessType::Conjecture,
+        leaf_hash_params,
+        two_to_one_params,
+        fiat_shamir_config: fs::blake3::default_fs_config(),
+        _field: Default::default(),
+    }
+}
+
+// The soundness error of the first round for a function that is `distance`-far from the code.
+// Each of the `repetitions` queries independently lands on a consistent position with probability
+// at most `1 - distance`.
+fn predicted_acceptance(parameters: &TestParameters, distance: f64) -> f64 {
+    let repetitions = parameters.repetitions(parameters.starting_rate);
+    (1. - distance).powi(repetitions as i32)
+}
+
+// Checks that the empirical acceptance rate is not significantly above the predicted one,
+// allowing for four standard deviations of sampling error.
+fn assert_acceptance_within(strategy: &str, accepted: usize, trials: usize, predicted: f64) {
+    let observed = accepted as f64 / trials as f64;
+    let slack = 4. * (predicted * (1. - predicted) / trials as f64).sqrt() + 1. / trials as f64;
+    assert!(
+        observed <= predicted + slack,
+        "{}: accepted {}/{} (rate {:.3}), predicted at most {:.3}",
+        strategy,
+        accepted,
+        trials,
+        observed,
+        predicted
+    );
+}
+
+// Evaluates the polynomial over the starting domain and stacks the evaluations into leaves,
+// replacing `corrupted_leaves` of them with uniformly random values.
+fn corrupted_leaves(
+    parameters: &TestParameters,
+    polynomial: &DensePolynomial<TestField>,
+    corrupted_leaves: usize,
+    rng: &mut StdRng,
+) -> (Domain<TestField>, Vec<Vec<TestField>>) {
+    let domain =
+        Domain::<TestField>::new(parameters.starting_degree, parameters.starting_rate).unwrap();
+    let evals = polynomial
+        .evaluate_over_domain_by_ref(domain.backing_domain)
+        .evals;
+    let mut folded_evals = utils::stack_evaluations(evals, parameters.folding_factor);
+
+    let mut indexes: Vec<_> = (0..folded_evals.len()).collect();
+    indexes.shuffle(rng);
+    for &index in indexes.iter().take(corrupted_leaves) {
+        for eval in folded_evals[index].iter_mut() {
+            *eval += TestField::rand(rng) + TestField::ONE;
+        }
+    }
+
+    (domain, folded_evals)
+}
+
+fn merkle_tree(parameters: &TestParameters, leaves: &[Vec<TestField>]) -> MerkleTree<MerkleConfig> {
+    MerkleTree::<MerkleConfig>::new(
+        &parameters.leaf_hash_params,
+        &parameters.two_to_one_params,
+        leaves,
+    )
+    .unwrap()
+}
+
+// The prover commits to a codeword in which a `distance` fraction of the leaves has been
+// corrupted, and then runs the honest prover on the original polynomial.
+fn stir_corrupted_codeword(distance: f64) -> (usize, f64) {
+    let parameters = test_parameters(4);
+    let (prover, verifier) = Stir::instantiate(parameters.clone());
+
+    let mut accepted = 0;
+    for trial in 0..TRIALS {
+        let mut rng = StdRng::seed_from_u64(trial as u64);
+        let polynomial = DensePolynomial::rand(parameters.starting_degree - 1, &mut rng);
+        let num_leaves = parameters.starting_degree * (1 << parameters.starting_rate)
+            / parameters.folding_factor;
+        let corrupted = (distance * num_leaves as f64).round() as usize;
+        let (domain, folded_evals) =
+            corrupted_leaves(&parameters, &polynomial, corrupted, &mut rng);
+        let merkle_tree = merkle_tree(&parameters, &folded_evals);
+
+        let commitment = stir::common::Commitment {
+            root: merkle_tree.root(),
+        };
+        let witness = stir::prover::Witness {
+            domain,
+            polynomial: Some(polynomial),
+            merkle_tree,
+            folded_evals,
+        };
+        let proof = prover.prove(witness);
+        if verifier.verify(&commitment, &proof) {
+            accepted += 1;
+        }
+    }
+
+    (accepted, predicted_acceptance(&parameters, distance))
+}
+
+fn fri_corrupted_codeword(distance: f64) -> (usize, f64) {
+    let parameters = test_parameters(4);
+    let (prover, verifier) = Fri::instantiate(parameters.clone());
+
+    let mut accepted = 0;
+    for trial in 0..TRIALS {
+        let mut rng = StdRng::seed_from_u64(trial as u64);
+        let polynomial = DensePolynomial::rand(parameters.starting_degree - 1, &mut rng);
+        let num_leaves = parameters.starting_degree * (1 << parameters.starting_rate)
+            / parameters.folding_factor;
+        let corrupted = (distance * num_leaves as f64).round() as usize;
+        let (domain, folded_evals) =
+            corrupted_leaves(&parameters, &polynomial, corrupted, &mut rng);
+        let merkle_tree = merkle_tree(&parameters, &folded_evals);
+
+        let commitment = fri::common::Commitment {
+            root: merkle_tree.root(),
+        };
+        let witness = fri::prover::Witness {
+            domain,
+            polynomial: Some(polynomial),
+            merkle_tree,
+            folded_evals,
+        };
+        let proof = prover.prove(witness);
+        if verifier.verify(&commitment, &proof) {
+            accepted += 1;
+        }
+    }
+
+    (accepted, predicted_acceptance(&parameters, distance))
+}
+
+#[test]
+fn stir_corrupted_codewords() {
+    for distance in [0.125, 0.25, 0.5, 1.] {
+        let (accepted, predicted) = stir_corrupted_codeword(distance);
+        assert_acceptance_within(
+            &format!("stir corrupted codeword (distance {})", distance),
+            accepted,
+            TRIALS,
+            predicted,
+        );
+    }
+}
+
+#[test]
+fn fri_corrupted_codewords() {
+    for distance in [0.125, 0.25, 0.5, 1.] {
+        let (accepted, predicted) = fri_corrupted_codeword(distance);
+        assert_acceptance_within(
+            &format!("fri corrupted codeword (distance {})", distance),
+            accepted,
+            TRIALS,
+            predicted,
+        );
+    }
+}
+
+// The prover commits to a polynomial of degree exactly `starting_degree`, one more than allowed,
+// and then proves as if it had committed to its truncation to degree `starting_degree - 1`.
+#[test]
+fn stir_degree_just_above_bound() {
+    let parameters = test_parameters(4);
+    let (prover, verifier) = Stir::instantiate(parameters.clone());
+
+    let mut accepted = 0;
+    for trial in 0..TRIALS {
+        let mut rng = StdRng::seed_from_u64(trial as u64);
+        let polynomial = DensePolynomial::rand(parameters.starting_degree, &mut rng);
+        let (commitment, mut witness) = prover.commit(polynomial);
+        let polynomial = witness.polynomial.unwrap();
+        witness.polynomial = Some(DensePolynomial::from_coefficients_slice(
+            &polynomial.coeffs[..parameters.starting_degree],
+        ));
+
+        let proof = prover.prove(witness);
+        if verifier.verify(&commitment, &proof) {
+            accepted += 1;
+        }
+    }
+
+    // The committed function disagrees with every low degree polynomial on a large fraction of
+    // the domain, we conservatively use the unique decoding radius.
+    let distance = (1. - 2f64.powi(-(parameters.starting_rate as i32))) / 2.;
+    assert_acceptance_within(
+        "stir degree just above bound",
+        accepted,
+        TRIALS,
+        predicted_acceptance(&parameters, distance),
+    );
+}
+
+#[test]
+fn fri_degree_just_above_bound() {
+    let parameters = test_parameters(4);
+    let (prover, verifier) = Fri::instantiate(parameters.clone());
+
+    let mut accepted = 0;
+    for trial in 0..TRIALS {
+        let mut rng = StdRng::seed_from_u64(trial as u64);
+        let polynomial = DensePolynomial::rand(parameters.starting_degree, &mut rng);
+        let (commitment, mut witness) = prover.commit(polynomial);
+        let polynomial = witness.polynomial.unwrap();
+        witness.polynomial = Some(DensePolynomial::from_coefficients_slice(
+            &polynomial.coeffs[..parameters.starting_degree],
+        ));
+
+        let proof = prover.prove(witness);
+        if verifier.verify(&commitment, &proof) {
+            accepted += 1;
+        }
+    }
+
+    let distance = (1. - 2f64.powi(-(parameters.starting_rate as i32))) / 2.;
+    assert_acceptance_within(
+        "fri degree just above bound",
+        accepted,
+        TRIALS,
+        predicted_acceptance(&parameters, distance),
+    );
+}
+
+// Runs the honest STIR prover and then applies `tamper` to the proof before verification.
+fn stir_tampered_proof(
+    strategy: &str,
+    tamper: impl Fn(
+        &mut stir::common::Commitment<MerkleConfig>,
+        &mut stir::common::Proof<TestField, MerkleConfig>,
+    ),
+) {
+    // With more than one round, the first round commits to an oracle whatever the final phase
+    let mut parameters = test_parameters(4);
+    parameters.starting_degree = 1 << 8;
+    let (prover, verifier) = Stir::instantiate(parameters.clone());
+
+    let mut accepted = 0;
+    for trial in 0..TRIALS {
+        let mut rng = StdRng::seed_from_u64(trial as u64);
+        let polynomial = DensePolynomial::rand(parameters.starting_degree - 1, &mut rng);
+        let (mut commitment, witness) = prover.commit(polynomial);
+        let mut proof = prover.prove(witness);
+        assert!(!proof.round_proofs.is_empty());
+
+        tamper(&mut commitment, &mut proof);
+        if verifier.verify(&commitment, &proof) {
+            accepted += 1;
+        }
+    }
+
+    // A tampered proof is a proof for a function that is not in the code at all
+    assert_acceptance_within(
+        strategy,
+        accepted,
+        TRIALS,
+        predicted_acceptance(&parameters, 1.),
+    );
+}
+
+#[test]
+fn stir_tampered_ans_polynomial() {
+    stir_tampered_proof("stir tampered ans_polynomial", |_, proof| {
+        proof.round_proofs[0].ans_polynomial.coeffs[0] += TestField::ONE;
+    });
+}
+
+#[test]
+fn stir_tampered_shake_polynomial() {
+    stir_tampered_proof("stir tampered shake_polynomial", |_, proof| {
+        proof.round_proofs[0].shake_polynomial.coeffs[0] += TestField::ONE;
+    });
+}
+
+#[test]
+fn stir_tampered_betas() {
+    stir_tampered_proof("stir tampered betas", |_, proof| {
+        proof.round_proofs[0].betas[0] += TestField::ONE;
+    });
+}
+
+#[test]
+fn stir_swapped_merkle_roots() {
+    stir_tampered_proof("stir swapped merkle roots", |commitment, proof| {
+        std::mem::swap(&mut commitment.root, &mut proof.round_proofs[0].g_root);
+    });
+}
+
+#[test]
+fn fri_swapped_merkle_roots() {
+    let parameters = test_parameters(4);
+    let (prover, verifier) = Fri::instantiate(parameters.clone());
+
+    let mut accepted = 0;
+    for trial in 0..TRIALS {
+        let mut rng = StdRng::seed_from_u64(trial as u64);
+        let polynomial = DensePolynomial::rand(parameters.starting_degree - 1, &mut rng);
+        let (mut commitment, witness) = prover.commit(polynomial);
+        let mut proof = prover.prove(witness);
+        assert!(!proof.commitments.is_empty());
+
+        std::mem::swap(&mut commitment.root, &mut proof.commitments[0]);
+        if verifier.verify(&commitment, &proof) {
+            accepted += 1;
+        }
+    }
+
+    assert_acceptance_within(
+        "fri swapped merkle roots",
+        accepted,
+        TRIALS,
+        predicted_acceptance(&parameters, 1.),
+    );
+}
+
+// The prover is given a corrupted codeword in evaluation form and has to decode it by itself.
+#[test]
+fn stir_try_prove_close_function() {
+    let parameters = test_parameters(4);
+    let (prover, verifier) = Stir::instantiate(parameters.clone());
+    // Within the unique decoding radius (which is 1/4 for rate 1/2)
+    let distance = 0.125;
+
+    let mut accepted = 0;
+    for trial in 0..TRIALS {
+        let mut rng = StdRng::seed_from_u64(trial as u64);
+        let polynomial = DensePolynomial::rand(parameters.starting_degree - 1, &mut rng);
+        let num_leaves = parameters.starting_degree * (1 << parameters.starting_rate)
+            / parameters.folding_factor;
+        let corrupted = (distance * num_leaves as f64).round() as usize;
+        let (_, folded_evals) = corrupted_leaves(&parameters, &polynomial, corrupted, &mut rng);
+
+        let (commitment, witness) =
+            prover.commit_evaluations(utils::unstack_evaluations(&folded_evals));
+        let proof = prover.try_prove(witness).unwrap();
+        if verifier.verify(&commitment, &proof) {
+            accepted += 1;
+        }
+    }
+
+    assert_acceptance_within(
+        "stir close function",
+        accepted,
+        TRIALS,
+        predicted_acceptance(&parameters, distance),
+    );
+}
+
+#[test]
+fn stir_try_prove_far_function() {
+    let parameters = test_parameters(4);
+    let (prover, _) = Stir::instantiate(parameters.clone());
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let size = parameters.starting_degree * (1 << parameters.starting_rate);
+    let evals = (0..size).map(|_| TestField::rand(&mut rng)).collect();
+    let (_, witness) = prover.commit_evaluations(evals);
+
+    assert_eq!(
+        prover.try_prove(witness).err(),
+        Some(ProverError::NotDecodable)
+    );
+}
+
+#[test]
+fn fri_try_prove_far_function() {
+    let parameters = test_parameters(4);
+    let (prover, _) = Fri::instantiate(parameters.clone());
+
+    // A polynomial of too large degree is far from the code
+    let mut rng = StdRng::seed_from_u64(0);
+    let polynomial = DensePolynomial::rand(2 * parameters.starting_degree, &mut rng);
+    let (_, witness) = prover.commit(polynomial);
+
+    assert_eq!(
+        prover.try_prove(witness).err(),
+        Some(ProverError::NotDecodable)
+    );
+}
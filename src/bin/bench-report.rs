@@ -0,0 +1,483 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+use clap::Parser;
+
+// Aggregates the records that the prover and verifier binaries append to outputs/, grouping them
+// by parameters, and compares STIR to FRI across degrees: medians with 95% confidence intervals
+// are written to a CSV file, charted in SVG, and charted in ASCII on stdout
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[arg(long, default_value = "outputs/prover_output.json")]
+    prover: PathBuf,
+
+    #[arg(long, default_value = "outputs/verifier_output.json")]
+    verifier: PathBuf,
+
+    // Where to write report.csv and the SVG charts
+    #[arg(short = 'o', long, default_value = "outputs/report")]
+    out_dir: PathBuf,
+}
+
+// The parameters the records are grouped by, apart from the degree along which STIR and FRI are
+// compared. Records from before the backends were selectable at runtime have no backends.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+struct Configuration {
+    #[serde(default)]
+    field: Option<String>,
+    #[serde(default)]
+    merkle: Option<String>,
+    #[serde(default)]
+    fs: Option<String>,
+    security_level: usize,
+    protocol_security_level: usize,
+    stopping_degree: usize,
+    starting_rate: usize,
+    stir_folding_factor: usize,
+    fri_folding_factor: usize,
+}
+
+impl Configuration {
+    fn backends(&self) -> [&str; 3] {
+        [&self.field, &self.merkle, &self.fs].map(|b| b.as_deref().unwrap_or(""))
+    }
+
+    // Identifies the configuration in file names
+    fn slug(&self) -> String {
+        let [field, merkle, fs] = self.backends().map(|b| if b.is_empty() { "na" } else { b });
+        format!(
+            "f{}_{}_{}_l{}_p{}_r{}_s{}_k{}_{}",
+            field,
+            merkle,
+            fs,
+            self.security_level,
+            self.protocol_security_level,
+            self.starting_rate,
+            self.stopping_degree.ilog2(),
+            self.stir_folding_factor,
+            self.fri_folding_factor
+        )
+    }
+}
+
+impl std::fmt::Display for Configuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [field, merkle, fs] =
+            self.backends()
+                .map(|b| if b.is_empty() { "unspecified" } else { b });
+        write!(
+            f,
+            "Field: {} bits, Merkle tree: {}, Fiat-Shamir: {}, security: {} (protocol {}), rate: {}, stopping degree: 2^{}, folding: {} (STIR) {} (FRI)",
+            field,
+            merkle,
+            fs,
+            self.security_level,
+            self.protocol_security_level,
+            self.starting_rate,
+            self.stopping_degree.ilog2(),
+            self.stir_folding_factor,
+            self.fri_folding_factor
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProverRecord {
+    #[serde(flatten)]
+    configuration: Configuration,
+    starting_degree: usize,
+    stir_prover_time: Duration,
+    stir_prover_hashes: usize,
+    fri_prover_time: Duration,
+    fri_prover_hashes: usize,
+    stir_argument_size: usize,
+    fri_argument_size: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerifierRecord {
+    #[serde(flatten)]
+    configuration: Configuration,
+    starting_degree: usize,
+    // Already averaged over the repetitions
+    stir_verifier_time: Duration,
+    stir_verifier_hashes: usize,
+    fri_verifier_time: Duration,
+    fri_verifier_hashes: usize,
+}
+
+// Name and unit of each metric, in the order they are reported in
+const METRICS: [(&str, &str); 5] = [
+    ("prover_time", "s"),
+    ("prover_hashes", "hashes"),
+    ("argument_size", "KiB"),
+    ("verifier_time", "ms"),
+    ("verifier_hashes", "hashes"),
+];
+
+// The samples of a metric at one degree, for STIR and FRI
+#[derive(Default)]
+struct Samples {
+    stir: Vec<f64>,
+    fri: Vec<f64>,
+}
+
+impl Samples {
+    fn by_ldt(&self) -> [(&'static str, &[f64]); 2] {
+        [("stir", &self.stir), ("fri", &self.fri)]
+    }
+}
+
+// For each configuration and metric, the samples at each degree
+type Table = BTreeMap<Configuration, BTreeMap<&'static str, BTreeMap<usize, Samples>>>;
+
+#[derive(Debug, Clone, Copy)]
+struct Summary {
+    samples: usize,
+    median: f64,
+    low: f64,
+    high: f64,
+}
+
+// The median and a distribution-free 95% confidence interval for it, given by the order
+// statistics at n/2 -+ 1.96 sqrt(n) / 2
+fn summarize(samples: &[f64]) -> Summary {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len();
+
+    let median = if n % 2 == 1 {
+        sorted[n / 2]
+    } else {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.
+    };
+    let spread = 1.96 * (n as f64).sqrt() / 2.;
+    let low = (n as f64 / 2. - spread).floor().max(0.) as usize;
+    let high = ((n as f64 / 2. + spread).ceil() as usize).min(n - 1);
+
+    Summary {
+        samples: n,
+        median,
+        low: sorted[low],
+        high: sorted[high],
+    }
+}
+
+fn read_records<T: DeserializeOwned>(path: &Path) -> Vec<T> {
+    let file = File::open(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    BufReader::new(file)
+        .lines()
+        .map(|line| line.unwrap())
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(&line).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+fn push(
+    table: &mut Table,
+    configuration: &Configuration,
+    metric: &'static str,
+    degree: usize,
+    (stir, fri): (f64, f64),
+) {
+    let samples = table
+        .entry(configuration.clone())
+        .or_default()
+        .entry(metric)
+        .or_default()
+        .entry(degree)
+        .or_default();
+    samples.stir.push(stir);
+    samples.fri.push(fri);
+}
+
+fn tabulate(prover: &[ProverRecord], verifier: &[VerifierRecord]) -> Table {
+    let mut table = Table::new();
+    for r in prover {
+        let (c, d) = (&r.configuration, r.starting_degree);
+        let time = (
+            r.stir_prover_time.as_secs_f64(),
+            r.fri_prover_time.as_secs_f64(),
+        );
+        push(&mut table, c, "prover_time", d, time);
+        let hashes = (r.stir_prover_hashes as f64, r.fri_prover_hashes as f64);
+        push(&mut table, c, "prover_hashes", d, hashes);
+        let size = (
+            r.stir_argument_size as f64 / 1024.,
+            r.fri_argument_size as f64 / 1024.,
+        );
+        push(&mut table, c, "argument_size", d, size);
+    }
+    for r in verifier {
+        let (c, d) = (&r.configuration, r.starting_degree);
+        let time = (
+            r.stir_verifier_time.as_secs_f64() * 1e3,
+            r.fri_verifier_time.as_secs_f64() * 1e3,
+        );
+        push(&mut table, c, "verifier_time", d, time);
+        let hashes = (r.stir_verifier_hashes as f64, r.fri_verifier_hashes as f64);
+        push(&mut table, c, "verifier_hashes", d, hashes);
+    }
+    table
+}
+
+// The metrics of a configuration that have samples, with their units, in the order of METRICS
+fn reported<'a>(
+    metrics: &'a BTreeMap<&'static str, BTreeMap<usize, Samples>>,
+) -> impl Iterator<Item = ((&'static str, &'static str), &'a BTreeMap<usize, Samples>)> {
+    METRICS
+        .into_iter()
+        .filter_map(|metric| Some((metric, metrics.get(metric.0)?)))
+}
+
+fn format_value(x: f64) -> String {
+    if x.abs() >= 100. {
+        format!("{:.0}", x)
+    } else if x.abs() >= 1. {
+        format!("{:.2}", x)
+    } else {
+        format!("{:.3}", x)
+    }
+}
+
+fn csv(table: &Table) -> String {
+    let mut out = String::from(
+        "field,merkle,fs,security_level,protocol_security_level,starting_rate,stopping_degree,\
+         stir_folding_factor,fri_folding_factor,starting_degree,metric,unit,ldt,samples,median,\
+         ci_low,ci_high\n",
+    );
+    for (c, metrics) in table {
+        let [field, merkle, fs] = c.backends();
+        for ((metric, unit), degrees) in reported(metrics) {
+            for (degree, samples) in degrees {
+                for (ldt, samples) in samples.by_ldt() {
+                    let s = summarize(samples);
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                        field,
+                        merkle,
+                        fs,
+                        c.security_level,
+                        c.protocol_security_level,
+                        c.starting_rate,
+                        c.stopping_degree,
+                        c.stir_folding_factor,
+                        c.fri_folding_factor,
+                        degree,
+                        metric,
+                        unit,
+                        ldt,
+                        s.samples,
+                        s.median,
+                        s.low,
+                        s.high
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+    out
+}
+
+// A line chart of STIR and FRI against the log of the degree, with the confidence intervals as
+// error bars
+fn svg(title: &str, degrees: &BTreeMap<usize, Samples>) -> String {
+    const WIDTH: f64 = 480.;
+    const HEIGHT: f64 = 300.;
+    const LEFT: f64 = 70.;
+    const RIGHT: f64 = 20.;
+    const TOP: f64 = 30.;
+    const BOTTOM: f64 = 50.;
+
+    let [stir, fri] = [0, 1].map(|i| -> Vec<(f64, Summary)> {
+        degrees
+            .iter()
+            .map(|(degree, samples)| {
+                let (_, samples) = samples.by_ldt()[i];
+                (degree.ilog2() as f64, summarize(samples))
+            })
+            .collect()
+    });
+    let series = [("STIR", "#1f77b4", stir), ("FRI", "#d62728", fri)];
+
+    let log_degrees: Vec<f64> = degrees.keys().map(|d| d.ilog2() as f64).collect();
+    let (x_min, x_max) = (log_degrees[0], log_degrees[log_degrees.len() - 1]);
+    let (x_min, x_max) = if x_min == x_max {
+        (x_min - 1., x_max + 1.)
+    } else {
+        (x_min, x_max)
+    };
+    let y_max = series
+        .iter()
+        .flat_map(|(_, _, points)| points.iter().map(|(_, s)| s.high))
+        .fold(0., f64::max)
+        .max(f64::MIN_POSITIVE)
+        * 1.1;
+
+    let x = |v: f64| LEFT + (v - x_min) / (x_max - x_min) * (WIDTH - LEFT - RIGHT);
+    let y = |v: f64| HEIGHT - BOTTOM - v / y_max * (HEIGHT - TOP - BOTTOM);
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" font-family="sans-serif" font-size="11">"#
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<text x="{}" y="18" text-anchor="middle" font-size="13">{}</text>"#,
+        WIDTH / 2.,
+        title
+    )
+    .unwrap();
+
+    // Axes, with a tick at each degree and five along the metric
+    writeln!(
+        out,
+        r#"<path d="M{LEFT},{TOP} V{} H{}" fill="none" stroke="black"/>"#,
+        HEIGHT - BOTTOM,
+        WIDTH - RIGHT
+    )
+    .unwrap();
+    for &d in &log_degrees {
+        writeln!(
+            out,
+            r#"<text x="{:.1}" y="{}" text-anchor="middle">2^{}</text>"#,
+            x(d),
+            HEIGHT - BOTTOM + 16.,
+            d
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        r#"<text x="{}" y="{}" text-anchor="middle">Degree</text>"#,
+        (LEFT + WIDTH - RIGHT) / 2.,
+        HEIGHT - 10.
+    )
+    .unwrap();
+    for i in 0..=5 {
+        let v = y_max * i as f64 / 5.;
+        writeln!(
+            out,
+            r##"<line x1="{LEFT}" x2="{}" y1="{:.1}" y2="{:.1}" stroke="#ddd"/><text x="{}" y="{:.1}" text-anchor="end">{}</text>"##,
+            WIDTH - RIGHT,
+            y(v),
+            y(v),
+            LEFT - 6.,
+            y(v) + 4.,
+            format_value(v)
+        )
+        .unwrap();
+    }
+
+    for (i, (name, color, points)) in series.iter().enumerate() {
+        let path: Vec<String> = points
+            .iter()
+            .map(|(d, s)| format!("{:.1},{:.1}", x(*d), y(s.median)))
+            .collect();
+        writeln!(
+            out,
+            r#"<polyline points="{}" fill="none" stroke="{color}"/>"#,
+            path.join(" ")
+        )
+        .unwrap();
+        for (d, s) in points {
+            writeln!(
+                out,
+                r#"<line x1="{0:.1}" x2="{0:.1}" y1="{1:.1}" y2="{2:.1}" stroke="{color}"/><circle cx="{0:.1}" cy="{3:.1}" r="3" fill="{color}"/>"#,
+                x(*d),
+                y(s.low),
+                y(s.high),
+                y(s.median)
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="10" height="10" fill="{color}"/><text x="{}" y="{}">{}</text>"#,
+            LEFT + 10.,
+            TOP + 4. + 16. * i as f64,
+            LEFT + 24.,
+            TOP + 13. + 16. * i as f64,
+            name
+        )
+        .unwrap();
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+// Horizontal bars of the medians of STIR and FRI at each degree
+fn ascii(title: &str, degrees: &BTreeMap<usize, Samples>) -> String {
+    const WIDTH: f64 = 40.;
+
+    let rows: Vec<(usize, Summary, Summary)> = degrees
+        .iter()
+        .map(|(degree, samples)| (*degree, summarize(&samples.stir), summarize(&samples.fri)))
+        .collect();
+    let max = rows
+        .iter()
+        .map(|(_, stir, fri)| stir.median.max(fri.median))
+        .fold(0., f64::max)
+        .max(f64::MIN_POSITIVE);
+
+    let mut out = format!("{}\n", title);
+    for (degree, stir, fri) in rows {
+        for (name, s) in [("STIR", stir), ("FRI", fri)] {
+            let bar = "#".repeat((s.median / max * WIDTH).round() as usize);
+            writeln!(
+                out,
+                "  2^{:<3} {:<4} {:<40} {} [{}, {}]",
+                degree.ilog2(),
+                name,
+                bar,
+                format_value(s.median),
+                format_value(s.low),
+                format_value(s.high)
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let prover: Vec<ProverRecord> = read_records(&args.prover);
+    let verifier: Vec<VerifierRecord> = read_records(&args.verifier);
+    let table = tabulate(&prover, &verifier);
+
+    std::fs::create_dir_all(&args.out_dir).unwrap();
+    std::fs::write(args.out_dir.join("report.csv"), csv(&table)).unwrap();
+
+    for (c, metrics) in &table {
+        println!("=========================================");
+        println!("{}", c);
+        for ((metric, unit), degrees) in reported(metrics) {
+            let title = format!("{} ({})", metric, unit);
+            std::fs::write(
+                args.out_dir.join(format!("{}_{}.svg", c.slug(), metric)),
+                svg(&title, degrees),
+            )
+            .unwrap();
+            println!("{}", ascii(&title, degrees));
+        }
+    }
+
+    println!("Wrote the report to {}", args.out_dir.display());
+}
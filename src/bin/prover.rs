@@ -1,5 +1,5 @@
 use std::{
-    fs::OpenOptions,
+    collections::HashMap,
     time::{Duration, Instant},
 };
 
@@ -9,18 +9,14 @@ use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
 use ark_serialize::CanonicalSerialize;
 
 use stir::{
-    crypto::{
-        fields,
-        fs::{
-            self,
-            blake3::{Blake3Config, Sponge},
-        },
-        merkle_tree::{self, HashCounter},
-    },
+    crypto::backends::BackendArgs,
     fri::Fri,
     ldt::{LowDegreeTest, Prover},
+    metrics,
     parameters::{Parameters, SoundnessType},
     stir::Stir,
+    sweep::{Point, Progress, SweepArgs},
+    with_backends,
 };
 
 use clap::Parser;
@@ -51,10 +47,18 @@ struct Args {
 
     #[arg(long = "fk", default_value = "8")]
     fri_folding_factor: usize,
+
+    #[command(flatten)]
+    backends: BackendArgs,
+
+    #[command(flatten)]
+    sweep: SweepArgs,
 }
 
 #[derive(Debug, Serialize)]
 struct ProverOutput {
+    #[serde(flatten)]
+    backends: BackendArgs,
     security_level: usize,
     protocol_security_level: usize,
     starting_degree: usize,
@@ -72,156 +76,171 @@ struct ProverOutput {
 }
 
 fn main() {
-    type F = fields::Field192;
-    use merkle_tree::sha3 as merkle_tree;
     let soundness_type = SoundnessType::Conjecture;
 
     let args = Args::parse();
-
-    let security_level = args.security_level;
-    let protocol_security_level = args.protocol_security_level;
-    let starting_degree = 1 << args.initial_degree;
-    let stopping_degree = 1 << args.final_degree;
-    let starting_rate = args.rate;
-    let reps = args.verifier_repetitions;
-
-    let mut rng = ark_std::test_rng();
-    let poly = DensePolynomial::<F>::rand(starting_degree - 1, &mut rng);
-
-    let fiat_shamir_config: Blake3Config = fs::blake3::default_fs_config();
-
-    std::fs::create_dir_all("artifacts").unwrap();
-    std::fs::create_dir_all("outputs").unwrap();
-
-    // STIR
-    let (stir_prover_time, stir_prover_hashes, stir_argument_size) = {
-        println!("=========================================");
-        println!("STIR - Shaken");
-        let (leaf_hash_params, two_to_one_params) =
-            merkle_tree::default_config::<F>(&mut rng, args.stir_folding_factor);
-        let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> = Parameters {
-            security_level,
-            protocol_security_level,
-            starting_degree,
-            stopping_degree,
-            folding_factor: args.stir_folding_factor,
-            starting_rate,
-            soundness_type,
-
-            leaf_hash_params,
-            two_to_one_params,
-            fiat_shamir_config,
-            _field: Default::default(),
-        };
-
-        Stir::display(params.clone());
-
-        let stir_prover_time = Instant::now();
-        let (prover, _) = Stir::instantiate(params);
-        let (commitment, witness) = prover.commit(poly.clone());
-
-        let proof = prover.prove(witness);
-        let stir_prover_time = stir_prover_time.elapsed();
-        let stir_prover_hashes = HashCounter::get();
-        HashCounter::reset();
-        dbg!(stir_prover_time);
-        dbg!(stir_prover_hashes);
-
-        let mut serialized_bytes = vec![];
-        (commitment, proof)
-            .serialize_compressed(&mut serialized_bytes)
-            .unwrap();
-
-        let stir_argument_size = serialized_bytes.len();
-
-        for i in 0..reps {
-            std::fs::write(
-                format!("artifacts/stir_proof{}", i),
-                serialized_bytes.clone(),
-            )
-            .unwrap();
-        }
-
-        (
-            stir_prover_time,
-            stir_prover_hashes,
-            stir_argument_size,
-        )
+    println!("{}", args.backends);
+
+    let points = if args.sweep.sweep {
+        args.sweep.points()
+    } else {
+        vec![Point {
+            security_level: args.security_level,
+            initial_degree: args.initial_degree,
+            rate: args.rate,
+            stir_folding_factor: args.stir_folding_factor,
+            fri_folding_factor: args.fri_folding_factor,
+        }]
     };
-
-    //FRI
-    let (fri_prover_time, fri_prover_hashes, fri_argument_size) = {
-        println!("=========================================");
-        println!("FRI");
-        let (leaf_hash_params, two_to_one_params) =
-            merkle_tree::default_config::<F>(&mut rng, args.fri_folding_factor);
-        let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> = Parameters {
-            security_level,
-            protocol_security_level,
-            starting_degree,
-            stopping_degree,
-            folding_factor: args.fri_folding_factor,
-            starting_rate,
-            soundness_type,
-
-            leaf_hash_params,
-            two_to_one_params,
-            fiat_shamir_config,
-            _field: Default::default(),
-        };
-
-        Fri::display(params.clone());
-
-        let fri_prover_time = Instant::now();
-        let (prover, _) = Fri::instantiate(params);
-        let (commitment, witness) = prover.commit(poly);
-
-        let proof = prover.prove(witness);
-        let fri_prover_time = fri_prover_time.elapsed();
-        let fri_prover_hashes = HashCounter::get();
-        HashCounter::reset();
-        dbg!(fri_prover_time);
-        dbg!(fri_prover_hashes);
-
-        let mut serialized_bytes = vec![];
-        (commitment, proof)
-            .serialize_compressed(&mut serialized_bytes)
-            .unwrap();
-
-        let fri_argument_size = serialized_bytes.len();
-
-        for i in 0..reps {
-            std::fs::write(
-                format!("artifacts/fri_proof{}", i),
-                serialized_bytes.clone(),
-            )
-            .unwrap();
+    let mut out_file = args.sweep.output("prover");
+
+    with_backends!(
+        args.backends,
+        |F, merkle_tree, Sponge, fiat_shamir_config| {
+            let protocol_security_level = args.protocol_security_level;
+            let stopping_degree = 1 << args.final_degree;
+            let reps = args.verifier_repetitions;
+
+            // The hash parameters for each folding factor, set up once for the whole sweep
+            let mut hash_params = HashMap::new();
+            let mut hash_params_for = |folding_factor: usize| {
+                hash_params
+                    .entry(folding_factor)
+                    .or_insert_with(|| {
+                        merkle_tree::default_config::<F>(&mut ark_std::test_rng(), folding_factor)
+                    })
+                    .clone()
+            };
+
+            let mut progress = Progress::new(points.len());
+            for point in &points {
+                progress.start(point);
+
+                let security_level = point.security_level;
+                let starting_degree = 1 << point.initial_degree;
+                let starting_rate = point.rate;
+
+                let mut rng = ark_std::test_rng();
+                let poly = DensePolynomial::<F>::rand(starting_degree - 1, &mut rng);
+
+                let artifacts = args.sweep.artifacts_dir(point);
+                std::fs::create_dir_all(&artifacts).unwrap();
+
+                // STIR
+                let (stir_prover_time, stir_prover_hashes, stir_argument_size) = {
+                    println!("=========================================");
+                    println!("STIR - Shaken");
+                    let (leaf_hash_params, two_to_one_params) =
+                        hash_params_for(point.stir_folding_factor);
+                    let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> =
+                        Parameters {
+                            security_level,
+                            protocol_security_level,
+                            starting_degree,
+                            stopping_degree,
+                            folding_factor: point.stir_folding_factor,
+                            starting_rate,
+                            soundness_type,
+
+                            leaf_hash_params,
+                            two_to_one_params,
+                            fiat_shamir_config: fiat_shamir_config.clone(),
+                            _field: Default::default(),
+                        };
+
+                    Stir::display(params.clone());
+
+                    let stir_prover_time = Instant::now();
+                    let (prover, _) = Stir::instantiate(params);
+                    let ((commitment, proof), prover_counters) = metrics::with_counter(|| {
+                        let (commitment, witness) = prover.commit(poly.clone());
+                        (commitment, prover.prove(witness))
+                    });
+                    let stir_prover_time = stir_prover_time.elapsed();
+                    let stir_prover_hashes = prover_counters.hashes();
+                    dbg!(stir_prover_time);
+                    dbg!(stir_prover_hashes);
+
+                    let mut serialized_bytes = vec![];
+                    (commitment, proof)
+                        .serialize_compressed(&mut serialized_bytes)
+                        .unwrap();
+
+                    let stir_argument_size = serialized_bytes.len();
+
+                    std::fs::write(artifacts.join("stir_proof"), serialized_bytes).unwrap();
+
+                    (stir_prover_time, stir_prover_hashes, stir_argument_size)
+                };
+
+                //FRI
+                let (fri_prover_time, fri_prover_hashes, fri_argument_size) = {
+                    println!("=========================================");
+                    println!("FRI");
+                    let (leaf_hash_params, two_to_one_params) =
+                        hash_params_for(point.fri_folding_factor);
+                    let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> =
+                        Parameters {
+                            security_level,
+                            protocol_security_level,
+                            starting_degree,
+                            stopping_degree,
+                            folding_factor: point.fri_folding_factor,
+                            starting_rate,
+                            soundness_type,
+
+                            leaf_hash_params,
+                            two_to_one_params,
+                            fiat_shamir_config: fiat_shamir_config.clone(),
+                            _field: Default::default(),
+                        };
+
+                    Fri::display(params.clone());
+
+                    let fri_prover_time = Instant::now();
+                    let (prover, _) = Fri::instantiate(params);
+                    let ((commitment, proof), prover_counters) = metrics::with_counter(|| {
+                        let (commitment, witness) = prover.commit(poly);
+                        (commitment, prover.prove(witness))
+                    });
+                    let fri_prover_time = fri_prover_time.elapsed();
+                    let fri_prover_hashes = prover_counters.hashes();
+                    dbg!(fri_prover_time);
+                    dbg!(fri_prover_hashes);
+
+                    let mut serialized_bytes = vec![];
+                    (commitment, proof)
+                        .serialize_compressed(&mut serialized_bytes)
+                        .unwrap();
+
+                    let fri_argument_size = serialized_bytes.len();
+
+                    std::fs::write(artifacts.join("fri_proof"), serialized_bytes).unwrap();
+                    (fri_prover_time, fri_prover_hashes, fri_argument_size)
+                };
+
+                let output = ProverOutput {
+                    backends: args.backends,
+                    security_level,
+                    protocol_security_level,
+                    starting_degree,
+                    stopping_degree,
+                    starting_rate,
+                    repetitions: reps,
+                    stir_folding_factor: point.stir_folding_factor,
+                    fri_folding_factor: point.fri_folding_factor,
+                    stir_prover_time,
+                    fri_prover_time,
+                    stir_prover_hashes,
+                    fri_prover_hashes,
+                    stir_argument_size,
+                    fri_argument_size,
+                };
+
+                use std::io::Write;
+                writeln!(out_file, "{}", serde_json::to_string(&output).unwrap()).unwrap();
+            }
+            progress.finish();
         }
-        (fri_prover_time, fri_prover_hashes, fri_argument_size)
-    };
-
-    let output = ProverOutput {
-        security_level,
-        protocol_security_level,
-        starting_degree,
-        stopping_degree,
-        starting_rate,
-        repetitions: reps,
-        stir_folding_factor: args.stir_folding_factor,
-        fri_folding_factor: args.fri_folding_factor,
-        stir_prover_time,
-        fri_prover_time,
-        stir_prover_hashes,
-        fri_prover_hashes,
-        stir_argument_size,
-        fri_argument_size,
-    };
-
-    let mut out_file = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open("outputs/prover_output.json")
-        .unwrap();
-    use std::io::Write;
-    writeln!(out_file, "{}", serde_json::to_string(&output).unwrap()).unwrap();
+    )
 }
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+
+use stir::{
+    crypto::{fields, fs, merkle_tree},
+    evm::{self, calldata, solidity, EvmParameters},
+    ldt::{Prover, Verifier},
+    parameters::{Parameters, SoundnessType},
+    stir::{
+        parameters::{FinalPhase, FullParameters},
+        prover::StirProver,
+        verifier::StirVerifier,
+    },
+};
+
+use clap::Parser;
+
+// Generates a Solidity verifier for STIR proofs with the given parameters, over Field256 with
+// Keccak-256 Merkle trees and transcript, together with the calldata of a sample proof
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[arg(short = 'l', long, default_value = "128")]
+    security_level: usize,
+
+    #[arg(short = 'p', long, default_value = "106")]
+    protocol_security_level: usize,
+
+    #[arg(short = 'd', long, default_value = "20")]
+    initial_degree: usize,
+
+    #[arg(short = 'f', long, default_value = "6")]
+    final_degree: usize,
+
+    #[arg(short = 'r', long, default_value = "2")]
+    rate: usize,
+
+    #[arg(short = 'k', long, default_value = "16")]
+    folding_factor: usize,
+
+    // Where to write StirVerifier.sol and stir_calldata.hex
+    #[arg(short = 'o', long, default_value = "artifacts")]
+    out_dir: PathBuf,
+}
+
+fn main() {
+    type F = fields::Field256;
+
+    let args = Args::parse();
+
+    let starting_degree = 1 << args.initial_degree;
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) =
+        merkle_tree::keccak::default_config::<F>(&mut rng, args.folding_factor);
+    // The contract checks the final polynomial in coefficient form
+    let params: EvmParameters = FullParameters::from(Parameters {
+        security_level: args.security_level,
+        protocol_security_level: args.protocol_security_level,
+        starting_degree,
+        stopping_degree: 1 << args.final_degree,
+        folding_factor: args.folding_factor,
+        starting_rate: args.rate,
+        soundness_type: SoundnessType::Conjecture,
+
+        leaf_hash_params,
+        two_to_one_params,
+        fiat_shamir_config: fs::keccak::default_fs_config(),
+        _field: Default::default(),
+    })
+    .with_final_phase(FinalPhase::Coefficients);
+    println!("{}", params);
+
+    std::fs::create_dir_all(&args.out_dir).unwrap();
+    let contract_path = args.out_dir.join("StirVerifier.sol");
+    std::fs::write(
+        &contract_path,
+        solidity::generate_verifier(&params).unwrap(),
+    )
+    .unwrap();
+    println!("Wrote {}", contract_path.display());
+
+    let prover = StirProver::new_full(params.clone());
+    let verifier = StirVerifier::new_full(params.clone());
+    let poly = DensePolynomial::<F>::rand(starting_degree - 1, &mut rng);
+    let (commitment, witness) = prover.commit(poly);
+    let proof = prover.prove(witness);
+    assert!(verifier.verify(&commitment, &proof));
+
+    let calldata = calldata::encode_calldata(&params, &commitment, &proof).unwrap();
+    let calldata_path = args.out_dir.join("stir_calldata.hex");
+    std::fs::write(&calldata_path, format!("0x{}\n", evm::hex(&calldata))).unwrap();
+    // Transaction data costs 4 gas per zero byte and 16 per other byte
+    let calldata_gas: usize = calldata
+        .iter()
+        .map(|&byte| if byte == 0 { 4 } else { 16 })
+        .sum();
+    println!(
+        "Wrote {}: {} bytes, {} gas of transaction data",
+        calldata_path.display(),
+        calldata.len(),
+        calldata_gas
+    );
+}
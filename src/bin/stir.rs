@@ -4,17 +4,12 @@ use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
 use ark_serialize::CanonicalSerialize;
 
 use stir::{
-    crypto::{
-        fields,
-        fs::{
-            self,
-            blake3::{Blake3Config, Sponge},
-        },
-        merkle_tree::{self, HashCounter},
-    },
-    ldt::{LowDegreeTest, Prover, Verifier},
+    crypto::backends::BackendArgs,
+    ldt::{LowDegreeTest, Prover},
+    metrics,
     parameters::{Parameters, SoundnessType},
     stir::Stir,
+    with_backends,
 };
 
 use clap::Parser;
@@ -42,67 +37,83 @@ struct Args {
 
     #[arg(short = 'k', long, default_value = "16")]
     folding_factor: usize,
+
+    // Prints the hashes, field operations and proof of work checks of a verification, per round
+    #[arg(long)]
+    cost_report: bool,
+
+    #[command(flatten)]
+    backends: BackendArgs,
 }
 
 fn main() {
-    type F = fields::Field192;
-    use merkle_tree::sha3 as merkle_tree;
-
     let args = Args::parse();
-
-    let security_level = args.security_level;
-    let protocol_security_level = args.protocol_security_level;
-    let starting_degree = 1 << args.initial_degree;
-    let stopping_degree = 1 << args.final_degree;
-    let starting_rate = args.rate;
-    let folding_factor = args.folding_factor;
-    let soundness_type = SoundnessType::Conjecture;
-    let reps = args.verifier_repetitions;
-
-    let mut rng = ark_std::test_rng();
-    let poly = DensePolynomial::<F>::rand(starting_degree - 1, &mut rng);
-
-    let (leaf_hash_params, two_to_one_params) =
-        merkle_tree::default_config::<F>(&mut rng, folding_factor);
-    let fiat_shamir_config: Blake3Config = fs::blake3::default_fs_config();
-
-    println!("=========================================");
-    println!("STIR");
-    let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> = Parameters {
-        security_level,
-        protocol_security_level,
-        starting_degree,
-        stopping_degree,
-        folding_factor,
-        starting_rate,
-        soundness_type,
-
-        leaf_hash_params: leaf_hash_params.clone(),
-        two_to_one_params: two_to_one_params.clone(),
-        fiat_shamir_config,
-        _field: Default::default(),
-    };
-
-    Stir::display(params.clone());
-
-    let stir_prover_time = Instant::now();
-    let (prover, verifier) = Stir::instantiate(params);
-    let (commitment, witness) = prover.commit(poly.clone());
-
-    let proof = prover.prove(witness);
-    dbg!(stir_prover_time.elapsed());
-    dbg!(proof.serialized_size(ark_serialize::Compress::Yes));
-    let prover_hashes = HashCounter::get();
-    dbg!(prover_hashes);
-    HashCounter::reset();
-
-    let stir_verifier_time = Instant::now();
-    for _ in 0..reps {
-        let result = verifier.verify(&commitment, &proof);
-        assert!(result);
-    }
-    dbg!(stir_verifier_time.elapsed());
-    let verifier_hashes = HashCounter::get() / reps;
-    dbg!(verifier_hashes);
-    HashCounter::reset();
+    println!("{}", args.backends);
+
+    with_backends!(
+        args.backends,
+        |F, merkle_tree, Sponge, fiat_shamir_config| {
+            let security_level = args.security_level;
+            let protocol_security_level = args.protocol_security_level;
+            let starting_degree = 1 << args.initial_degree;
+            let stopping_degree = 1 << args.final_degree;
+            let starting_rate = args.rate;
+            let folding_factor = args.folding_factor;
+            let soundness_type = SoundnessType::Conjecture;
+            let reps = args.verifier_repetitions;
+
+            let mut rng = ark_std::test_rng();
+            let poly = DensePolynomial::<F>::rand(starting_degree - 1, &mut rng);
+
+            let (leaf_hash_params, two_to_one_params) =
+                merkle_tree::default_config::<F>(&mut rng, folding_factor);
+
+            println!("=========================================");
+            println!("STIR");
+            let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> = Parameters {
+                security_level,
+                protocol_security_level,
+                starting_degree,
+                stopping_degree,
+                folding_factor,
+                starting_rate,
+                soundness_type,
+
+                leaf_hash_params: leaf_hash_params.clone(),
+                two_to_one_params: two_to_one_params.clone(),
+                fiat_shamir_config,
+                _field: Default::default(),
+            };
+
+            Stir::display(params.clone());
+
+            let stir_prover_time = Instant::now();
+            let (prover, verifier) = Stir::instantiate(params);
+            let ((commitment, proof), prover_counters) = metrics::with_counter(|| {
+                let (commitment, witness) = prover.commit(poly.clone());
+                (commitment, prover.prove(witness))
+            });
+            dbg!(stir_prover_time.elapsed());
+            dbg!(proof.serialized_size(ark_serialize::Compress::Yes));
+            let prover_hashes = prover_counters.hashes();
+            dbg!(prover_hashes);
+
+            let verifier = verifier.prepare();
+            let stir_verifier_time = Instant::now();
+            let (_, verifier_counters) = metrics::with_counter(|| {
+                for _ in 0..reps {
+                    let result = verifier.verify_prepared(&commitment, &proof);
+                    assert!(result);
+                }
+            });
+            dbg!(stir_verifier_time.elapsed());
+            let verifier_hashes = verifier_counters.hashes() / reps;
+            dbg!(verifier_hashes);
+
+            if args.cost_report {
+                let (_, report) = verifier.verify_prepared_with_cost_report(&commitment, &proof);
+                println!("{}", report);
+            }
+        }
+    )
 }
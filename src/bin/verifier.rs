@@ -1,23 +1,19 @@
 use std::{
-    fs::OpenOptions,
+    collections::HashMap,
     time::{Duration, Instant},
 };
 
 use serde::Serialize;
 
 use stir::{
-    crypto::{
-        fields,
-        fs::{
-            self,
-            blake3::{Blake3Config, Sponge},
-        },
-        merkle_tree::{self, HashCounter},
-    },
+    crypto::backends::BackendArgs,
     fri::Fri,
     ldt::{LowDegreeTest, Verifier},
+    metrics,
     parameters::{Parameters, SoundnessType},
     stir::Stir,
+    sweep::{Point, Progress, SweepArgs},
+    with_backends,
 };
 
 use clap::Parser;
@@ -48,10 +44,18 @@ struct Args {
 
     #[arg(long = "fk", default_value = "8")]
     fri_folding_factor: usize,
+
+    #[command(flatten)]
+    backends: BackendArgs,
+
+    #[command(flatten)]
+    sweep: SweepArgs,
 }
 
 #[derive(Debug, Serialize)]
 struct VerifierOutput {
+    #[serde(flatten)]
+    backends: BackendArgs,
     security_level: usize,
     protocol_security_level: usize,
     starting_degree: usize,
@@ -67,141 +71,178 @@ struct VerifierOutput {
 }
 
 fn main() {
-    type F = fields::Field192;
-    use merkle_tree::sha3 as merkle_tree;
-
     let args = Args::parse();
-
-    let security_level = args.security_level;
-    let protocol_security_level = args.protocol_security_level;
-    let starting_degree = 1 << args.initial_degree;
-    let stopping_degree = 1 << args.final_degree;
-    let starting_rate = args.rate;
-    let soundness_type = SoundnessType::Conjecture;
-    let reps = args.verifier_repetitions;
-
-    let mut rng = ark_std::test_rng();
-    let fiat_shamir_config: Blake3Config = fs::blake3::default_fs_config();
-
-    // STIR
-    let (stir_verifier_time, stir_verifier_hashes) = {
-        println!("=========================================");
-        println!("STIR - Shaken");
-        let (leaf_hash_params, two_to_one_params) =
-            merkle_tree::default_config::<F>(&mut rng, args.stir_folding_factor);
-        let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> = Parameters {
-            security_level,
-            protocol_security_level,
-            starting_degree,
-            stopping_degree,
-            folding_factor: args.stir_folding_factor,
-            starting_rate,
-            soundness_type,
-
-            leaf_hash_params,
-            two_to_one_params,
-            fiat_shamir_config,
-            _field: Default::default(),
-        };
-
-        Stir::display(params.clone());
-
-        let (_, verifier) = Stir::instantiate(params);
-
-        let mut proofs = vec![];
-        for i in 0..reps {
-            let file_contents = std::fs::read(format!("artifacts/stir_proof{}", i)).unwrap();
-            let (commitment, proof): (
-                stir::stir::common::Commitment<merkle_tree::MerkleTreeParams<F>>,
-                stir::stir::common::Proof<F, merkle_tree::MerkleTreeParams<F>>,
-            ) = ark_serialize::CanonicalDeserialize::deserialize_compressed(
-                &mut &file_contents[..],
-            )
-            .unwrap();
-            proofs.push((commitment, proof));
-        }
-
-        let stir_verifier_time = Instant::now();
-        for (commitment, proof) in proofs {
-            verifier.verify(&commitment, &proof);
-        }
-        let stir_verifier_time = stir_verifier_time.elapsed() / reps as u32;
-        let stir_verifier_hashes = HashCounter::get() / reps;
-        HashCounter::reset();
-        println!("STIR verifier time: {:?}", stir_verifier_time);
-        println!("STIR verifier hashes: {:?}", stir_verifier_hashes);
-        (stir_verifier_time, stir_verifier_hashes)
+    println!("{}", args.backends);
+
+    let points = if args.sweep.sweep {
+        args.sweep.points()
+    } else {
+        vec![Point {
+            security_level: args.security_level,
+            initial_degree: args.initial_degree,
+            rate: args.rate,
+            stir_folding_factor: args.stir_folding_factor,
+            fri_folding_factor: args.fri_folding_factor,
+        }]
     };
-
-    //FRI
-    let (fri_verifier_time, fri_verifier_hashes) = {
-        println!("=========================================");
-        println!("FRI");
-        let (leaf_hash_params, two_to_one_params) =
-            merkle_tree::default_config::<F>(&mut rng, args.fri_folding_factor);
-        let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> = Parameters {
-            security_level,
-            protocol_security_level,
-            starting_degree,
-            stopping_degree,
-            folding_factor: args.fri_folding_factor,
-            starting_rate,
-            soundness_type,
-
-            leaf_hash_params,
-            two_to_one_params,
-            fiat_shamir_config,
-            _field: Default::default(),
-        };
-
-        Fri::display(params.clone());
-
-        let (_, verifier) = Fri::instantiate(params);
-
-        let mut proofs = vec![];
-        for i in 0..reps {
-            let file_contents = std::fs::read(format!("artifacts/fri_proof{}", i)).unwrap();
-            let (commitment, proof): (
-                stir::fri::common::Commitment<merkle_tree::MerkleTreeParams<F>>,
-                stir::fri::common::Proof<F, merkle_tree::MerkleTreeParams<F>>,
-            ) = ark_serialize::CanonicalDeserialize::deserialize_compressed(
-                &mut &file_contents[..],
-            )
-            .unwrap();
-            proofs.push((commitment, proof));
-        }
-
-        let fri_verifier_time = Instant::now();
-        for (commitment, proof) in proofs {
-            verifier.verify(&commitment, &proof);
+    let mut out_file = args.sweep.output("verifier");
+
+    with_backends!(
+        args.backends,
+        |F, merkle_tree, Sponge, fiat_shamir_config| {
+            let protocol_security_level = args.protocol_security_level;
+            let stopping_degree = 1 << args.final_degree;
+            let soundness_type = SoundnessType::Conjecture;
+            let reps = args.verifier_repetitions;
+
+            // The hash parameters for each folding factor, set up once for the whole sweep
+            let mut hash_params = HashMap::new();
+            let mut hash_params_for = |folding_factor: usize| {
+                hash_params
+                    .entry(folding_factor)
+                    .or_insert_with(|| {
+                        merkle_tree::default_config::<F>(&mut ark_std::test_rng(), folding_factor)
+                    })
+                    .clone()
+            };
+
+            let mut progress = Progress::new(points.len());
+            for point in &points {
+                progress.start(point);
+
+                let security_level = point.security_level;
+                let starting_degree = 1 << point.initial_degree;
+                let starting_rate = point.rate;
+
+                let artifacts = args.sweep.artifacts_dir(point);
+
+                // STIR
+                let (stir_verifier_time, stir_verifier_hashes) = {
+                    println!("=========================================");
+                    println!("STIR - Shaken");
+                    let (leaf_hash_params, two_to_one_params) =
+                        hash_params_for(point.stir_folding_factor);
+                    let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> =
+                        Parameters {
+                            security_level,
+                            protocol_security_level,
+                            starting_degree,
+                            stopping_degree,
+                            folding_factor: point.stir_folding_factor,
+                            starting_rate,
+                            soundness_type,
+
+                            leaf_hash_params,
+                            two_to_one_params,
+                            fiat_shamir_config: fiat_shamir_config.clone(),
+                            _field: Default::default(),
+                        };
+
+                    Stir::display(params.clone());
+
+                    let (_, verifier) = Stir::instantiate(params);
+                    let verifier = verifier.prepare();
+
+                    // Each repetition verifies its own copy of the proof
+                    let file_contents = std::fs::read(artifacts.join("stir_proof")).unwrap();
+                    let mut proofs = vec![];
+                    for _ in 0..reps {
+                        let (commitment, proof): (
+                            stir::stir::common::Commitment<merkle_tree::MerkleTreeParams<F>>,
+                            stir::stir::common::Proof<F, merkle_tree::MerkleTreeParams<F>>,
+                        ) = ark_serialize::CanonicalDeserialize::deserialize_compressed(
+                            &mut &file_contents[..],
+                        )
+                        .unwrap();
+                        proofs.push((commitment, proof));
+                    }
+
+                    let stir_verifier_time = Instant::now();
+                    let (_, stir_verifier_counters) = metrics::with_counter(|| {
+                        for (commitment, proof) in proofs {
+                            verifier.verify_prepared(&commitment, &proof);
+                        }
+                    });
+                    let stir_verifier_time = stir_verifier_time.elapsed() / reps as u32;
+                    let stir_verifier_hashes = stir_verifier_counters.hashes() / reps;
+                    println!("STIR verifier time: {:?}", stir_verifier_time);
+                    println!("STIR verifier hashes: {:?}", stir_verifier_hashes);
+                    (stir_verifier_time, stir_verifier_hashes)
+                };
+
+                //FRI
+                let (fri_verifier_time, fri_verifier_hashes) = {
+                    println!("=========================================");
+                    println!("FRI");
+                    let (leaf_hash_params, two_to_one_params) =
+                        hash_params_for(point.fri_folding_factor);
+                    let params: Parameters<F, merkle_tree::MerkleTreeParams<F>, Sponge> =
+                        Parameters {
+                            security_level,
+                            protocol_security_level,
+                            starting_degree,
+                            stopping_degree,
+                            folding_factor: point.fri_folding_factor,
+                            starting_rate,
+                            soundness_type,
+
+                            leaf_hash_params,
+                            two_to_one_params,
+                            fiat_shamir_config: fiat_shamir_config.clone(),
+                            _field: Default::default(),
+                        };
+
+                    Fri::display(params.clone());
+
+                    let (_, verifier) = Fri::instantiate(params);
+
+                    // Each repetition verifies its own copy of the proof
+                    let file_contents = std::fs::read(artifacts.join("fri_proof")).unwrap();
+                    let mut proofs = vec![];
+                    for _ in 0..reps {
+                        let (commitment, proof): (
+                            stir::fri::common::Commitment<merkle_tree::MerkleTreeParams<F>>,
+                            stir::fri::common::Proof<F, merkle_tree::MerkleTreeParams<F>>,
+                        ) = ark_serialize::CanonicalDeserialize::deserialize_compressed(
+                            &mut &file_contents[..],
+                        )
+                        .unwrap();
+                        proofs.push((commitment, proof));
+                    }
+
+                    let fri_verifier_time = Instant::now();
+                    let (_, fri_verifier_counters) = metrics::with_counter(|| {
+                        for (commitment, proof) in proofs {
+                            verifier.verify(&commitment, &proof);
+                        }
+                    });
+                    let fri_verifier_time = fri_verifier_time.elapsed() / reps as u32;
+                    let fri_verifier_hashes = fri_verifier_counters.hashes() / reps;
+                    println!("FRI verifier time: {:?}", fri_verifier_time);
+                    println!("FRI verifier hashes: {:?}", fri_verifier_hashes);
+                    (fri_verifier_time, fri_verifier_hashes)
+                };
+
+                let output = VerifierOutput {
+                    backends: args.backends,
+                    security_level,
+                    protocol_security_level,
+                    starting_degree,
+                    stopping_degree,
+                    starting_rate,
+                    stir_folding_factor: point.stir_folding_factor,
+                    fri_folding_factor: point.fri_folding_factor,
+                    repetitions: reps,
+                    stir_verifier_time,
+                    fri_verifier_time,
+                    stir_verifier_hashes,
+                    fri_verifier_hashes,
+                };
+
+                use std::io::Write;
+                writeln!(out_file, "{}", serde_json::to_string(&output).unwrap()).unwrap();
+            }
+            progress.finish();
         }
-        let fri_verifier_time = fri_verifier_time.elapsed() / reps as u32;
-        let fri_verifier_hashes = HashCounter::get() / reps;
-        println!("FRI verifier time: {:?}", fri_verifier_time);
-        println!("FRI verifier hashes: {:?}", fri_verifier_hashes);
-        (fri_verifier_time, fri_verifier_hashes)
-    };
-
-    let output = VerifierOutput {
-        security_level,
-        protocol_security_level,
-        starting_degree,
-        stopping_degree,
-        starting_rate,
-        stir_folding_factor: args.stir_folding_factor,
-        fri_folding_factor: args.fri_folding_factor,
-        repetitions: reps,
-        stir_verifier_time,
-        fri_verifier_time,
-        stir_verifier_hashes,
-        fri_verifier_hashes,
-    };
-
-    let mut out_file = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open("outputs/verifier_output.json")
-        .unwrap();
-    use std::io::Write;
-    writeln!(out_file, "{}", serde_json::to_string(&output).unwrap()).unwrap();
+    )
 }
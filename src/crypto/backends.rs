@@ -0,0 +1,136 @@
+// Runtime selection of the field, Merkle tree and Fiat-Shamir sponge in the binaries. Each
+// combination is a separate monomorphized instantiation, which with_backends! dispatches to.
+
+use std::fmt::Display;
+
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+pub enum FieldChoice {
+    #[value(name = "64")]
+    #[serde(rename = "64")]
+    Field64,
+    #[value(name = "128")]
+    #[serde(rename = "128")]
+    Field128,
+    #[value(name = "192")]
+    #[serde(rename = "192")]
+    Field192,
+    #[value(name = "256")]
+    #[serde(rename = "256")]
+    Field256,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MerkleChoice {
+    Blake2,
+    Blake3,
+    Sha3,
+    Poseidon,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FsChoice {
+    Blake3,
+    Poseidon,
+}
+
+#[derive(Debug, Clone, Copy, Args, Serialize)]
+pub struct BackendArgs {
+    #[arg(long, value_enum, default_value = "192")]
+    pub field: FieldChoice,
+
+    #[arg(long, value_enum, default_value = "sha3")]
+    pub merkle: MerkleChoice,
+
+    #[arg(long, value_enum, default_value = "blake3")]
+    pub fs: FsChoice,
+}
+
+fn name(value: impl ValueEnum) -> String {
+    value.to_possible_value().unwrap().get_name().to_string()
+}
+
+impl Display for BackendArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Field: {} bits, Merkle tree: {}, Fiat-Shamir: {}",
+            name(self.field),
+            name(self.merkle),
+            name(self.fs)
+        )
+    }
+}
+
+// Runs the body with the field, Merkle tree and sponge of the given BackendArgs. In the body, $F
+// is the field, $merkle_tree the module of crypto::merkle_tree, $Sponge the sponge and $fs_config
+// its configuration, as in
+//
+//     with_backends!(args.backends, |F, merkle_tree, Sponge, fiat_shamir_config| { ... })
+#[macro_export]
+macro_rules! with_backends {
+    ($backends:expr, |$F:ident, $merkle_tree:ident, $Sponge:ident, $fs_config:ident| $body:block) => {{
+        use $crate::crypto::backends::FieldChoice;
+        let backends: $crate::crypto::backends::BackendArgs = $backends;
+        match backends.field {
+            FieldChoice::Field64 => $crate::with_backends!(
+                @merkle backends, $crate::crypto::fields::Field64, |$F, $merkle_tree, $Sponge, $fs_config| $body
+            ),
+            FieldChoice::Field128 => $crate::with_backends!(
+                @merkle backends, $crate::crypto::fields::Field128, |$F, $merkle_tree, $Sponge, $fs_config| $body
+            ),
+            FieldChoice::Field192 => $crate::with_backends!(
+                @merkle backends, $crate::crypto::fields::Field192, |$F, $merkle_tree, $Sponge, $fs_config| $body
+            ),
+            FieldChoice::Field256 => $crate::with_backends!(
+                @merkle backends, $crate::crypto::fields::Field256, |$F, $merkle_tree, $Sponge, $fs_config| $body
+            ),
+        }
+    }};
+    (@merkle $backends:ident, $field:ty, |$F:ident, $merkle_tree:ident, $Sponge:ident, $fs_config:ident| $body:block) => {{
+        use $crate::crypto::backends::MerkleChoice;
+        match $backends.merkle {
+            MerkleChoice::Blake2 => $crate::with_backends!(
+                @fs $backends, $field, blake2, |$F, $merkle_tree, $Sponge, $fs_config| $body
+            ),
+            MerkleChoice::Blake3 => $crate::with_backends!(
+                @fs $backends, $field, blake3, |$F, $merkle_tree, $Sponge, $fs_config| $body
+            ),
+            MerkleChoice::Sha3 => $crate::with_backends!(
+                @fs $backends, $field, sha3, |$F, $merkle_tree, $Sponge, $fs_config| $body
+            ),
+            MerkleChoice::Poseidon => $crate::with_backends!(
+                @fs $backends, $field, poseidon, |$F, $merkle_tree, $Sponge, $fs_config| $body
+            ),
+        }
+    }};
+    (@fs $backends:ident, $field:ty, $merkle:ident, |$F:ident, $merkle_tree:ident, $Sponge:ident, $fs_config:ident| $body:block) => {{
+        use $crate::crypto::backends::FsChoice;
+        match $backends.fs {
+            FsChoice::Blake3 => {
+                type $F = $field;
+                use $crate::crypto::merkle_tree::$merkle as $merkle_tree;
+                type $Sponge = $crate::crypto::fs::blake3::Sponge;
+                let $fs_config = $crate::crypto::fs::blake3::default_fs_config();
+                // The body clones configurations and hash parameters, which are only Copy for
+                // some backends
+                #[allow(clippy::clone_on_copy)]
+                let output = $body;
+                output
+            }
+            FsChoice::Poseidon => {
+                type $F = $field;
+                use $crate::crypto::merkle_tree::$merkle as $merkle_tree;
+                type $Sponge = $crate::crypto::fs::poseidon::Sponge<$F>;
+                let $fs_config = $crate::crypto::fs::poseidon::default_fs_config::<$F>();
+                #[allow(clippy::clone_on_copy)]
+                let output = $body;
+                output
+            }
+        }
+    }};
+}
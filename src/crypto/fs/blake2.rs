@@ -0,0 +1,30 @@
+use blake2::Digest;
+
+use super::duplex::{DuplexHash, DuplexSponge};
+
+#[derive(Default, Clone, Copy)]
+pub struct Blake2Config;
+
+pub fn default_fs_config() -> Blake2Config {
+    Blake2Config
+}
+
+// Blake2s-256, matching the blake2 Merkle trees
+#[derive(Clone)]
+pub struct Blake2;
+
+impl DuplexHash for Blake2 {
+    type Config = Blake2Config;
+
+    const NAME: &'static [u8] = b"blake2s-256";
+
+    fn hash(inputs: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = blake2::Blake2s256::new();
+        for input in inputs {
+            hasher.update(input);
+        }
+        hasher.finalize().into()
+    }
+}
+
+pub type Sponge = DuplexSponge<Blake2>;
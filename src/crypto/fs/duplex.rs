@@ -0,0 +1,294 @@
+// Duplex sponge over a byte-oriented hash function, used for Fiat-Shamir.
+//
+// Transcript specification. The state is a 32 byte chaining value `cv` and a buffer `pending` of
+// bytes absorbed since the last squeeze. `H(x)` is the 32 byte hash of `x`, `XOF(x, n)` the first
+// `n` bytes of the extendable output on `x`, `||` is concatenation and `le64(n)` is `n` as a
+// little-endian u64. XOF is the native extendable output of the hash if it has one, and otherwise
+// H in counter mode: XOF(x, n) = H(x || le64(0)) || H(x || le64(1)) || ... truncated to n bytes.
+//
+// - new:        cv = H(INIT || DOMAIN_SEPARATOR || name), pending = []
+// - absorb(x):  pending = pending || le64(|b|) || b, where b = x.to_sponge_bytes()
+// - squeeze(n): if pending is not empty (absorb phase), first
+//                   cv = H(ABSORB || cv || pending), pending = []
+//               then output XOF(SQUEEZE || cv || le64(n), n) and ratchet
+//                   cv = H(RATCHET || cv)
+//
+// Every absorbed message is length prefixed, so the absorbed messages (and not only their
+// concatenation) are bound. The one byte tags separate the absorb, squeeze and ratchet calls to
+// the hash, and the ratchet makes consecutive squeezes independent and the previous states
+// unrecoverable from the current one.
+//
+// Field elements are sampled by wide reduction: an element of a field with a b-bit modulus is read
+// from ceil(b / 8) + 16 squeezed bytes, interpreted in little-endian and reduced modulo p, so that
+// the distance from uniform is at most 2^-128. Elements truncated to b bits are read from
+// ceil(b / 8) bytes with the excess bits cleared, which is exactly uniform. Bits are the
+// little-endian bits of ceil(n / 8) squeezed bytes.
+//
+// For crate::metrics, every evaluation of H and every 32 bytes of XOF output count as one
+// permutation.
+
+use std::marker::PhantomData;
+
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge, FieldElementSize};
+use ark_ff::PrimeField;
+
+use crate::metrics::{self, Metric};
+
+pub const DOMAIN_SEPARATOR: &[u8] = b"STIR duplex sponge v1";
+
+const INIT: u8 = 0;
+const ABSORB: u8 = 1;
+const SQUEEZE: u8 = 2;
+const RATCHET: u8 = 3;
+
+// Number of extra bytes used when reducing squeezed bytes to a field element
+const WIDE_REDUCTION_BYTES: usize = 16;
+
+pub trait DuplexHash: Clone {
+    type Config: Clone + Default;
+
+    const NAME: &'static [u8];
+
+    // H applied to the concatenation of inputs
+    fn hash(inputs: &[&[u8]]) -> [u8; 32];
+
+    // XOF applied to the concatenation of inputs, filling output.
+    // Defaults to H in counter mode, so that a verifier only needs to implement H.
+    fn xof(inputs: &[&[u8]], output: &mut [u8]) {
+        for (counter, chunk) in output.chunks_mut(32).enumerate() {
+            let counter = (counter as u64).to_le_bytes();
+            let mut block_inputs = inputs.to_vec();
+            block_inputs.push(&counter);
+            let block = Self::hash(&block_inputs);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DuplexSponge<H: DuplexHash> {
+    chaining_value: [u8; 32],
+    pending: Vec<u8>,
+    _hash: PhantomData<H>,
+}
+
+impl<H: DuplexHash> DuplexSponge<H> {
+    // The current chaining value, e.g. the initial one, which verifiers outside of this crate
+    // (see crate::evm) hardcode
+    pub fn chaining_value(&self) -> [u8; 32] {
+        self.chaining_value
+    }
+
+    fn hash(inputs: &[&[u8]]) -> [u8; 32] {
+        metrics::record(Metric::Permutation, 1);
+        H::hash(inputs)
+    }
+
+    fn ratchet(&mut self) {
+        self.chaining_value = Self::hash(&[&[RATCHET], &self.chaining_value]);
+    }
+
+    fn finish_absorb(&mut self) {
+        if !self.pending.is_empty() {
+            self.chaining_value = Self::hash(&[&[ABSORB], &self.chaining_value, &self.pending]);
+            self.pending.clear();
+        }
+    }
+}
+
+impl<H: DuplexHash> CryptographicSponge for DuplexSponge<H> {
+    type Config = H::Config;
+
+    fn new(_config: &Self::Config) -> Self {
+        Self {
+            chaining_value: Self::hash(&[&[INIT], DOMAIN_SEPARATOR, H::NAME]),
+            pending: vec![],
+            _hash: PhantomData,
+        }
+    }
+
+    fn absorb(&mut self, input: &impl Absorb) {
+        let bytes = input.to_sponge_bytes_as_vec();
+        self.pending
+            .extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        self.pending.extend_from_slice(&bytes);
+    }
+
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        self.finish_absorb();
+        let mut output = vec![0u8; num_bytes];
+        metrics::record(Metric::Permutation, num_bytes.div_ceil(32));
+        H::xof(
+            &[
+                &[SQUEEZE],
+                &self.chaining_value,
+                &(num_bytes as u64).to_le_bytes(),
+            ],
+            &mut output,
+        );
+        self.ratchet();
+        output
+    }
+
+    fn squeeze_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        squeeze_bits(self, num_bits)
+    }
+
+    fn squeeze_field_elements_with_sizes<F: PrimeField>(
+        &mut self,
+        sizes: &[FieldElementSize],
+    ) -> Vec<F> {
+        squeeze_field_elements_with_sizes(self, sizes)
+    }
+
+    fn squeeze_field_elements<F: PrimeField>(&mut self, num_elements: usize) -> Vec<F> {
+        self.squeeze_field_elements_with_sizes(&vec![FieldElementSize::Full; num_elements])
+    }
+}
+
+fn field_element_bytes<F: PrimeField>(size: FieldElementSize) -> usize {
+    match size {
+        FieldElementSize::Full => (F::MODULUS_BIT_SIZE as usize).div_ceil(8) + WIDE_REDUCTION_BYTES,
+        FieldElementSize::Truncated(bits) => bits.div_ceil(8),
+    }
+}
+
+// Bits and field elements sampled from squeezed bytes, for the byte-oriented sponges
+pub(super) fn squeeze_bits(sponge: &mut impl CryptographicSponge, num_bits: usize) -> Vec<bool> {
+    sponge
+        .squeeze_bytes(num_bits.div_ceil(8))
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+        .take(num_bits)
+        .collect()
+}
+
+pub(super) fn squeeze_field_elements_with_sizes<F: PrimeField>(
+    sponge: &mut impl CryptographicSponge,
+    sizes: &[FieldElementSize],
+) -> Vec<F> {
+    let lengths: Vec<_> = sizes
+        .iter()
+        .map(|&size| field_element_bytes::<F>(size))
+        .collect();
+    let bytes = sponge.squeeze_bytes(lengths.iter().sum());
+
+    let mut offset = 0;
+    sizes
+        .iter()
+        .zip(lengths)
+        .map(|(size, length)| {
+            let mut element_bytes = bytes[offset..offset + length].to_vec();
+            offset += length;
+            if let FieldElementSize::Truncated(bits) = size {
+                if bits % 8 != 0 {
+                    *element_bytes.last_mut().unwrap() &= (1u8 << (bits % 8)) - 1;
+                }
+            }
+            F::from_le_bytes_mod_order(&element_bytes)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{fields::Field64 as TestField, fs::blake3::Sponge};
+
+    fn new_sponge() -> Sponge {
+        Sponge::new(&Default::default())
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let mut a = new_sponge();
+        let mut b = new_sponge();
+        a.absorb(&vec![1u8, 2, 3]);
+        b.absorb(&vec![1u8, 2, 3]);
+        assert_eq!(a.squeeze_bytes(64), b.squeeze_bytes(64));
+    }
+
+    #[test]
+    fn test_absorb_boundaries() {
+        let mut a = new_sponge();
+        a.absorb(&vec![1u8, 2]);
+        let mut b = new_sponge();
+        b.absorb(&vec![1u8]);
+        b.absorb(&vec![2u8]);
+        assert_ne!(a.squeeze_bytes(32), b.squeeze_bytes(32));
+
+        // Absorbing an empty message is not a no-op
+        let mut a = new_sponge();
+        let mut b = new_sponge();
+        b.absorb(&Vec::<u8>::new());
+        assert_ne!(a.squeeze_bytes(32), b.squeeze_bytes(32));
+    }
+
+    #[test]
+    fn test_squeezes_are_independent() {
+        let mut a = new_sponge();
+        let first = a.squeeze_bytes(32);
+        let second = a.squeeze_bytes(32);
+        assert_ne!(first, second);
+
+        // Squeezing in two calls is not a prefix of squeezing in one
+        let mut b = new_sponge();
+        let both = b.squeeze_bytes(64);
+        assert_ne!(&both[..32], &first[..]);
+    }
+
+    #[test]
+    fn test_counter_mode_xof() {
+        use crate::crypto::fs::keccak::Keccak;
+        use sha3::Digest;
+
+        let mut output = [0u8; 40];
+        Keccak::xof(&[b"ab", b"c"], &mut output);
+
+        let first = sha3::Keccak256::digest(b"abc\x00\x00\x00\x00\x00\x00\x00\x00");
+        let second = sha3::Keccak256::digest(b"abc\x01\x00\x00\x00\x00\x00\x00\x00");
+        assert_eq!(&output[..32], &first[..]);
+        assert_eq!(&output[32..], &second[..8]);
+    }
+
+    #[test]
+    fn test_permutations() {
+        let (mut sponge, counters) = metrics::with_counter(new_sponge);
+        let expected = if cfg!(feature = "metrics") { 1 } else { 0 };
+        assert_eq!(counters.permutations, expected);
+
+        // Finishing the absorb phase, 2 blocks of XOF output and the ratchet
+        let (_, counters) = metrics::with_counter(|| {
+            sponge.absorb(&vec![1u8; 100]);
+            sponge.squeeze_bytes(40)
+        });
+        let expected = if cfg!(feature = "metrics") { 4 } else { 0 };
+        assert_eq!(counters.permutations, expected);
+    }
+
+    #[test]
+    fn test_wide_reduction() {
+        let mut a = new_sponge();
+        let mut b = a.clone();
+
+        let elements: Vec<TestField> = a.squeeze_field_elements(2);
+        // 8 bytes for the modulus and 16 for the reduction
+        let bytes = b.squeeze_bytes(2 * 24);
+        assert_eq!(
+            elements[0],
+            TestField::from_le_bytes_mod_order(&bytes[..24])
+        );
+        assert_eq!(
+            elements[1],
+            TestField::from_le_bytes_mod_order(&bytes[24..])
+        );
+    }
+
+    #[test]
+    fn test_truncated_elements() {
+        let mut a = new_sponge();
+        let elements: Vec<TestField> =
+            a.squeeze_field_elements_with_sizes(&[FieldElementSize::Truncated(12); 64]);
+        assert!(elements.iter().all(|e| e.into_bigint().0[0] < (1 << 12)));
+    }
+}
@@ -0,0 +1,31 @@
+use sha3::Digest;
+
+use super::duplex::{DuplexHash, DuplexSponge};
+
+#[derive(Default, Clone, Copy)]
+pub struct KeccakConfig;
+
+pub fn default_fs_config() -> KeccakConfig {
+    KeccakConfig
+}
+
+// Keccak-256, both as H and (in counter mode) as the XOF. This is the keccak256 opcode of the EVM,
+// so that an on-chain verifier can recompute the transcript, see crate::evm.
+#[derive(Clone)]
+pub struct Keccak;
+
+impl DuplexHash for Keccak {
+    type Config = KeccakConfig;
+
+    const NAME: &'static [u8] = b"keccak-256";
+
+    fn hash(inputs: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = sha3::Keccak256::new();
+        for input in inputs {
+            hasher.update(input);
+        }
+        hasher.finalize().into()
+    }
+}
+
+pub type Sponge = DuplexSponge<Keccak>;
@@ -1,2 +1,7 @@
+pub mod blake2;
 pub mod blake3;
+pub mod duplex;
+pub mod keccak;
 pub mod poseidon;
+pub mod poseidon2;
+pub mod sha3;
@@ -1,14 +1,154 @@
-use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, PoseidonSponge};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use ark_crypto_primitives::sponge::{
+    poseidon::{PoseidonConfig, PoseidonSponge},
+    Absorb, CryptographicSponge, DuplexSpongeMode, FieldBasedCryptographicSponge, FieldElementSize,
+};
 use ark_ff::PrimeField;
+use lazy_static::lazy_static;
 use poseidon_paramgen::v1::generate;
 
-pub type Sponge<F> = PoseidonSponge<F>;
+use crate::metrics::{self, Metric};
 
-// PoseidonSponge for testing
-pub fn default_fs_config<F: PrimeField>() -> PoseidonConfig<F> {
+// PoseidonSponge, counting its permutations for crate::metrics. The permutation of PoseidonSponge
+// is private, so the count is derived from its mode and the number of native elements each call
+// absorbs or squeezes, following the implementation in ark_crypto_primitives.
+#[derive(Clone)]
+pub struct Sponge<F: PrimeField>(PoseidonSponge<F>);
+
+impl<F: PrimeField> Sponge<F> {
+    // Records the permutations of absorbing (or squeezing) num_elements native elements
+    fn record(&self, num_elements: usize, squeeze: bool) {
+        let rate = self.0.parameters.rate;
+        let (mut permutations, start) = match self.0.mode {
+            DuplexSpongeMode::Absorbing { .. } if squeeze => (1, 0),
+            DuplexSpongeMode::Absorbing { next_absorb_index } if next_absorb_index == rate => {
+                (1, 0)
+            }
+            DuplexSpongeMode::Absorbing { next_absorb_index } => (0, next_absorb_index),
+            DuplexSpongeMode::Squeezing { .. } if !squeeze => (0, 0),
+            DuplexSpongeMode::Squeezing { next_squeeze_index } if next_squeeze_index == rate => {
+                (1, 0)
+            }
+            DuplexSpongeMode::Squeezing { next_squeeze_index } => (0, next_squeeze_index),
+        };
+        // Absorbing nothing is a no-op, squeezing nothing still finishes the absorb phase
+        if !squeeze && num_elements == 0 {
+            return;
+        }
+        permutations += (start + num_elements).div_ceil(rate).saturating_sub(1);
+        metrics::record(Metric::Permutation, permutations);
+    }
+
+    // Number of bits squeezed for elements of F2 with the given sizes
+    fn num_bits<F2: PrimeField>(sizes: &[FieldElementSize]) -> usize {
+        sizes
+            .iter()
+            .map(|size| match size {
+                FieldElementSize::Full => (F2::MODULUS_BIT_SIZE - 1) as usize,
+                FieldElementSize::Truncated(bits) => *bits,
+            })
+            .sum()
+    }
+
+    fn record_bits(&self, num_bits: usize) {
+        let usable_bits = (F::MODULUS_BIT_SIZE - 1) as usize;
+        self.record(num_bits.div_ceil(usable_bits), true);
+    }
+}
+
+impl<F: PrimeField> CryptographicSponge for Sponge<F> {
+    type Config = PoseidonConfig<F>;
+
+    fn new(config: &Self::Config) -> Self {
+        Self(PoseidonSponge::new(config))
+    }
+
+    fn absorb(&mut self, input: &impl Absorb) {
+        let num_elements = input.to_sponge_field_elements_as_vec::<F>().len();
+        self.record(num_elements, false);
+        self.0.absorb(input);
+    }
+
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        let usable_bytes = ((F::MODULUS_BIT_SIZE - 1) / 8) as usize;
+        self.record(num_bytes.div_ceil(usable_bytes), true);
+        self.0.squeeze_bytes(num_bytes)
+    }
+
+    fn squeeze_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        self.record_bits(num_bits);
+        self.0.squeeze_bits(num_bits)
+    }
+
+    fn squeeze_field_elements_with_sizes<F2: PrimeField>(
+        &mut self,
+        sizes: &[FieldElementSize],
+    ) -> Vec<F2> {
+        if F::characteristic() != F2::characteristic() {
+            if !sizes.is_empty() {
+                self.record_bits(Self::num_bits::<F2>(sizes));
+            }
+        } else if sizes.iter().all(|&size| size == FieldElementSize::Full) {
+            self.record(sizes.len(), true);
+        } else {
+            self.record_bits(Self::num_bits::<F>(sizes));
+        }
+        self.0.squeeze_field_elements_with_sizes(sizes)
+    }
+
+    fn squeeze_field_elements<F2: PrimeField>(&mut self, num_elements: usize) -> Vec<F2> {
+        self.squeeze_field_elements_with_sizes(&vec![FieldElementSize::Full; num_elements])
+    }
+}
+
+impl<F: PrimeField> FieldBasedCryptographicSponge<F> for Sponge<F> {
+    fn squeeze_native_field_elements(&mut self, num_elements: usize) -> Vec<F> {
+        self.record(num_elements, true);
+        self.0.squeeze_native_field_elements(num_elements)
+    }
+}
+
+// We need 2 field elements of capacity for security
+pub const CAPACITY: usize = 2;
+
+// Generating Poseidon parameters is expensive, so we only do it once per field, rate and capacity
+// and share the result
+type Registry = HashMap<(TypeId, usize, usize), Arc<dyn Any + Send + Sync>>;
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(HashMap::new());
+}
+
+// The Poseidon configuration for F with the given rate (and width rate + CAPACITY)
+pub fn poseidon_config<F: PrimeField>(rate: usize) -> Arc<PoseidonConfig<F>> {
+    poseidon_config_with_capacity(rate, CAPACITY)
+}
+
+// The Poseidon configuration for F with the given rate and capacity. A capacity lower than
+// CAPACITY is only meant for fixed-length compression functions, not for sponges.
+pub fn poseidon_config_with_capacity<F: PrimeField>(
+    rate: usize,
+    capacity: usize,
+) -> Arc<PoseidonConfig<F>> {
+    assert!(rate > 0 && capacity > 0);
+    let mut registry = REGISTRY.lock().unwrap();
+    registry
+        .entry((TypeId::of::<F>(), rate, capacity))
+        .or_insert_with(|| Arc::new(generate_config::<F>(rate, capacity)))
+        .clone()
+        .downcast()
+        .unwrap()
+}
+
+fn generate_config<F: PrimeField>(rate: usize, capacity: usize) -> PoseidonConfig<F> {
     // initialize params
     let security_level_bits: usize = 128;
-    let width_hash_function: usize = 3; // In this case, we do 2-to-1
+    let width_hash_function: usize = rate + capacity;
     let allow_inverse: bool = false;
     let poseidon_parameters = generate::<F>(
         security_level_bits,
@@ -25,7 +165,60 @@ pub fn default_fs_config<F: PrimeField>() -> PoseidonConfig<F> {
         u32::from_le_bytes(poseidon_parameters.alpha.to_bytes_le()) as u64,
         poseidon_parameters.mds.into(),
         poseidon_parameters.arc.into(),
-        1, // rate
-        2, // capacity
+        rate,
+        capacity,
     )
 }
+
+// PoseidonSponge for testing
+pub fn default_fs_config<F: PrimeField>() -> PoseidonConfig<F> {
+    poseidon_config::<F>(1).as_ref().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::fields::{Field256, Field64};
+
+    #[test]
+    fn test_registry() {
+        let config = poseidon_config::<Field64>(1);
+        assert!(Arc::ptr_eq(&config, &poseidon_config::<Field64>(1)));
+        assert_eq!(config.ark, default_fs_config::<Field64>().ark);
+
+        // Configurations are per field and per rate
+        let wide_config = poseidon_config::<Field64>(4);
+        assert_eq!(wide_config.rate, 4);
+        assert_eq!(wide_config.capacity, CAPACITY);
+        assert_eq!(wide_config.mds.len(), 4 + CAPACITY);
+        assert_eq!(poseidon_config::<Field256>(4).mds.len(), 4 + CAPACITY);
+    }
+
+    #[test]
+    fn test_sponge() {
+        let config = poseidon_config::<Field64>(2);
+        let mut sponge = Sponge::new(config.as_ref());
+        let mut expected = PoseidonSponge::new(config.as_ref());
+
+        let (_, counters) = metrics::with_counter(|| {
+            sponge.absorb(&vec![Field64::from(1u64); 3]);
+            expected.absorb(&vec![Field64::from(1u64); 3]);
+            assert_eq!(
+                sponge.squeeze_native_field_elements(3),
+                expected.squeeze_native_field_elements(3)
+            );
+            assert_eq!(sponge.squeeze_bytes(20), expected.squeeze_bytes(20));
+            assert_eq!(
+                sponge.squeeze_field_elements::<Field256>(1),
+                expected.squeeze_field_elements::<Field256>(1)
+            );
+        });
+
+        // At rate 2: 1 to absorb 3 elements, 2 to squeeze 3 elements, 1 to squeeze the 3 elements
+        // for 20 bytes (7 per element) after the one left over, and 3 to squeeze the 5 elements
+        // for the 254 bits (63 per element) of a Field256 element from a full rate
+        if cfg!(feature = "metrics") {
+            assert_eq!(counters.permutations, 1 + 2 + 1 + 3);
+        }
+    }
+}
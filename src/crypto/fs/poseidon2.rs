@@ -0,0 +1,9 @@
+use std::sync::Arc;
+
+use crate::crypto::poseidon2::{Poseidon2Config, Poseidon2Field, Poseidon2Sponge};
+
+pub type Sponge<F> = Poseidon2Sponge<F>;
+
+pub fn default_fs_config<F: Poseidon2Field>() -> Arc<Poseidon2Config<F>> {
+    F::poseidon2_config()
+}
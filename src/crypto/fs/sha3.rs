@@ -0,0 +1,159 @@
+// Duplex sponge over Keccak-f[1600], the permutation of SHA3, used for Fiat-Shamir.
+//
+// Transcript specification. The state is the 200 byte Keccak state, with the rate and capacity of
+// SHA3-256: the first RATE = 136 bytes are the rate, and the remaining 64 bytes the capacity. `f`
+// is Keccak-f[1600], `le64(n)` is `n` as a little-endian u64 and `||` is concatenation. Writing
+// bytes XORs them into the rate from the current position, and applies f (returning to the start
+// of the rate) each time the rate is full.
+//
+// - new:        state = 0, then write DOMAIN_SEPARATOR || NAME
+// - absorb(x):  write ABSORB || le64(|b|) || b, where b = x.to_sponge_bytes()
+// - squeeze(n): write SQUEEZE || le64(n), pad the rate with pad10*1 and apply f, then output the
+//               first n bytes of the rates of the states, applying f after each rate (including
+//               the last one, so that the next call starts from a fresh state)
+//
+// This is a duplex and not SHA3-256 itself: its transcripts are not computable from the keccak256
+// opcode of the EVM, for which see super::keccak. Bits and field elements are sampled from the
+// squeezed bytes as in super::duplex.
+
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge, FieldElementSize};
+use ark_ff::PrimeField;
+
+use super::duplex::{self, DOMAIN_SEPARATOR};
+use crate::metrics::{self, Metric};
+
+const NAME: &[u8] = b"keccak-f1600";
+
+const RATE: usize = 136;
+
+const ABSORB: u8 = 1;
+const SQUEEZE: u8 = 2;
+
+#[derive(Default, Clone, Copy)]
+pub struct Sha3Config;
+
+pub fn default_fs_config() -> Sha3Config {
+    Sha3Config
+}
+
+#[derive(Clone)]
+pub struct Sponge {
+    state: [u64; 25],
+    // Position of the next byte written in the rate
+    position: usize,
+}
+
+impl Sponge {
+    fn permute(&mut self) {
+        metrics::record(Metric::Permutation, 1);
+        keccak::f1600(&mut self.state);
+        self.position = 0;
+    }
+
+    fn xor_byte(&mut self, index: usize, byte: u8) {
+        self.state[index / 8] ^= (byte as u64) << (8 * (index % 8));
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.xor_byte(self.position, byte);
+            self.position += 1;
+            if self.position == RATE {
+                self.permute();
+            }
+        }
+    }
+}
+
+impl CryptographicSponge for Sponge {
+    type Config = Sha3Config;
+
+    fn new(_config: &Self::Config) -> Self {
+        let mut sponge = Self {
+            state: [0; 25],
+            position: 0,
+        };
+        sponge.write(DOMAIN_SEPARATOR);
+        sponge.write(NAME);
+        sponge
+    }
+
+    fn absorb(&mut self, input: &impl Absorb) {
+        let bytes = input.to_sponge_bytes_as_vec();
+        self.write(&[ABSORB]);
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(&bytes);
+    }
+
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        self.write(&[SQUEEZE]);
+        self.write(&(num_bytes as u64).to_le_bytes());
+        self.xor_byte(self.position, 0x01);
+        self.xor_byte(RATE - 1, 0x80);
+        self.permute();
+
+        let mut output = Vec::with_capacity(num_bytes);
+        while output.len() < num_bytes {
+            let length = (num_bytes - output.len()).min(RATE);
+            let rate = self.state.iter().flat_map(|lane| lane.to_le_bytes());
+            output.extend(rate.take(length));
+            self.permute();
+        }
+        output
+    }
+
+    fn squeeze_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        duplex::squeeze_bits(self, num_bits)
+    }
+
+    fn squeeze_field_elements_with_sizes<F: PrimeField>(
+        &mut self,
+        sizes: &[FieldElementSize],
+    ) -> Vec<F> {
+        duplex::squeeze_field_elements_with_sizes(self, sizes)
+    }
+
+    fn squeeze_field_elements<F: PrimeField>(&mut self, num_elements: usize) -> Vec<F> {
+        self.squeeze_field_elements_with_sizes(&vec![FieldElementSize::Full; num_elements])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_sponge() -> Sponge {
+        Sponge::new(&default_fs_config())
+    }
+
+    #[test]
+    fn test_transcripts() {
+        let mut a = new_sponge();
+        let mut b = new_sponge();
+        a.absorb(&vec![1u8, 2, 3]);
+        b.absorb(&vec![1u8, 2, 3]);
+        assert_eq!(a.squeeze_bytes(300), b.squeeze_bytes(300));
+
+        // Messages are bound, and not only their concatenation
+        let mut a = new_sponge();
+        a.absorb(&vec![1u8, 2]);
+        let mut b = new_sponge();
+        b.absorb(&vec![1u8]);
+        b.absorb(&vec![2u8]);
+        assert_ne!(a.squeeze_bytes(32), b.squeeze_bytes(32));
+
+        // Squeezing in two calls is not a prefix of squeezing in one
+        let mut a = new_sponge();
+        let mut b = new_sponge();
+        let first = a.squeeze_bytes(32);
+        assert_ne!(a.squeeze_bytes(32), first);
+        assert_ne!(&b.squeeze_bytes(64)[..32], &first[..]);
+
+        // Absorbing across the rate
+        let mut a = new_sponge();
+        let mut b = new_sponge();
+        a.absorb(&vec![0u8; RATE + 1]);
+        b.absorb(&vec![0u8; RATE]);
+        assert_ne!(a.squeeze_bytes(32), b.squeeze_bytes(32));
+    }
+}
@@ -9,7 +9,7 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use blake2::Digest;
 use rand::RngCore;
 
-use super::HashCounter;
+use crate::metrics::{self, Metric};
 
 #[derive(
     Debug, Default, Clone, Copy, Eq, PartialEq, Hash, CanonicalSerialize, CanonicalDeserialize,
@@ -52,7 +52,7 @@ impl<F: CanonicalSerialize + Send> CRHScheme for Blake2LeafHash<F> {
 
         let mut output = [0; 32];
         output.copy_from_slice(&h.finalize()[..]);
-        HashCounter::add();
+        metrics::record(Metric::LeafHash, 1);
         Ok(Blake2Digest(output))
     }
 }
@@ -76,7 +76,7 @@ impl TwoToOneCRHScheme for Blake2TwoToOneCRHScheme {
         h.update(&right_input.borrow().0);
         let mut output = [0; 32];
         output.copy_from_slice(&h.finalize()[..]);
-        HashCounter::add();
+        metrics::record(Metric::Compression, 1);
         Ok(Blake2Digest(output))
     }
 
@@ -1,6 +1,6 @@
 use std::{borrow::Borrow, marker::PhantomData};
 
-use super::HashCounter;
+use crate::metrics::{self, Metric};
 use ark_crypto_primitives::{
     crh::{CRHScheme, TwoToOneCRHScheme},
     merkle_tree::{Config, IdentityDigestConverter},
@@ -50,7 +50,7 @@ impl<F: CanonicalSerialize + Send> CRHScheme for Blake3LeafHash<F> {
 
         let mut output = [0; 32];
         output.copy_from_slice(h.finalize().as_bytes());
-        HashCounter::add();
+        metrics::record(Metric::LeafHash, 1);
         Ok(Blake3Digest(output))
     }
 }
@@ -74,7 +74,7 @@ impl TwoToOneCRHScheme for Blake3TwoToOneCRHScheme {
         h.update(&right_input.borrow().0);
         let mut output = [0; 32];
         output.copy_from_slice(h.finalize().as_bytes());
-        HashCounter::add();
+        metrics::record(Metric::Compression, 1);
         Ok(Blake3Digest(output))
     }
 
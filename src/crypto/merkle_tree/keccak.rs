@@ -0,0 +1,123 @@
+// Merkle trees over Keccak-256, the hash of the EVM keccak256 opcode (which differs from SHA3-256
+// only in its padding), so that authentication paths can be checked on-chain. Leaves are hashed
+// like in the sha3 trees: the hash of their compressed serialization.
+
+use std::{borrow::Borrow, marker::PhantomData};
+
+use crate::metrics::{self, Metric};
+use ark_crypto_primitives::{
+    crh::{CRHScheme, TwoToOneCRHScheme},
+    merkle_tree::{Config, IdentityDigestConverter},
+    sponge::Absorb,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::RngCore;
+use sha3::Digest;
+
+#[derive(
+    Debug, Default, Clone, Copy, Eq, PartialEq, Hash, CanonicalSerialize, CanonicalDeserialize,
+)]
+pub struct KeccakDigest(pub(crate) [u8; 32]);
+
+impl Absorb for KeccakDigest {
+    fn to_sponge_bytes(&self, dest: &mut Vec<u8>) {
+        dest.extend_from_slice(&self.0);
+    }
+
+    fn to_sponge_field_elements<F: ark_ff::PrimeField>(&self, dest: &mut Vec<F>) {
+        let mut buf = [0; 32];
+        buf.copy_from_slice(&self.0);
+        dest.push(F::from_be_bytes_mod_order(&buf));
+    }
+}
+
+pub struct KeccakLeafHash<F>(PhantomData<F>);
+pub struct KeccakTwoToOneCRHScheme;
+
+impl<F: CanonicalSerialize + Send> CRHScheme for KeccakLeafHash<F> {
+    type Input = Vec<F>;
+    type Output = KeccakDigest;
+    type Parameters = ();
+
+    fn setup<R: RngCore>(_: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
+        Ok(())
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        _: &Self::Parameters,
+        input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        let mut buf = vec![];
+        CanonicalSerialize::serialize_compressed(input.borrow(), &mut buf)?;
+
+        let mut h = sha3::Keccak256::new();
+        h.update(&buf);
+
+        let mut output = [0; 32];
+        output.copy_from_slice(&h.finalize()[..]);
+        metrics::record(Metric::LeafHash, 1);
+        Ok(KeccakDigest(output))
+    }
+}
+
+impl TwoToOneCRHScheme for KeccakTwoToOneCRHScheme {
+    type Input = KeccakDigest;
+    type Output = KeccakDigest;
+    type Parameters = ();
+
+    fn setup<R: RngCore>(_: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
+        Ok(())
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        _: &Self::Parameters,
+        left_input: T,
+        right_input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        let mut h = sha3::Keccak256::new();
+        h.update(left_input.borrow().0);
+        h.update(right_input.borrow().0);
+        let mut output = [0; 32];
+        output.copy_from_slice(&h.finalize()[..]);
+        metrics::record(Metric::Compression, 1);
+        Ok(KeccakDigest(output))
+    }
+
+    fn compress<T: Borrow<Self::Output>>(
+        parameters: &Self::Parameters,
+        left_input: T,
+        right_input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        <Self as TwoToOneCRHScheme>::evaluate(parameters, left_input, right_input)
+    }
+}
+
+pub type LeafH<F> = KeccakLeafHash<F>;
+pub type CompressH = KeccakTwoToOneCRHScheme;
+
+#[derive(Debug, Default, Clone)]
+pub struct MerkleTreeParams<F>(PhantomData<F>);
+
+impl<F: CanonicalSerialize + Send> Config for MerkleTreeParams<F> {
+    type Leaf = Vec<F>;
+
+    type LeafDigest = <LeafH<F> as CRHScheme>::Output;
+    type LeafInnerDigestConverter = IdentityDigestConverter<KeccakDigest>;
+    type InnerDigest = <CompressH as TwoToOneCRHScheme>::Output;
+
+    type LeafHash = LeafH<F>;
+    type TwoToOneHash = CompressH;
+}
+
+pub fn default_config<F: CanonicalSerialize + Send>(
+    rng: &mut impl RngCore,
+    _leaf_arity: usize,
+) -> (
+    <LeafH<F> as CRHScheme>::Parameters,
+    <CompressH as TwoToOneCRHScheme>::Parameters,
+) {
+    // Both hashes are unkeyed
+    <LeafH<F> as CRHScheme>::setup(rng).unwrap();
+    <CompressH as TwoToOneCRHScheme>::setup(rng).unwrap();
+    ((), ())
+}
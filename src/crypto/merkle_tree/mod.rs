@@ -1,43 +1,54 @@
 pub mod blake2;
 pub mod blake3;
+pub mod keccak;
 pub mod mock;
 pub mod poseidon;
+pub mod poseidon2;
 pub mod sha3;
 
-use std::{borrow::Borrow, marker::PhantomData, sync::atomic::AtomicUsize};
+use std::{borrow::Borrow, marker::PhantomData};
 
-use ark_crypto_primitives::crh::CRHScheme;
+use ark_crypto_primitives::{
+    crh::CRHScheme,
+    merkle_tree::{Config, MultiPath},
+};
+use ark_ff::UniformRand;
 use ark_serialize::CanonicalSerialize;
-use lazy_static::lazy_static;
 use rand::RngCore;
 
-#[derive(Debug, Default)]
-pub struct HashCounter {
-    counter: AtomicUsize,
-}
-
-lazy_static! {
-    static ref HASH_COUNTER: HashCounter = HashCounter::default();
-}
-
-impl HashCounter {
-    pub(crate) fn add() -> usize {
-        HASH_COUNTER
-            .counter
-            .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+// Appends salt_size uniformly random field elements to each leaf. The leaf hashers hash the
+// whole leaf, so that the digest of a salted leaf reveals nothing about its contents until it is
+// opened. This is what makes the commitments hiding in zero-knowledge mode.
+pub fn salt_leaves<F: UniformRand>(
+    leaves: &mut [Vec<F>],
+    salt_size: usize,
+    rng: &mut impl RngCore,
+) {
+    for leaf in leaves.iter_mut() {
+        leaf.extend((0..salt_size).map(|_| F::rand(rng)));
     }
+}
 
-    pub fn reset() {
-        HASH_COUNTER
-            .counter
-            .store(0, std::sync::atomic::Ordering::SeqCst)
-    }
+// The sibling of a leaf and the siblings of the inner nodes on its path
+pub type DecodedPath<P> = (<P as Config>::LeafDigest, Vec<<P as Config>::InnerDigest>);
 
-    pub fn get() -> usize {
-        HASH_COUNTER
-            .counter
-            .load(std::sync::atomic::Ordering::SeqCst)
-    }
+// Decodes the authentication path of each leaf of a MultiPath, in the order of leaf_indexes: the
+// sibling of the leaf, then the siblings of the inner nodes from the top of the tree down, as in
+// ark_crypto_primitives::merkle_tree::Path. None if the prefix lengths are inconsistent.
+pub fn decode_paths<P: Config>(multi_path: &MultiPath<P>) -> Option<Vec<DecodedPath<P>>> {
+    let mut prev_path = vec![];
+    multi_path
+        .leaf_siblings_hashes
+        .iter()
+        .zip(&multi_path.auth_paths_prefix_lenghts)
+        .zip(&multi_path.auth_paths_suffixes)
+        .map(|((leaf_sibling, &prefix_len), suffix)| {
+            let mut path = prev_path.get(..prefix_len)?.to_vec();
+            path.extend_from_slice(suffix);
+            prev_path = path.clone();
+            Some((leaf_sibling.clone(), path))
+        })
+        .collect()
 }
 
 #[derive(Debug, Default)]
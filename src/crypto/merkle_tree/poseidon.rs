@@ -1,7 +1,6 @@
-use std::{borrow::Borrow, marker::PhantomData};
+use std::{borrow::Borrow, marker::PhantomData, sync::Arc};
 
-use ark_crypto_primitives::crh::poseidon;
-use ark_crypto_primitives::sponge::poseidon::PoseidonSponge;
+use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, PoseidonSponge};
 use ark_crypto_primitives::sponge::CryptographicSponge;
 use ark_crypto_primitives::{
     crh::{CRHScheme, TwoToOneCRHScheme},
@@ -13,12 +12,18 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use rand::RngCore;
 
 use crate::crypto::fs;
-
-use super::HashCounter;
+use crate::metrics::{self, Metric};
 
 // We need 2 field elements for security
+const DIGEST_SIZE: usize = 2;
+
+// Number of permutations a sponge with the given rate makes to absorb and then squeeze
+fn permutations(absorbed: usize, squeezed: usize, rate: usize) -> usize {
+    absorbed.max(1).div_ceil(rate) + squeezed.div_ceil(rate) - 1
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Default, CanonicalSerialize, CanonicalDeserialize)]
-pub struct PoseidonDigest<F: PrimeField>([F; 2]);
+pub struct PoseidonDigest<F: PrimeField>(pub(crate) [F; 2]);
 
 impl<DigestField: PrimeField + Absorb> Absorb for PoseidonDigest<DigestField> {
     fn to_sponge_bytes(&self, dest: &mut Vec<u8>) {
@@ -37,22 +42,26 @@ pub struct PoseidonCRH<F>(PhantomData<F>);
 impl<F: PrimeField + Absorb> CRHScheme for PoseidonCRH<F> {
     type Input = Vec<F>;
     type Output = PoseidonDigest<F>;
-    type Parameters = <poseidon::CRH<F> as CRHScheme>::Parameters;
+    type Parameters = Arc<PoseidonConfig<F>>;
 
+    // Without knowing the size of the leaves we absorb at rate 1, see default_config
     fn setup<R: RngCore>(_rng: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
-        Ok(fs::poseidon::default_fs_config::<F>())
+        Ok(fs::poseidon::poseidon_config::<F>(1))
     }
 
     fn evaluate<T: Borrow<Self::Input>>(
         parameters: &Self::Parameters,
         input: T,
     ) -> Result<Self::Output, ark_crypto_primitives::Error> {
-        HashCounter::add();
+        let input = input.borrow();
+        metrics::record(Metric::LeafHash, 1);
+        metrics::record(
+            Metric::Permutation,
+            permutations(input.len(), DIGEST_SIZE, parameters.rate),
+        );
         let mut sponge = PoseidonSponge::new(parameters);
-        for el in input.borrow() {
-            sponge.absorb(el);
-        }
-        let res = sponge.squeeze_field_elements::<F>(2);
+        sponge.absorb(input);
+        let res = sponge.squeeze_field_elements::<F>(DIGEST_SIZE);
         Ok(PoseidonDigest([res[0], res[1]]))
     }
 }
@@ -62,10 +71,14 @@ pub struct PoseidonTwoToOneCRH<F>(PhantomData<F>);
 impl<F: PrimeField + Absorb> TwoToOneCRHScheme for PoseidonTwoToOneCRH<F> {
     type Input = PoseidonDigest<F>;
     type Output = PoseidonDigest<F>;
-    type Parameters = <poseidon::TwoToOneCRH<F> as TwoToOneCRHScheme>::Parameters;
+    type Parameters = Arc<PoseidonConfig<F>>;
 
+    // A single permutation of width 2 * DIGEST_SIZE + 1, truncated to DIGEST_SIZE elements
     fn setup<R: RngCore>(_rng: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
-        Ok(fs::poseidon::default_fs_config::<F>())
+        Ok(fs::poseidon::poseidon_config_with_capacity::<F>(
+            2 * DIGEST_SIZE,
+            1,
+        ))
     }
 
     fn evaluate<T: Borrow<Self::Input>>(
@@ -81,7 +94,11 @@ impl<F: PrimeField + Absorb> TwoToOneCRHScheme for PoseidonTwoToOneCRH<F> {
         left_input: T,
         right_input: T,
     ) -> Result<Self::Output, ark_crypto_primitives::Error> {
-        HashCounter::add();
+        metrics::record(Metric::Compression, 1);
+        metrics::record(
+            Metric::Permutation,
+            permutations(2 * DIGEST_SIZE, DIGEST_SIZE, parameters.rate),
+        );
         let left_input = left_input.borrow();
         let right_input = right_input.borrow();
         let mut sponge = PoseidonSponge::new(parameters);
@@ -89,9 +106,8 @@ impl<F: PrimeField + Absorb> TwoToOneCRHScheme for PoseidonTwoToOneCRH<F> {
         sponge.absorb(&left_input.0[1]);
         sponge.absorb(&right_input.0[0]);
         sponge.absorb(&right_input.0[1]);
-        let res = sponge.squeeze_field_elements::<F>(2);
+        let res = sponge.squeeze_field_elements::<F>(DIGEST_SIZE);
         Ok(PoseidonDigest([res[0], res[1]]))
-
     }
 }
 
@@ -112,15 +128,34 @@ impl<F: PrimeField + Absorb> Config for MerkleTreeParams<F> {
     type TwoToOneHash = CompressH<F>;
 }
 
+// Leaves are absorbed at rate leaf_arity, so that hashing a leaf is a single permutation
 pub fn default_config<F: PrimeField + Absorb>(
     rng: &mut impl RngCore,
-    _leaf_arity: usize,
+    leaf_arity: usize,
 ) -> (
     <LeafH<F> as CRHScheme>::Parameters,
     <CompressH<F> as TwoToOneCRHScheme>::Parameters,
 ) {
-    let leaf_hash_params = <LeafH<F> as CRHScheme>::setup(rng).unwrap();
+    let leaf_hash_params = fs::poseidon::poseidon_config::<F>(leaf_arity);
     let two_to_one_params = <CompressH<F> as TwoToOneCRHScheme>::setup(rng).unwrap();
 
     (leaf_hash_params, two_to_one_params)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permutations() {
+        // A leaf of 16 elements at rate 1, and squeezing the two elements of the digest
+        assert_eq!(permutations(16, DIGEST_SIZE, 1), 17);
+        assert_eq!(permutations(16, DIGEST_SIZE, 16), 1);
+        assert_eq!(permutations(20, DIGEST_SIZE, 16), 2);
+        // Compression
+        assert_eq!(
+            permutations(2 * DIGEST_SIZE, DIGEST_SIZE, 2 * DIGEST_SIZE),
+            1
+        );
+    }
+}
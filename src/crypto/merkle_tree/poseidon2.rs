@@ -0,0 +1,122 @@
+use std::{borrow::Borrow, marker::PhantomData, sync::Arc};
+
+use ark_crypto_primitives::sponge::CryptographicSponge;
+use ark_crypto_primitives::{
+    crh::{CRHScheme, TwoToOneCRHScheme},
+    merkle_tree::{Config, IdentityDigestConverter},
+    sponge::Absorb,
+};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::RngCore;
+
+use crate::crypto::poseidon2::{Poseidon2Config, Poseidon2Field, Poseidon2Sponge};
+
+use crate::metrics::{self, Metric};
+
+// The digest is as wide as the capacity of the sponge, i.e. 4 elements for Field64, 2 for Field128
+// and 1 for Field256
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Default, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Poseidon2Digest<F: PrimeField>(Vec<F>);
+
+impl<DigestField: PrimeField + Absorb> Absorb for Poseidon2Digest<DigestField> {
+    fn to_sponge_bytes(&self, dest: &mut Vec<u8>) {
+        for element in &self.0 {
+            element.to_sponge_bytes(dest);
+        }
+    }
+
+    fn to_sponge_field_elements<F: PrimeField>(&self, dest: &mut Vec<F>) {
+        for element in &self.0 {
+            element.to_sponge_field_elements(dest);
+        }
+    }
+}
+
+pub struct Poseidon2CRH<F>(PhantomData<F>);
+
+impl<F: Poseidon2Field> CRHScheme for Poseidon2CRH<F> {
+    type Input = Vec<F>;
+    type Output = Poseidon2Digest<F>;
+    type Parameters = Arc<Poseidon2Config<F>>;
+
+    fn setup<R: RngCore>(_rng: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
+        Ok(F::poseidon2_config())
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        parameters: &Self::Parameters,
+        input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        metrics::record(Metric::LeafHash, 1);
+        let mut sponge = Poseidon2Sponge::new(parameters);
+        sponge.absorb(input.borrow());
+        Ok(Poseidon2Digest(
+            sponge.squeeze_native_field_elements(parameters.capacity()),
+        ))
+    }
+}
+
+pub struct Poseidon2TwoToOneCRH<F>(PhantomData<F>);
+
+impl<F: Poseidon2Field> TwoToOneCRHScheme for Poseidon2TwoToOneCRH<F> {
+    type Input = Poseidon2Digest<F>;
+    type Output = Poseidon2Digest<F>;
+    type Parameters = Arc<Poseidon2Config<F>>;
+
+    // A single permutation of the two concatenated digests, truncated to the size of one
+    fn setup<R: RngCore>(_rng: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
+        Ok(F::poseidon2_compression_config())
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        parameters: &Self::Parameters,
+        left_input: T,
+        right_input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        Self::compress(parameters, left_input, right_input)
+    }
+
+    fn compress<T: Borrow<Self::Output>>(
+        parameters: &Self::Parameters,
+        left_input: T,
+        right_input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        metrics::record(Metric::Compression, 1);
+        metrics::record(Metric::Permutation, 1);
+        let mut state = [left_input.borrow().0.as_slice(), &right_input.borrow().0].concat();
+        parameters.permute(&mut state);
+        state.truncate(parameters.width / 2);
+        Ok(Poseidon2Digest(state))
+    }
+}
+
+pub type LeafH<F> = Poseidon2CRH<F>;
+pub type CompressH<F> = Poseidon2TwoToOneCRH<F>;
+
+#[derive(Debug, Default, Clone)]
+pub struct MerkleTreeParams<F>(PhantomData<F>);
+
+impl<F: Poseidon2Field> Config for MerkleTreeParams<F> {
+    type Leaf = Vec<F>;
+
+    type LeafDigest = <LeafH<F> as CRHScheme>::Output;
+    type LeafInnerDigestConverter = IdentityDigestConverter<Poseidon2Digest<F>>;
+    type InnerDigest = <CompressH<F> as TwoToOneCRHScheme>::Output;
+
+    type LeafHash = LeafH<F>;
+    type TwoToOneHash = CompressH<F>;
+}
+
+pub fn default_config<F: Poseidon2Field>(
+    rng: &mut impl RngCore,
+    _leaf_arity: usize,
+) -> (
+    <LeafH<F> as CRHScheme>::Parameters,
+    <CompressH<F> as TwoToOneCRHScheme>::Parameters,
+) {
+    let leaf_hash_params = <LeafH<F> as CRHScheme>::setup(rng).unwrap();
+    let two_to_one_params = <CompressH<F> as TwoToOneCRHScheme>::setup(rng).unwrap();
+
+    (leaf_hash_params, two_to_one_params)
+}
@@ -1,6 +1,6 @@
 use std::{borrow::Borrow, marker::PhantomData};
 
-use super::HashCounter;
+use crate::metrics::{self, Metric};
 use ark_crypto_primitives::{
     crh::{CRHScheme, TwoToOneCRHScheme},
     merkle_tree::{Config, IdentityDigestConverter},
@@ -51,7 +51,7 @@ impl<F: CanonicalSerialize + Send> CRHScheme for SHA3LeafHash<F> {
 
         let mut output = [0; 32];
         output.copy_from_slice(&h.finalize()[..]);
-        HashCounter::add();
+        metrics::record(Metric::LeafHash, 1);
         Ok(SHA3Digest(output))
     }
 }
@@ -75,7 +75,7 @@ impl TwoToOneCRHScheme for SHA3TwoToOneCRHScheme {
         h.update(&right_input.borrow().0);
         let mut output = [0; 32];
         output.copy_from_slice(&h.finalize()[..]);
-        HashCounter::add();
+        metrics::record(Metric::Compression, 1);
         Ok(SHA3Digest(output))
     }
 
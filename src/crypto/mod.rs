@@ -1,3 +1,5 @@
+pub mod backends;
 pub mod fields;
 pub mod fs;
 pub mod merkle_tree;
+pub mod poseidon2;
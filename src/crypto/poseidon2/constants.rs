@@ -0,0 +1,668 @@
+// Generated by scripts/poseidon2_constants.py, do not edit by hand.
+
+use super::ConstantsTable;
+
+pub const FIELD64: ConstantsTable = ConstantsTable {
+    width: 8,
+    rate: 4,
+    alpha: 7,
+    full_rounds: 8,
+    partial_rounds: 22,
+    external_constants: &[
+        &[
+            "15949291268843349465",
+            "14644164809401934923",
+            "18420360874837380316",
+            "4756469047455716334",
+            "8685499049481102115",
+            "3799221349720045367",
+            "13676397835037157930",
+            "6566439050423619635",
+        ],
+        &[
+            "17428268347612331188",
+            "2833135872454503769",
+            "4767009016213040191",
+            "2797635963551733652",
+            "5312339450141126694",
+            "5356668452102813289",
+            "1234059326449530173",
+            "7724302552453704877",
+        ],
+        &[
+            "14868588146468890290",
+            "12825281145595371185",
+            "13097885453579304196",
+            "7905326782341128063",
+            "14167525334039893569",
+            "2082169701994688927",
+            "12190787523818595537",
+            "12602917751946636",
+        ],
+        &[
+            "14890907856876319003",
+            "16552240149997473409",
+            "5634093690795187558",
+            "4883714163685656967",
+            "12440776365164557866",
+            "3923800234666204307",
+            "9858064884105950259",
+            "16040043470428402038",
+        ],
+        &[
+            "18094737559774241307",
+            "10930414073408006485",
+            "632205812023241520",
+            "4896184719192102012",
+            "18046601795562835805",
+            "4874799261050045371",
+            "14805515569601384764",
+            "14962150510141817503",
+        ],
+        &[
+            "14603649285241593324",
+            "761417572415388640",
+            "1226185771089878470",
+            "3868252963893629278",
+            "10213206374844377176",
+            "12513523831859935725",
+            "5930658500326631305",
+            "12194321302721369377",
+        ],
+        &[
+            "11321798037787734142",
+            "8980154259883873756",
+            "11736938192888407579",
+            "13192414074373038918",
+            "2476169510837310467",
+            "1533959397343817152",
+            "12606670949879920962",
+            "7925350173351919368",
+        ],
+        &[
+            "9072593798739179107",
+            "12001755003233916850",
+            "16756686135965682097",
+            "15550825404583733749",
+            "8694668369540076298",
+            "15457222476085186130",
+            "5108598208140922837",
+            "3620213722474373078",
+        ],
+    ],
+    internal_constants: &[
+        "5226594323142090582",
+        "6254574254498162968",
+        "1486402152218690509",
+        "18280773820738154043",
+        "12955488253560265444",
+        "14893530806420712543",
+        "12823640325246269760",
+        "9655649918592982429",
+        "5114517305918572341",
+        "14723209762776543479",
+        "16432743296706583079",
+        "2917690869176376459",
+        "16733481957374600673",
+        "10050632638543068589",
+        "13942956444831714793",
+        "16149299494464346121",
+        "5319968881416215882",
+        "4813399736209008072",
+        "17082894626802242899",
+        "9937044286652006583",
+        "9604751035609530679",
+        "5298568465954566493",
+    ],
+    internal_diagonal: &[
+        "12360104777066765133",
+        "6980804037641883365",
+        "6906701863644728436",
+        "2097533388172431834",
+        "5181645704347790125",
+        "1520375086664255296",
+        "2822568888272001785",
+        "15627788923957762838",
+    ],
+};
+
+// Permutation of [0, 1, ..., width - 1]
+#[cfg(test)]
+pub const FIELD64_TEST_VECTOR: &[&str] = &[
+    "3414310160613450725",
+    "8357769970410854381",
+    "11162196528566134849",
+    "16597043654800806516",
+    "15833312979977961100",
+    "2278061938541991801",
+    "11389903603052338806",
+    "8714813210317483421",
+];
+
+pub const FIELD128: ConstantsTable = ConstantsTable {
+    width: 3,
+    rate: 1,
+    alpha: 3,
+    full_rounds: 8,
+    partial_rounds: 83,
+    external_constants: &[
+        &[
+            "160990949511784030149768568328953716161",
+            "187751695502734755786101387790214908767",
+            "64841440441948957854459325958951869662",
+        ],
+        &[
+            "288991794994470256898483558331488098777",
+            "329842717509864131615405262274493656659",
+            "226806069499366922608992039032527935969",
+        ],
+        &[
+            "288697727368415931693960688244371178430",
+            "145982238834870457622344605731286524874",
+            "176648907908593883096194260525032949682",
+        ],
+        &[
+            "135668036508354201908741743135549020319",
+            "196855599891829473217379400517033781003",
+            "206998259880088404615292391704979502409",
+        ],
+        &[
+            "104930627523307897971538885992789596176",
+            "329938978996438118998516159514572802391",
+            "286067269754535156775246382891180577110",
+        ],
+        &[
+            "293127492918027342604782901755812776",
+            "45431849615590022599577276504733104286",
+            "96014977917389854022117593804637528822",
+        ],
+        &[
+            "114101690806652497063623526290075609464",
+            "145902078591269843225561089176911487756",
+            "186777634196509649528856962225263729843",
+        ],
+        &[
+            "92986329581337612241017428899960139407",
+            "79107084882104034967447781101081099289",
+            "30170683628703550818118998758766234532",
+        ],
+    ],
+    internal_constants: &[
+        "292539058873847034829802490933963845947",
+        "291118897918165928175777072493987090383",
+        "126474381127093384897257457372661237395",
+        "320935907916942072597155674146741233758",
+        "260492140402693414651859426036870150235",
+        "300233710425713328944928555432848887249",
+        "30092251675111651039004146080737517554",
+        "117490505762968008427469817206550985030",
+        "68597717692699367252229106079925802753",
+        "291120325129610855715130698362758726379",
+        "37732330456848543523591870567572458259",
+        "255228865180972565012193052633355996794",
+        "152919436821102443167868172141948860646",
+        "191178072834976503862357318834722491608",
+        "35887436246506907158215496366921834636",
+        "296677414596947278020093711912163863637",
+        "108322131982919399933560295162462910794",
+        "87814589020816563941336332033072837219",
+        "257059410848942498020714529071226668580",
+        "158942767857276682026739478425453162790",
+        "319783489203938977135184199789977900687",
+        "276183130408175725474423209482453153139",
+        "119598637951642759950989156457973423792",
+        "6116152919463804399047945006534732205",
+        "332572912116646210288256161518137464923",
+        "173992097776043307374563661051382498032",
+        "142610672519911707534474594279860291836",
+        "116129511511373170225246438709670779283",
+        "104143839013231655298662702345227377480",
+        "263192363163691840317069956869207676831",
+        "14272909127135111195792834112541732690",
+        "228417589020241828079029145982592999173",
+        "332551349683653999609679361707672580659",
+        "264817061238458269931712910468270624593",
+        "124782451411581713167164322024782089549",
+        "77792116678018935181255588702856255166",
+        "163904174847367872429231412015164613138",
+        "157416217179726832100531386921848798621",
+        "270559172289707260547717750630260797135",
+        "150074057993031228666574973518601886863",
+        "3135561452733944533725877778508601421",
+        "173227784373257329807431188072173202684",
+        "192520346759326106964203604050686743721",
+        "14177579818162401185255694583523336913",
+        "83412939008195322821126808477897115693",
+        "169520526287620246356169730579384689863",
+        "153526905508727595931925636741479846011",
+        "253983899152005446989424387912509237250",
+        "281386403464170672967679962590466055263",
+        "45577959165398313946666908638559409867",
+        "145650573607472944130077685591599213062",
+        "149112304900393761951203304552499888829",
+        "134388027898600267481166169714608564702",
+        "118008884471585783953831478673188432032",
+        "63733697174180030305924977380041343193",
+        "138061909833095112713887542219637192280",
+        "96336899322436397430609350806362370373",
+        "311095961329162459983138912841732998816",
+        "10901231759133100886134156818918749944",
+        "294097767473899492721213093459772773482",
+        "62710018160305848843564072430012581267",
+        "59211726020708648855125104947061929471",
+        "105860011094664229885778088634866546764",
+        "306625993233355602692072889655911874934",
+        "334324460567900963286386024060195568531",
+        "212525587509661356789224686233610206977",
+        "314506258536676908986714295173470438326",
+        "146909824435794129804537616045694214273",
+        "24705995179116932952395423873292697949",
+        "91073118182620556743324037153964983529",
+        "118239719980673002323574363898483365383",
+        "144164462100049846083896696313913703759",
+        "262689973190191476158794009102506047922",
+        "141867676942005822387285444203247897711",
+        "284372092890394021493265776168671023250",
+        "7101011212656477786177002117812419392",
+        "258506512515101740076329490033938408085",
+        "42744391743756413764629352120492482479",
+        "217738711838625552859618685571888489876",
+        "134939875327981084319833022162931567567",
+        "34591049621305708680669807932463356928",
+        "61104693257109344419352869990810090073",
+        "117956593268447392013217910546788196134",
+    ],
+    internal_diagonal: &["1", "1", "2"],
+};
+
+// Permutation of [0, 1, ..., width - 1]
+#[cfg(test)]
+pub const FIELD128_TEST_VECTOR: &[&str] = &[
+    "155603715349515419692910469144253337152",
+    "93281038430308168666266744893370358931",
+    "213478409139021017741178598828001857702",
+];
+
+pub const FIELD128_COMPRESSION: ConstantsTable = ConstantsTable {
+    width: 4,
+    rate: 0,
+    alpha: 3,
+    full_rounds: 8,
+    partial_rounds: 84,
+    external_constants: &[
+        &[
+            "45334889744555977867253324815133748781",
+            "146544863696439775912455716647768119179",
+            "301369242970095846191952950607242954599",
+            "298629397416574851579925296463220409186",
+        ],
+        &[
+            "48508657175647058804441685579923860602",
+            "111224532338972866481338327100744874852",
+            "297608449307331747882931251613156159384",
+            "273959356172391017011936034849612120301",
+        ],
+        &[
+            "321280722512257502086968923718133851066",
+            "151738726210478233691322120997808434396",
+            "162209952280570339388833253066935155870",
+            "289888080871075522522206153108001168258",
+        ],
+        &[
+            "95756101844608760222084562419614001789",
+            "196792252036556922671264889365246862909",
+            "318514170659803234142690520073980255503",
+            "55508333576116448648950693615474562",
+        ],
+        &[
+            "321669542789169607228577354525314633996",
+            "206368664166370446903639281772453462975",
+            "110814380221158313717944288993108179751",
+            "49727194722450076848304911006367795715",
+        ],
+        &[
+            "335590561420368334362888334573536091842",
+            "336400663032701748335619757240411261334",
+            "265253857745828696840539362767342453779",
+            "249870583179940399467650599871978719019",
+        ],
+        &[
+            "96545086011640952136953464262184493129",
+            "306095934840591900764217358368882755187",
+            "259346674952034594394288053608016126811",
+            "334836610566049443288620856221324573084",
+        ],
+        &[
+            "320557320413451269900661438361358497802",
+            "119840533910238889162062050824446543839",
+            "167316314704221923046034062080058338366",
+            "29769735054445104474134403947357951811",
+        ],
+    ],
+    internal_constants: &[
+        "14092149625613505463488012760534272182",
+        "230630562180220443143456199177152545328",
+        "273495448317339669187702988743723542283",
+        "326602476756538764469248427808043099402",
+        "5550091138614982119153402954311269571",
+        "88480366888562885708330324538086697014",
+        "243086944774881562952843254435008736114",
+        "175450435528974791373349051269679805041",
+        "170093839351829786282320509648121011882",
+        "100323266533921657188181399897404188490",
+        "116176149845149167636188333221494686905",
+        "289946919710153721008866191045892539467",
+        "95287334348889521859011284382768027233",
+        "8132378270969742926180609180529685489",
+        "304748619843393095118463490693681130572",
+        "199056015602634465712035078840035107584",
+        "190447472057496228016344147092152642051",
+        "56417524046741046326048905682059616716",
+        "60525145013643958799076110197453778500",
+        "11627968811157369469690668683881586145",
+        "258066214168148634912019041982073663421",
+        "124326565990331150696199373491228969743",
+        "29963444772899228882753903844557693427",
+        "25209076609525522284000400247698918437",
+        "239678132010516150397785065538929814645",
+        "45287671901832557629415349181140910428",
+        "150148161408468718978410427713133731078",
+        "122642246833270561477024362135854439239",
+        "197924921178993907315132559647222473581",
+        "301823910641888557397535813456335462480",
+        "317288242195965092427282231951505273844",
+        "265704753525918832331284978605730767236",
+        "166077214946369576598969085724410334496",
+        "276423647080948545841519436127205458048",
+        "82889220812084126827793864117999695497",
+        "50579967505488935376423948213090342947",
+        "334515191235933125499961914947167531921",
+        "218006539600654797636081510474998365513",
+        "104887280565638376247848641498184600638",
+        "166267034721168298882420380145256136792",
+        "59373201040345899245958608132435140159",
+        "64557007788467392262975955952685582709",
+        "50168795214879636384709856643297060068",
+        "213832432764562320003424597980191295051",
+        "300787855729536227178237965021152567587",
+        "209740877607290453533419926861152003871",
+        "300300389854321472569734042543984658279",
+        "225665517425989082695782261464095349410",
+        "33580276360129016859747134133400469444",
+        "2742757544661910506313892095329427415",
+        "314461378329393122474068272413797290881",
+        "39505739512426665728378032599561415858",
+        "253938782143833465828071532950092557470",
+        "39806397199854774211320328683471344511",
+        "47542106719510597602311542943550376286",
+        "190533062082795573135298564383059237595",
+        "273251636887546447132558276565614178654",
+        "60524279511026374471800382326248010422",
+        "56178164069845181707160787651761442690",
+        "148519815673411819064636337883526431380",
+        "72457727941767275856915142197401765160",
+        "118153848689785577022641484347146768524",
+        "1583090264006501960240891904895633940",
+        "331363667599237892738869899942314600242",
+        "322197945080049992135827503432383958940",
+        "190019309659762964294213233634749998092",
+        "95929744171164478292916206112979545163",
+        "66299944707002371965047520180072142881",
+        "7544320188974688381025468643178819634",
+        "237878960544115095556427930503884386005",
+        "107795414844348493137809406227070971912",
+        "206783509738082233531662280131620429190",
+        "227269635713215588246831715675889067992",
+        "221510898104806679631573999879716986759",
+        "274817965136460835435505744855267754142",
+        "7169469396379708971808252461293690802",
+        "188399375983864785894900758604751600445",
+        "237489596912387758357207606791082066850",
+        "71592236883723218403115966665352861240",
+        "145809784373679566504371337487162593519",
+        "130746412139973159914619304323052813837",
+        "202878500441413274054684069686514419652",
+        "323771562461565289508712475851778166497",
+        "271639270342091884089368459068378224971",
+    ],
+    internal_diagonal: &[
+        "204711506856857474945316734259386942835",
+        "37431293499101114715249744132950116521",
+        "263559348943788898902029069773241001572",
+        "88266605128020148004935386103937381020",
+    ],
+};
+
+// Permutation of [0, 1, ..., width - 1]
+#[cfg(test)]
+pub const FIELD128_COMPRESSION_TEST_VECTOR: &[&str] = &[
+    "241990387828945406088577560485976930484",
+    "170391092668101143486362845482327234223",
+    "276720948402710117169423398645015050313",
+    "11725709080876691864991358652350454574",
+];
+
+pub const FIELD256: ConstantsTable = ConstantsTable {
+    width: 3,
+    rate: 2,
+    alpha: 5,
+    full_rounds: 8,
+    partial_rounds: 56,
+    external_constants: &[
+        &[
+            "50207570499218320245539736680169582180207201335688461025883902752909290481781",
+            "24448666467656506447555018649749346340705294023832615387641453784702583464707",
+            "34092944507611308604157957266676007619644244199372265837364557849561670729974",
+        ],
+        &[
+            "46954129210702959446093971191783182601726081775951103310666314834569091037713",
+            "38612156878839717097806285947575477749087608521505464809942918879152074545066",
+            "19752610610343814834081989345964253902282700341539483876504601969121084774539",
+        ],
+        &[
+            "46567545048462867923299713424766325689670511126407629551256255807498976196546",
+            "9520793415506326549109545537894287560752519598132096386048093015534488804808",
+            "22814234098357034097599682726494820560934925862581927123816510593532324971186",
+        ],
+        &[
+            "3277621627834606517208177071759088097855048183641615082769528872043050020787",
+            "29230456498980145088774069819561206654397510279226264474986155631775387918911",
+            "19087113294497892618475669593723876605785307026981218038380435259594863105240",
+        ],
+        &[
+            "12223512212940898490637099274041460462396141039347535357045458873882555463357",
+            "38303396997432596702386767516397451323593912575332612154360830379392053206914",
+            "22036410604956114612821236049340218857819342570630542797129771263395458080620",
+        ],
+        &[
+            "37192730952750566048661687478966837716282449954097744656554007040959020474565",
+            "36081679736007244304057373212485447018505000534036639299643664258145321997492",
+            "16234972515157845156347254546302253756049884771079767299929154819334994133255",
+        ],
+        &[
+            "17904936716836157876541779131216061242385468767894988462968562345774780322977",
+            "21714179460476085146012765269900102771027760696893388661664278472625524265920",
+            "202996664767557293309193718693189493265571271995400556847175225021661909888",
+        ],
+        &[
+            "37151885815048325002754741932521917166471426068184400588909976421807273328551",
+            "50599005035688736160466092701933521471546835926523505590060682712026791966719",
+            "8986959445646103225184427425621185795926770872760594291948007853933732792000",
+        ],
+    ],
+    internal_constants: &[
+        "39932371919358015185769877859035474336011770016475087638554815294278664040916",
+        "12607949331462269429981198199999740921418125994747028428126661151190418292729",
+        "20398677688057466110325934731430812468657996794663167456321709689030080949228",
+        "51835224419566813714481533481210630888564327175625175437244377303858990291964",
+        "13278128079226679628648689279705910775020794457648431336050464485837924986341",
+        "20751788049060260683191405008569080723662271828149227137187075968560831545739",
+        "42421164250058173810994728364144776180689735894673627964404703973460802099146",
+        "21120353743307986506720883740380468652053382764895882204680310593048134053982",
+        "29526223376722400691172584788126610514669516909826971155598997488361793726636",
+        "34042041521558704677804677569712674569738576001717295340556848855085089618161",
+        "20252491387019425681551488261397157776479297799360691728406809731508542196845",
+        "25856554324149146992239414502939942208580094928192925471532421030223074525051",
+        "35603718839327251012037553292043899153393807438387129505923567878785822738162",
+        "29943022708270799252522211109308629054849337552699067311814388215768905671554",
+        "40623265267364613450776577487319920007897396936924051398790906883872334022964",
+        "38947272924417356803622776795797899233194116520680026665045628837194239730633",
+        "26397988737034501095129796920971941795766209722106383463197090306632188634870",
+        "27893799443241349360688137159923920340185830261519093384488134540544971987330",
+        "2963742902601529003553690631564645593518709846059084207036841793643477514707",
+        "31668552784983283483593666924944066737680315058069542500069213700768949573692",
+        "40208795410444394963490428737133513683110766973508056822474493355065333491217",
+        "25964807298150242099204032696543021731332498792173212422070959505270506288817",
+        "26149402682269665088314773514719203730233986608723938665192802061570851149320",
+        "1115361296285111421659408034287929280905078990986385263729179376131648187058",
+        "10466479494603471110085160358255184712338985686117376680963274257033378093044",
+        "20492577817846125120765219135044390230365666103475157006227551523345028416653",
+        "5550990570115355104018261990072269149174220738166262960442108003631983239538",
+        "35644299241250297444013458321960523350819444468750920467435977033543433033457",
+        "28985472609889148437362238985477137130699370756855680573161102595592058796055",
+        "38512783555872270132587822550082534368549655865577551370193909696135736697844",
+        "33142108522227543534522360938923911911826451727392146582555409447496143776616",
+        "51983328868751841763333305508408581327679527569253121079747950511616126915026",
+        "36977856966896247792845521268080245209261495204736600825933277828384329384776",
+        "49902385166182411565714880065207184671103831877637178515830399091476338505528",
+        "51018556817719970036598482047146806508458652113641304661951802129285113404097",
+        "22405375952478438071934186194392756316305143676541092887399118393981076553314",
+        "26376584034857786080333290889331925169513316008268823389497001028535947725689",
+        "37424032517134964350839532755101908718388519313839739220539192283411958332893",
+        "2514052438055955996166191181555087722391472372457485613396039637466284876008",
+        "39783515728841708906635525163190887374125387723021402958995674781425394128914",
+        "14523364456910312064741833824384915970721514893070438053344001112980722532883",
+        "10663098851045790689902302726099843869982323815663085170094254490745070606259",
+        "49868962293665097907388782035445001151064884165311457103769721965990239111289",
+        "22073551841352628264163147373911841152201793863183403625155779300264423096775",
+        "16843535002948632653135556540328830165745557071105115915108598045919908377862",
+        "16130557973030629467749350011718803160555727145300402111387384840879624701824",
+        "12067734632794838098229971919863170976260163840996333398973186642649256640429",
+        "32375789793387988413411781684593792367447324753388061006491590483422128447809",
+        "48514243919521853672715701575143024980352323372870136829455127558215514845965",
+        "21978759908294544525641933568334804209246435087447053956459074200373748623404",
+        "21117898976989526626092655597834329944770204871453734194062340048170016405636",
+        "35323380468525756165110923823329391671744887569724755972653963194068807182255",
+        "17511538428050954712881014060315994278594220490801476991572564405719714025630",
+        "46014166193410208886905995703574300197059187902569422048665032227521333534188",
+        "29068148512298892172989152905246739260393303176864606634686224073777164720128",
+        "6491223227212249233572575827345665488706358465091526904036815973519451608489",
+    ],
+    internal_diagonal: &["1", "1", "2"],
+};
+
+// Permutation of [0, 1, ..., width - 1]
+#[cfg(test)]
+pub const FIELD256_TEST_VECTOR: &[&str] = &[
+    "4445036057357251570178553596629851506515776403604136362430145739383239796567",
+    "25010735553997264903318496522005989920171115651732380209919941846831184829692",
+    "10659715172334382444103761470940006022090486827209539315471038643885829266458",
+];
+
+pub const FIELD256_COMPRESSION: ConstantsTable = ConstantsTable {
+    width: 2,
+    rate: 0,
+    alpha: 5,
+    full_rounds: 8,
+    partial_rounds: 56,
+    external_constants: &[
+        &[
+            "44510337639712444877093863969199054965277800588455612249278638908194748645831",
+            "21803715039317278198490310228838761820084178670568647145430631061363562182159",
+        ],
+        &[
+            "7624865858307587153533893753671854337113466346291121078558552645350285711947",
+            "40816250157678830542785454550323790288400761867270997552332922267166370848099",
+        ],
+        &[
+            "26700489303136047462599262740180012654857443933973506452655094204874268181798",
+            "29300041198680547975810813644545348954050411371551740473502764872245855641482",
+        ],
+        &[
+            "26494260871076350781917504826961109818301921647993891506179327799406892257760",
+            "51471943067203395853539598076816386277188697473371359746626216561944728278869",
+        ],
+        &[
+            "3016855966341940942362945070831598449263452162740443068106636587120133813841",
+            "44359946037263544412037026950871238242252808659579112059057515224238610475264",
+        ],
+        &[
+            "43152244706153179960199190077643761063187411353573876648717196437540942522243",
+            "689696542019772963652824302391747148371345736156441968199920937150846324178",
+        ],
+        &[
+            "15430965548073157307483612810585613268058653381913099676476333295969104580313",
+            "6818080688798147338389733045669363039935453230187429668753478534759877532079",
+        ],
+        &[
+            "7334952591623961814137085038760054155869462703285635189028314853534912444967",
+            "1395088935948449734081725164592118911882208841378506321094899799193420360392",
+        ],
+    ],
+    internal_constants: &[
+        "48874150250826827063647140518997592549563417409147246235831213929889330889464",
+        "49787130886622940646628207982474849305464467960406760686521606845929813913147",
+        "43672937506493322470130890010227422460105683953151094688032165492686807529714",
+        "5929373583590601619353793840106929273025491048347772550388315300478156302480",
+        "35087653160263082011551011896785452178273871331090954735353760094574180797326",
+        "46811190561503483095087189032015959148465356044838419985597715002375968521789",
+        "3011951966042824356793101436014075881633742606023423824609791334873649401619",
+        "5949000965032854376013985161729805610095473216976505768565157587449663833146",
+        "44407194440944549422962884120864337491414458688079798116475114348830479824132",
+        "756868613004458973360577644537468651009832005811964377612175868388980341238",
+        "13293243933107737951928282334791569607692876620282367672054893180625816893632",
+        "41007993900563419378450318427807675773574107233531791780559911428122060668864",
+        "32234194819559922425974652761643838211443296225838831687358769666643041225472",
+        "23172302197508009638107441698229967178757578829167657081073715837516157038684",
+        "36025164071480125389137319620343252251920437049927443652919962965645042660420",
+        "20585606416170880487041307637777839232517038894653375498347934603702403525799",
+        "49864990265274912108645272682223261996354786042911445790248422528668444967688",
+        "39934722872842035804029775488645871956511886709858512717725127998627130523912",
+        "39198754144978337534654702520273605486297255614756323128663775493042981926264",
+        "20857205525756474383857323509517945359548153106777905032259140536596758842151",
+        "5512348932066875222255592673449822544023582557729178288775446420395599163714",
+        "20722592968207591585193709289557966995643707360519106502308701025990663556112",
+        "37998633152333475045376762610205951441947316428701472139959038991258723083573",
+        "19421269742609173994970218921590288196829015512476170234735579602917648585528",
+        "38015410591674700109176981164929629504879929166582206795827935147005325179107",
+        "4839910768263945909615643698821897421248623201436406727882801614226282796736",
+        "13920948384274828210917386586592591296235909076917892178237942711445546791673",
+        "17228860181078068965008756660486864527552317469394243328944319614877053158985",
+        "49940012233787551970719440300197866554675665187348390484098206972627022147562",
+        "22953533088186447995354081903847946065608888217162100116127853233026059928601",
+        "10763107301772445560209819564880953581415947909035345171575074311681161298071",
+        "44640541969065703218376218137336488126193962189090743983027473909004591005110",
+        "28114847165366527836483079218599759916977495113224195917000936494217008192920",
+        "18283550941916909218700813231310605366388645926154486394285595766228045413625",
+        "30389705678922861060451248158091717271149867497299034518488462066558473269323",
+        "5225053507977526915087521556753153784014570893360182277133733916305847901685",
+        "6415959674406994527926577281892903938752462546208243491413197850838054531309",
+        "27405158324847355508137581112610440419528359063212329651521367590453620664715",
+        "40769605646379138505148188530827566885080214731045294558871607209111126194095",
+        "48129579774003119688596002306212324161843037429858016339699621820102605427644",
+        "7997971756720021159944469497338589576274927083648880041157399707368533588880",
+        "6607365721455494131538291399270606885299331260642673367824120258028929960196",
+        "21147546855549437029090155261621026675903426004114397528843069540056819603379",
+        "39735488380328308224882090918653820728609287965797457931498626424771729680083",
+        "644670877965064524210551594696783352968769640736136597131126076665060695352",
+        "11402421169443522732555318574117317048162495828149027697536531039073930716885",
+        "25592766393325077419266300093223136010231473582289694978281322539225425070276",
+        "4950298165823256166744043731905545438979995302907644128064358497521419822771",
+        "8637526951198910623412711502180127909569423518267964720449882636729937122548",
+        "158159284104513041134560852123253815880535856963780308706041338038440574213",
+        "26137390109925415773485972137796240914504343095763560925008597574910837706855",
+        "46338418500488127372099190113506206184734050965961800958163291841845239921786",
+        "33589831953113957046694009828821410627453745834585526303925241031868514123279",
+        "24376217791876137094568586161720692274532957572326875543375783682787641414112",
+        "35089955462251327697952669388224441087588775866081190046882802874774996202430",
+        "42103069991328718504908163557758951872256121802934734299949054578240590812048",
+    ],
+    internal_diagonal: &["1", "2"],
+};
+
+// Permutation of [0, 1, ..., width - 1]
+#[cfg(test)]
+pub const FIELD256_COMPRESSION_TEST_VECTOR: &[&str] = &[
+    "31888399364419207064781369916789424080215845092961949687910055106171506011117",
+    "41025926173474295409820621501017586368001687971409061784109134370552291163530",
+];
@@ -0,0 +1,531 @@
+// The Poseidon2 permutation (https://eprint.iacr.org/2023/323) and a duplex sponge built on it.
+//
+// The parameters for Field64, Field128 and Field256 are precomputed by
+// scripts/poseidon2_constants.py, see there for how they are derived. They are instances of our
+// own rather than those of the Poseidon2 reference implementation, so hashes are not expected to
+// match other implementations.
+
+use std::sync::Arc;
+
+use ark_crypto_primitives::sponge::{
+    Absorb, CryptographicSponge, DuplexSpongeMode, FieldElementSize,
+};
+use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use lazy_static::lazy_static;
+
+use super::fields::{Field128, Field256, Field64};
+use crate::metrics::{self, Metric};
+
+mod constants;
+
+// Parameters as emitted by the generation script
+struct ConstantsTable {
+    width: usize,
+    rate: usize,
+    alpha: u64,
+    full_rounds: usize,
+    partial_rounds: usize,
+    external_constants: &'static [&'static [&'static str]],
+    internal_constants: &'static [&'static str],
+    internal_diagonal: &'static [&'static str],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Poseidon2Config<F: PrimeField> {
+    pub width: usize,
+    // Zero for instances only used for compression
+    pub rate: usize,
+    pub alpha: u64,
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+    // One vector of width constants per full round
+    pub external_constants: Vec<Vec<F>>,
+    // One constant per partial round
+    pub internal_constants: Vec<F>,
+    // The internal matrix is J + diag(internal_diagonal), where J is the all ones matrix
+    pub internal_diagonal: Vec<F>,
+}
+
+// Fields for which we have precomputed Poseidon2 parameters
+pub trait Poseidon2Field: PrimeField + Absorb {
+    // The instance used by the sponge, with a capacity of 256 bits
+    fn poseidon2_config() -> Arc<Poseidon2Config<Self>>;
+
+    // The instance used to compress two digests of capacity elements each, which is twice as
+    // wide as the capacity
+    fn poseidon2_compression_config() -> Arc<Poseidon2Config<Self>>;
+}
+
+// Parsing the constants is not free, so we only do it once per instance and share the result
+lazy_static! {
+    static ref FIELD64: Arc<Poseidon2Config<Field64>> =
+        Arc::new(Poseidon2Config::from_table(&constants::FIELD64));
+    static ref FIELD128: Arc<Poseidon2Config<Field128>> =
+        Arc::new(Poseidon2Config::from_table(&constants::FIELD128));
+    static ref FIELD128_COMPRESSION: Arc<Poseidon2Config<Field128>> = Arc::new(
+        Poseidon2Config::from_table(&constants::FIELD128_COMPRESSION)
+    );
+    static ref FIELD256: Arc<Poseidon2Config<Field256>> =
+        Arc::new(Poseidon2Config::from_table(&constants::FIELD256));
+    static ref FIELD256_COMPRESSION: Arc<Poseidon2Config<Field256>> = Arc::new(
+        Poseidon2Config::from_table(&constants::FIELD256_COMPRESSION)
+    );
+}
+
+impl Poseidon2Field for Field64 {
+    fn poseidon2_config() -> Arc<Poseidon2Config<Self>> {
+        FIELD64.clone()
+    }
+
+    // The sponge instance is already twice as wide as its capacity
+    fn poseidon2_compression_config() -> Arc<Poseidon2Config<Self>> {
+        FIELD64.clone()
+    }
+}
+
+impl Poseidon2Field for Field128 {
+    fn poseidon2_config() -> Arc<Poseidon2Config<Self>> {
+        FIELD128.clone()
+    }
+
+    fn poseidon2_compression_config() -> Arc<Poseidon2Config<Self>> {
+        FIELD128_COMPRESSION.clone()
+    }
+}
+
+impl Poseidon2Field for Field256 {
+    fn poseidon2_config() -> Arc<Poseidon2Config<Self>> {
+        FIELD256.clone()
+    }
+
+    fn poseidon2_compression_config() -> Arc<Poseidon2Config<Self>> {
+        FIELD256_COMPRESSION.clone()
+    }
+}
+
+fn parse<F: PrimeField>(values: &[&str]) -> Vec<F> {
+    values
+        .iter()
+        .map(|value| F::from_str(value).ok().unwrap())
+        .collect()
+}
+
+impl<F: PrimeField> Poseidon2Config<F> {
+    fn from_table(table: &ConstantsTable) -> Self {
+        let config = Self {
+            width: table.width,
+            rate: table.rate,
+            alpha: table.alpha,
+            full_rounds: table.full_rounds,
+            partial_rounds: table.partial_rounds,
+            external_constants: table
+                .external_constants
+                .iter()
+                .map(|constants| parse(constants))
+                .collect(),
+            internal_constants: parse(table.internal_constants),
+            internal_diagonal: parse(table.internal_diagonal),
+        };
+        assert!(config.rate < config.width);
+        assert_eq!(config.external_constants.len(), config.full_rounds);
+        assert_eq!(config.internal_constants.len(), config.partial_rounds);
+        assert_eq!(config.internal_diagonal.len(), config.width);
+        config
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.width - self.rate
+    }
+
+    pub fn permute(&self, state: &mut [F]) {
+        assert_eq!(state.len(), self.width);
+
+        self.external_layer(state);
+        let (first_half, second_half) = self.external_constants.split_at(self.full_rounds / 2);
+        for constants in first_half {
+            self.full_round(state, constants);
+        }
+        for constant in &self.internal_constants {
+            state[0] += constant;
+            state[0] = state[0].pow([self.alpha]);
+            self.internal_layer(state);
+        }
+        for constants in second_half {
+            self.full_round(state, constants);
+        }
+    }
+
+    fn full_round(&self, state: &mut [F], constants: &[F]) {
+        for (s, c) in state.iter_mut().zip(constants) {
+            *s += c;
+            *s = s.pow([self.alpha]);
+        }
+        self.external_layer(state);
+    }
+
+    // Multiplication by M_E: circ(2, 1) or circ(2, 1, 1) for widths 2 and 3, and
+    // circ(2 M4, M4, ..., M4) for widths that are multiples of 4
+    fn external_layer(&self, state: &mut [F]) {
+        if state.len() <= 3 {
+            let sum: F = state.iter().sum();
+            state.iter_mut().for_each(|s| *s += sum);
+            return;
+        }
+
+        let mut chunks = state.chunks_exact_mut(4);
+        for chunk in &mut chunks {
+            apply_m4(chunk);
+        }
+        assert!(
+            chunks.into_remainder().is_empty(),
+            "width must be 2, 3 or a multiple of 4"
+        );
+        let sums: Vec<F> = (0..4)
+            .map(|i| state.iter().skip(i).step_by(4).sum())
+            .collect();
+        for (i, s) in state.iter_mut().enumerate() {
+            *s += sums[i % 4];
+        }
+    }
+
+    // Multiplication by M_I = J + diag(internal_diagonal)
+    fn internal_layer(&self, state: &mut [F]) {
+        let sum: F = state.iter().sum();
+        for (s, d) in state.iter_mut().zip(&self.internal_diagonal) {
+            *s = *s * d + sum;
+        }
+    }
+}
+
+// Multiplication by M4 = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]], with the
+// addition chain from the Poseidon2 paper
+fn apply_m4<F: PrimeField>(x: &mut [F]) {
+    let t0 = x[0] + x[1];
+    let t1 = x[2] + x[3];
+    let t2 = x[1].double() + t1;
+    let t3 = x[3].double() + t0;
+    let t4 = t1.double().double() + t3;
+    let t5 = t0.double().double() + t2;
+    let t6 = t3 + t5;
+    let t7 = t2 + t4;
+    x[0] = t6;
+    x[1] = t5;
+    x[2] = t7;
+    x[3] = t4;
+}
+
+// Duplex sponge over the Poseidon2 permutation. The first capacity elements of the state are the
+// capacity, the remaining rate elements are the rate.
+#[derive(Clone)]
+pub struct Poseidon2Sponge<F: PrimeField> {
+    config: Arc<Poseidon2Config<F>>,
+    state: Vec<F>,
+    mode: DuplexSpongeMode,
+}
+
+impl<F: PrimeField> Poseidon2Sponge<F> {
+    fn permute(&mut self) {
+        metrics::record(Metric::Permutation, 1);
+        self.config.permute(&mut self.state);
+    }
+
+    fn absorb_internal(&mut self, mut rate_start_index: usize, elements: &[F]) {
+        let capacity = self.config.capacity();
+        let rate = self.config.rate;
+        let mut remaining = elements;
+        loop {
+            if rate_start_index + remaining.len() <= rate {
+                for (i, element) in remaining.iter().enumerate() {
+                    self.state[capacity + rate_start_index + i] += element;
+                }
+                self.mode = DuplexSpongeMode::Absorbing {
+                    next_absorb_index: rate_start_index + remaining.len(),
+                };
+                return;
+            }
+
+            let num_absorbed = rate - rate_start_index;
+            for (i, element) in remaining[..num_absorbed].iter().enumerate() {
+                self.state[capacity + rate_start_index + i] += element;
+            }
+            self.permute();
+            remaining = &remaining[num_absorbed..];
+            rate_start_index = 0;
+        }
+    }
+
+    fn squeeze_internal(&mut self, mut rate_start_index: usize, output: &mut [F]) {
+        let capacity = self.config.capacity();
+        let rate = self.config.rate;
+        let mut output_remaining = output;
+        loop {
+            if rate_start_index + output_remaining.len() <= rate {
+                output_remaining.copy_from_slice(
+                    &self.state[capacity + rate_start_index
+                        ..capacity + rate_start_index + output_remaining.len()],
+                );
+                self.mode = DuplexSpongeMode::Squeezing {
+                    next_squeeze_index: rate_start_index + output_remaining.len(),
+                };
+                return;
+            }
+
+            let num_squeezed = rate - rate_start_index;
+            output_remaining[..num_squeezed].copy_from_slice(
+                &self.state
+                    [capacity + rate_start_index..capacity + rate_start_index + num_squeezed],
+            );
+            self.permute();
+            output_remaining = &mut output_remaining[num_squeezed..];
+            rate_start_index = 0;
+        }
+    }
+
+    pub fn squeeze_native_field_elements(&mut self, num_elements: usize) -> Vec<F> {
+        let mut output = vec![F::zero(); num_elements];
+        match self.mode {
+            DuplexSpongeMode::Absorbing { .. } => {
+                self.permute();
+                self.squeeze_internal(0, &mut output);
+            }
+            DuplexSpongeMode::Squeezing {
+                mut next_squeeze_index,
+            } => {
+                if next_squeeze_index == self.config.rate {
+                    self.permute();
+                    next_squeeze_index = 0;
+                }
+                self.squeeze_internal(next_squeeze_index, &mut output);
+            }
+        }
+        output
+    }
+}
+
+impl<F: PrimeField> CryptographicSponge for Poseidon2Sponge<F> {
+    type Config = Arc<Poseidon2Config<F>>;
+
+    fn new(config: &Self::Config) -> Self {
+        Self {
+            config: config.clone(),
+            state: vec![F::zero(); config.width],
+            mode: DuplexSpongeMode::Absorbing {
+                next_absorb_index: 0,
+            },
+        }
+    }
+
+    fn absorb(&mut self, input: &impl Absorb) {
+        let elements = input.to_sponge_field_elements_as_vec::<F>();
+        if elements.is_empty() {
+            return;
+        }
+
+        match self.mode {
+            DuplexSpongeMode::Absorbing {
+                mut next_absorb_index,
+            } => {
+                if next_absorb_index == self.config.rate {
+                    self.permute();
+                    next_absorb_index = 0;
+                }
+                self.absorb_internal(next_absorb_index, &elements);
+            }
+            DuplexSpongeMode::Squeezing { .. } => self.absorb_internal(0, &elements),
+        }
+    }
+
+    // Each squeezed element provides its (MODULUS_BIT_SIZE - 1) / 8 least significant bytes
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        let usable_bytes = (F::MODULUS_BIT_SIZE as usize - 1) / 8;
+        let num_elements = num_bytes.div_ceil(usable_bytes);
+        let mut bytes: Vec<u8> = self
+            .squeeze_native_field_elements(num_elements)
+            .iter()
+            .flat_map(|element| element.into_bigint().to_bytes_le()[..usable_bytes].to_vec())
+            .collect();
+        bytes.truncate(num_bytes);
+        bytes
+    }
+
+    // Each squeezed element provides its MODULUS_BIT_SIZE - 1 least significant bits
+    fn squeeze_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        let usable_bits = F::MODULUS_BIT_SIZE as usize - 1;
+        let num_elements = num_bits.div_ceil(usable_bits);
+        let mut bits: Vec<bool> = self
+            .squeeze_native_field_elements(num_elements)
+            .iter()
+            .flat_map(|element| element.into_bigint().to_bits_le()[..usable_bits].to_vec())
+            .collect();
+        bits.truncate(num_bits);
+        bits
+    }
+
+    fn squeeze_field_elements_with_sizes<F2: PrimeField>(
+        &mut self,
+        sizes: &[FieldElementSize],
+    ) -> Vec<F2> {
+        // Elements of the native field are squeezed directly
+        if F2::characteristic() == F::characteristic()
+            && sizes.iter().all(|size| *size == FieldElementSize::Full)
+        {
+            return self
+                .squeeze_native_field_elements(sizes.len())
+                .iter()
+                .map(|element| F2::from_le_bytes_mod_order(&element.into_bigint().to_bytes_le()))
+                .collect();
+        }
+
+        // As many bits as can be uniformly sampled in F2
+        let num_bits = |size: &FieldElementSize| match size {
+            FieldElementSize::Full => F2::MODULUS_BIT_SIZE as usize - 1,
+            FieldElementSize::Truncated(bits) => *bits,
+        };
+        let total_bits = sizes.iter().map(num_bits).sum();
+        let bits = self.squeeze_bits(total_bits);
+        let mut offset = 0;
+        sizes
+            .iter()
+            .map(|size| {
+                let num_bits = num_bits(size);
+                let element =
+                    F2::from_bigint(F2::BigInt::from_bits_le(&bits[offset..offset + num_bits]))
+                        .unwrap();
+                offset += num_bits;
+                element
+            })
+            .collect()
+    }
+
+    fn squeeze_field_elements<F2: PrimeField>(&mut self, num_elements: usize) -> Vec<F2> {
+        self.squeeze_field_elements_with_sizes(&vec![FieldElementSize::Full; num_elements])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn external_matrix<F: PrimeField>(width: usize) -> Vec<Vec<F>> {
+        const M4: [[u64; 4]; 4] = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+        (0..width)
+            .map(|i| {
+                (0..width)
+                    .map(|j| match width {
+                        2 | 3 => F::from(if i == j { 2u64 } else { 1 }),
+                        _ => F::from(M4[i % 4][j % 4] * if i / 4 == j / 4 { 2 } else { 1 }),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn mat_vec<F: PrimeField>(matrix: &[Vec<F>], vector: &[F]) -> Vec<F> {
+        matrix
+            .iter()
+            .map(|row| row.iter().zip(vector).map(|(a, b)| *a * b).sum())
+            .collect()
+    }
+
+    fn check_linear_layers<F: PrimeField>(config: &Poseidon2Config<F>) {
+        let mut rng = ark_std::test_rng();
+        let state: Vec<F> = (0..config.width).map(|_| F::rand(&mut rng)).collect();
+
+        let mut external = state.clone();
+        config.external_layer(&mut external);
+        assert_eq!(
+            external,
+            mat_vec(&external_matrix::<F>(config.width), &state)
+        );
+
+        let internal_matrix: Vec<Vec<F>> = (0..config.width)
+            .map(|i| {
+                (0..config.width)
+                    .map(|j| {
+                        F::one()
+                            + if i == j {
+                                config.internal_diagonal[i]
+                            } else {
+                                F::zero()
+                            }
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut internal = state.clone();
+        config.internal_layer(&mut internal);
+        assert_eq!(internal, mat_vec(&internal_matrix, &state));
+    }
+
+    fn check_test_vector<F: PrimeField>(config: &Poseidon2Config<F>, expected: &[&str]) {
+        let mut state: Vec<F> = (0..config.width as u64).map(F::from).collect();
+        config.permute(&mut state);
+        assert_eq!(state, parse::<F>(expected));
+    }
+
+    #[test]
+    fn test_linear_layers() {
+        check_linear_layers(&Field64::poseidon2_config());
+        check_linear_layers(&Field128::poseidon2_config());
+        check_linear_layers(&Field128::poseidon2_compression_config());
+        check_linear_layers(&Field256::poseidon2_config());
+        check_linear_layers(&Field256::poseidon2_compression_config());
+    }
+
+    #[test]
+    fn test_permutation() {
+        check_test_vector(&Field64::poseidon2_config(), constants::FIELD64_TEST_VECTOR);
+        check_test_vector(
+            &Field128::poseidon2_config(),
+            constants::FIELD128_TEST_VECTOR,
+        );
+        check_test_vector(
+            &Field128::poseidon2_compression_config(),
+            constants::FIELD128_COMPRESSION_TEST_VECTOR,
+        );
+        check_test_vector(
+            &Field256::poseidon2_config(),
+            constants::FIELD256_TEST_VECTOR,
+        );
+        check_test_vector(
+            &Field256::poseidon2_compression_config(),
+            constants::FIELD256_COMPRESSION_TEST_VECTOR,
+        );
+    }
+
+    #[test]
+    fn test_sponge() {
+        let config = Field64::poseidon2_config();
+        let elements: Vec<Field64> = (0..10u64).map(Field64::from).collect();
+
+        // Absorbing in one go or element by element is the same
+        let mut a = Poseidon2Sponge::new(&config);
+        a.absorb(&elements);
+        let mut b = Poseidon2Sponge::new(&config);
+        for element in &elements {
+            b.absorb(element);
+        }
+        let squeezed: Vec<Field64> = a.squeeze_field_elements(6);
+        assert_eq!(squeezed, b.squeeze_field_elements::<Field64>(6));
+
+        // The first rate elements are the rate part of the permuted state
+        let mut state = vec![Field64::from(0u64); config.width];
+        for (i, element) in elements[..4].iter().enumerate() {
+            state[config.capacity() + i] += element;
+        }
+        config.permute(&mut state);
+        for (i, element) in elements[4..8].iter().enumerate() {
+            state[config.capacity() + i] += element;
+        }
+        config.permute(&mut state);
+        for (i, element) in elements[8..].iter().enumerate() {
+            state[config.capacity() + i] += element;
+        }
+        config.permute(&mut state);
+        assert_eq!(squeezed[..4], state[config.capacity()..]);
+
+        let bytes = a.squeeze_bytes(20);
+        assert_eq!(bytes.len(), 20);
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt::Display;
+
+use ark_crypto_primitives::merkle_tree::MultiPath;
+use ark_ff::{BigInteger, PrimeField};
+use sha3::{Digest, Keccak256};
+
+use super::{EvmParameters, MerkleConfig};
+use crate::{
+    crypto::{fields::Field256, merkle_tree::decode_paths},
+    stir::common::{Commitment, Proof},
+};
+
+// A 256-bit word of calldata, in big-endian
+pub type Word = [u8; 32];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalldataError {
+    // The proof does not have the shape the parameters require. The native verifier rejects such
+    // proofs, but they do not fit the layout the contract reads.
+    MalformedProof,
+}
+
+impl Display for CalldataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalldataError::MalformedProof => write!(f, "malformed proof"),
+        }
+    }
+}
+
+impl std::error::Error for CalldataError {}
+
+fn integer(value: u64) -> Word {
+    let mut word = [0; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn element(element: &Field256) -> Word {
+    let mut word = [0; 32];
+    word.copy_from_slice(&element.into_bigint().to_bytes_be());
+    word
+}
+
+fn polynomial(words: &mut Vec<Word>, coefficients: &[Field256]) {
+    words.push(integer(coefficients.len() as u64));
+    words.extend(coefficients.iter().map(element));
+}
+
+// A missing proof of work nonce is 2^64, which nonces never reach
+fn nonce(pow_nonce: Option<usize>) -> Word {
+    match pow_nonce {
+        Some(nonce) => integer(nonce as u64),
+        None => {
+            let mut word = [0; 32];
+            word[23] = 1;
+            word
+        }
+    }
+}
+
+// The number of opened leaves, then for each of them its index, the leaf, the sibling of the leaf
+// and the authentication path from the top of the tree down
+fn queries(
+    words: &mut Vec<Word>,
+    queries: &(Vec<Vec<Field256>>, MultiPath<MerkleConfig>),
+    leaf_size: usize,
+    path_len: usize,
+) -> Result<(), CalldataError> {
+    let (answers, multi_path) = queries;
+    let paths = decode_paths(multi_path).ok_or(CalldataError::MalformedProof)?;
+    if answers.len() != multi_path.leaf_indexes.len() || paths.len() != answers.len() {
+        return Err(CalldataError::MalformedProof);
+    }
+
+    words.push(integer(answers.len() as u64));
+    for ((&index, answer), (leaf_sibling, auth_path)) in
+        multi_path.leaf_indexes.iter().zip(answers).zip(paths)
+    {
+        if answer.len() != leaf_size || auth_path.len() != path_len {
+            return Err(CalldataError::MalformedProof);
+        }
+        words.push(integer(index as u64));
+        words.extend(answer.iter().map(element));
+        words.push(leaf_sibling.0);
+        words.extend(auth_path.iter().map(|digest| digest.0));
+    }
+    Ok(())
+}
+
+// The proof as the words that the contract of evm::solidity reads, in order:
+// - the commitment
+// - for each round: the root of g, the OOD answers, the ans and shake polynomials (each as its
+//   number of coefficients followed by the coefficients), the proof of work nonce, and the queries
+//   to the previous oracle
+// - the final polynomial, the proof of work nonce and the queries to the last oracle
+pub fn proof_words(
+    parameters: &EvmParameters,
+    commitment: &Commitment<MerkleConfig>,
+    proof: &Proof<Field256, MerkleConfig>,
+) -> Result<Vec<Word>, CalldataError> {
+    if proof.round_proofs.len() != parameters.num_rounds {
+        return Err(CalldataError::MalformedProof);
+    }
+    let leaf_size = parameters.folding_factor + parameters.salt_size;
+    // The oracle of round i has domain_size / (folding_factor * 2^i) leaves
+    let num_leaves =
+        (parameters.starting_degree << parameters.starting_rate) / parameters.folding_factor;
+    let path_len = |round: usize| (num_leaves >> round).ilog2() as usize - 1;
+
+    let mut words = vec![commitment.root.0];
+    for (round, round_proof) in proof.round_proofs.iter().enumerate() {
+        if round_proof.betas.len() != parameters.ood_samples {
+            return Err(CalldataError::MalformedProof);
+        }
+        words.push(round_proof.g_root.0);
+        words.extend(round_proof.betas.iter().map(element));
+        polynomial(&mut words, &round_proof.ans_polynomial.coeffs);
+        polynomial(&mut words, &round_proof.shake_polynomial.coeffs);
+        words.push(nonce(round_proof.pow_nonce));
+        queries(
+            &mut words,
+            &round_proof.queries_to_prev,
+            leaf_size,
+            path_len(round),
+        )?;
+    }
+    polynomial(&mut words, &proof.final_polynomial);
+    words.push(nonce(proof.pow_nonce));
+    queries(
+        &mut words,
+        &proof.queries_to_final,
+        leaf_size,
+        path_len(parameters.num_rounds),
+    )?;
+    Ok(words)
+}
+
+// Calldata of a call to verify(uint256[]) with the given words
+pub fn encode_words(words: &[Word]) -> Vec<u8> {
+    let mut calldata = Keccak256::digest(b"verify(uint256[])")[..4].to_vec();
+    // The offset of the array, then its length and elements
+    calldata.extend_from_slice(&integer(32));
+    calldata.extend_from_slice(&integer(words.len() as u64));
+    for word in words {
+        calldata.extend_from_slice(word);
+    }
+    calldata
+}
+
+// Calldata of a call to verify(uint256[]) with the proof
+pub fn encode_calldata(
+    parameters: &EvmParameters,
+    commitment: &Commitment<MerkleConfig>,
+    proof: &Proof<Field256, MerkleConfig>,
+) -> Result<Vec<u8>, CalldataError> {
+    Ok(encode_words(&proof_words(parameters, commitment, proof)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+
+    use super::*;
+    use crate::{evm::test_parameters, ldt::Prover, stir::prover::StirProver};
+
+    #[test]
+    fn test_layout() {
+        let parameters = test_parameters(false);
+        let mut rng = ark_std::test_rng();
+        let prover = StirProver::new_full(parameters.clone());
+        let polynomial = DensePolynomial::rand(parameters.starting_degree - 1, &mut rng);
+        let (commitment, witness) = prover.commit(polynomial);
+        let proof = prover.prove(witness);
+
+        let words = proof_words(&parameters, &commitment, &proof).unwrap();
+        assert_eq!(words[0], commitment.root.0);
+        assert_eq!(words[1], proof.round_proofs[0].g_root.0);
+        assert_eq!(words[2], element(&proof.round_proofs[0].betas[0]));
+
+        let calldata = encode_calldata(&parameters, &commitment, &proof).unwrap();
+        assert_eq!(calldata.len(), 4 + 64 + 32 * words.len());
+        assert_eq!(calldata[4..36], integer(32));
+        assert_eq!(calldata[36..68], integer(words.len() as u64));
+
+        let mut malformed = proof.clone();
+        malformed.round_proofs[0].betas.pop();
+        assert_eq!(
+            proof_words(&parameters, &commitment, &malformed),
+            Err(CalldataError::MalformedProof)
+        );
+    }
+}
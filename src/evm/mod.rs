@@ -0,0 +1,232 @@
+// On-chain verification of STIR proofs. For fixed parameters over Field256 with Keccak-256 Merkle
+// trees (crypto::merkle_tree::keccak) and the Keccak-256 transcript (crypto::fs::keccak), which
+// only use the keccak256 opcode and 256-bit modular arithmetic, solidity::generate_verifier emits
+// a Solidity contract checking the proofs that calldata::encode_calldata encodes.
+
+use crate::{
+    crypto::{fields::Field256, fs, merkle_tree},
+    stir::parameters::FullParameters,
+};
+
+pub mod calldata;
+pub mod solidity;
+
+pub type MerkleConfig = merkle_tree::keccak::MerkleTreeParams<Field256>;
+pub type FSConfig = fs::keccak::Sponge;
+pub type EvmParameters = FullParameters<Field256, MerkleConfig, FSConfig>;
+
+// Lowercase hexadecimal, without prefix
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+pub(crate) fn test_parameters(zero_knowledge: bool) -> EvmParameters {
+    use crate::{
+        parameters::{Parameters, SoundnessType},
+        stir::parameters::FinalPhase,
+    };
+
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) =
+        merkle_tree::keccak::default_config::<Field256>(&mut rng, 4);
+    let parameters = Parameters {
+        security_level: 32,
+        protocol_security_level: 16,
+        starting_degree: 256,
+        stopping_degree: 4,
+        folding_factor: 4,
+        starting_rate: 2,
+        soundness_type: SoundnessType::Conjecture,
+        leaf_hash_params,
+        two_to_one_params,
+        fiat_shamir_config: fs::keccak::default_fs_config(),
+        _field: Default::default(),
+    };
+    if zero_knowledge {
+        FullParameters::zero_knowledge(parameters)
+    } else {
+        // The contract checks the final polynomial in coefficient form
+        FullParameters::from(parameters).with_final_phase(FinalPhase::Coefficients)
+    }
+}
+
+// Runs the generated contract in revm. It is compiled with solc, which is taken from the SOLC
+// environment variable or the PATH, so these tests are ignored by default and run with
+// cargo test --features evm -- --ignored
+#[cfg(all(test, feature = "evm"))]
+mod tests {
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+        process::Command,
+    };
+
+    use ark_ff::Field;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use revm::{
+        context::{
+            result::{ExecutionResult, Output},
+            Context, TxEnv,
+        },
+        database::{CacheDB, EmptyDB},
+        primitives::{eip7825, Bytes, TxKind},
+        ExecuteCommitEvm, ExecuteEvm, MainBuilder, MainContext,
+    };
+
+    use super::*;
+    use crate::{
+        ldt::{Prover, Verifier},
+        stir::{prover::StirProver, verifier::StirVerifier},
+    };
+
+    fn compile(source: &str) -> Vec<u8> {
+        let solc = std::env::var("SOLC").unwrap_or_else(|_| "solc".to_string());
+        let dir = std::env::temp_dir().join(format!("stir-evm-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Tests run concurrently, so each contract gets its own file
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        let path = dir.join(format!("StirVerifier-{:016x}.sol", hasher.finish()));
+        std::fs::write(&path, source).unwrap();
+
+        let output = Command::new(&solc)
+            .args(["--via-ir", "--optimize", "--bin"])
+            .arg(&path)
+            .output()
+            .unwrap_or_else(|err| panic!("cannot run {}: {}", solc, err));
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let bytecode = stdout
+            .lines()
+            .skip_while(|line| !line.starts_with("Binary:"))
+            .nth(1)
+            .unwrap()
+            .trim();
+        (0..bytecode.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&bytecode[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // Deploys the contract and calls it with each calldata, returning the gas used by the calls
+    // that returned true, and None for the ones that reverted
+    fn run(bytecode: Vec<u8>, calls: &[Vec<u8>]) -> Vec<Option<u64>> {
+        let mut evm = Context::mainnet()
+            .modify_cfg_chained(|cfg| cfg.disable_nonce_check = true)
+            .with_db(CacheDB::<EmptyDB>::default())
+            .build_mainnet();
+        let deployment = evm
+            .transact_commit(
+                TxEnv::builder()
+                    .kind(TxKind::Create)
+                    .data(Bytes::from(bytecode))
+                    .gas_limit(eip7825::TX_GAS_LIMIT_CAP)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        let ExecutionResult::Success {
+            output: Output::Create(_, Some(address)),
+            ..
+        } = deployment
+        else {
+            panic!("deployment failed: {:?}", deployment);
+        };
+
+        calls
+            .iter()
+            .map(|calldata| {
+                // Calls are limited to the transaction gas limit cap of EIP-7825
+                let result = evm
+                    .transact(
+                        TxEnv::builder()
+                            .kind(TxKind::Call(address))
+                            .data(Bytes::from(calldata.clone()))
+                            .gas_limit(eip7825::TX_GAS_LIMIT_CAP)
+                            .build()
+                            .unwrap(),
+                    )
+                    .unwrap()
+                    .result;
+                let gas_used = result.tx_gas_used();
+                match result {
+                    ExecutionResult::Success {
+                        output: Output::Call(output),
+                        ..
+                    } => {
+                        let mut expected = [0; 32];
+                        expected[31] = 1;
+                        assert_eq!(output.as_ref(), expected);
+                        Some(gas_used)
+                    }
+                    ExecutionResult::Revert { .. } => None,
+                    ExecutionResult::Halt { reason, .. } => panic!("halted: {:?}", reason),
+                    _ => unreachable!(),
+                }
+            })
+            .collect()
+    }
+
+    fn check_contract(parameters: EvmParameters) {
+        let bytecode = compile(&solidity::generate_verifier(&parameters).unwrap());
+
+        let mut rng = ark_std::test_rng();
+        let prover = StirProver::new_full(parameters.clone());
+        let verifier = StirVerifier::new_full(parameters.clone());
+        let polynomial = DensePolynomial::rand(parameters.witness_degree() - 1, &mut rng);
+        let (commitment, witness) = prover.commit(polynomial);
+        let proof = prover.prove(witness);
+        assert!(verifier.verify(&commitment, &proof));
+
+        let mut tampered = vec![];
+        let mut answer = proof.clone();
+        answer.round_proofs[0].queries_to_prev.0[0][1] += Field256::ONE;
+        tampered.push(answer);
+        let mut beta = proof.clone();
+        beta.round_proofs[1].betas[0] += Field256::ONE;
+        tampered.push(beta);
+        let mut shake_polynomial = proof.clone();
+        shake_polynomial.round_proofs[0].shake_polynomial.coeffs[0] += Field256::ONE;
+        tampered.push(shake_polynomial);
+        let mut final_polynomial = proof.clone();
+        final_polynomial.final_polynomial[0] += Field256::ONE;
+        tampered.push(final_polynomial);
+        let mut final_answer = proof.clone();
+        final_answer.queries_to_final.0[0][0] += Field256::ONE;
+        tampered.push(final_answer);
+        let mut pow_nonce = proof.clone();
+        pow_nonce.pow_nonce = pow_nonce.pow_nonce.map(|nonce| nonce + 1);
+        tampered.push(pow_nonce);
+
+        let mut calls = vec![calldata::encode_calldata(&parameters, &commitment, &proof).unwrap()];
+        for proof in &tampered {
+            assert!(!verifier.verify(&commitment, proof));
+            calls.push(calldata::encode_calldata(&parameters, &commitment, proof).unwrap());
+        }
+        // A proof missing its last word
+        let mut words = calldata::proof_words(&parameters, &commitment, &proof).unwrap();
+        words.pop();
+        calls.push(calldata::encode_words(&words));
+
+        let results = run(bytecode, &calls);
+        assert!(results[0].is_some());
+        assert!(results[1..].iter().all(Option::is_none));
+    }
+
+    #[test]
+    #[ignore = "needs solc"]
+    fn test_contract_matches_native() {
+        check_contract(test_parameters(false));
+    }
+
+    #[test]
+    #[ignore = "needs solc"]
+    fn test_contract_zero_knowledge() {
+        check_contract(test_parameters(true));
+    }
+}
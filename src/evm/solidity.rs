@@ -0,0 +1,165 @@
+use std::fmt::{Display, Write};
+
+use ark_crypto_primitives::sponge::CryptographicSponge;
+use ark_ff::{BigInteger, PrimeField};
+use ark_poly::EvaluationDomain;
+
+use super::{hex, EvmParameters, FSConfig};
+use crate::{crypto::fields::Field256, domain::Domain, stir::parameters::FinalPhase};
+
+// The contract, with the parameters and constants left out
+const TEMPLATE: &str = include_str!("verifier.sol");
+
+// Parameters that the contract does not support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsupportedParameters {
+    // The degree bound is not a power of two, so the committed function needs degree correction
+    DegreeCorrection,
+    // The final polynomial is not sent in coefficient form
+    FinalPhase,
+}
+
+impl Display for UnsupportedParameters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnsupportedParameters::DegreeCorrection => {
+                write!(f, "the contract does not support degree correction")
+            }
+            UnsupportedParameters::FinalPhase => write!(
+                f,
+                "the contract only supports sending the final polynomial in coefficient form"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UnsupportedParameters {}
+
+fn element_literal(element: Field256) -> String {
+    format!("0x{}", hex(&element.into_bigint().to_bytes_be()))
+}
+
+// A function returning values[round]
+fn per_round_function(name: &str, values: &[usize]) -> String {
+    let mut function = format!(
+        "    function {}(uint256 round) internal pure returns (uint256) {{\n",
+        name
+    );
+    let (last, values) = values.split_last().unwrap();
+    for (round, value) in values.iter().enumerate() {
+        writeln!(
+            function,
+            "        if (round == {}) return {};",
+            round, value
+        )
+        .unwrap();
+    }
+    writeln!(function, "        return {};\n    }}", last).unwrap();
+    function
+}
+
+fn constants(parameters: &EvmParameters) -> String {
+    let domain = Domain::<Field256>::new(parameters.starting_degree, parameters.starting_rate)
+        .expect("the starting domain is too large");
+    let mut two_256 = [0; 33];
+    two_256[32] = 1;
+    let initial_chaining_value = FSConfig::new(&parameters.fiat_shamir_config).chaining_value();
+
+    format!(
+        "    // The modulus of the field, and 2^256 mod P for the reduction of challenges
+    uint256 internal constant P = 0x{};
+    uint256 internal constant TWO_256_MOD_P = {};
+    // The chaining value of a new transcript
+    bytes32 internal constant INITIAL_CHAINING_VALUE = 0x{};
+    // The initial evaluation domain, whose generator is also the root of unity
+    uint256 internal constant DOMAIN_SIZE = {};
+    uint256 internal constant DOMAIN_GEN = {};
+    // Rounds, not counting the final one
+    uint256 internal constant NUM_ROUNDS = {};
+    uint256 internal constant FOLDING_FACTOR = {};
+    uint256 internal constant OOD_SAMPLES = {};
+    // The folding factor, and the salt in zero-knowledge mode
+    uint256 internal constant LEAF_SIZE = {};
+    // The final polynomial has at most this many coefficients
+    uint256 internal constant STOPPING_DEGREE = {};
+
+{}
+{}",
+        hex(&Field256::MODULUS.to_bytes_be()),
+        element_literal(Field256::from_le_bytes_mod_order(&two_256)),
+        hex(&initial_chaining_value),
+        domain.size(),
+        element_literal(domain.element(1)),
+        parameters.num_rounds,
+        parameters.folding_factor,
+        parameters.ood_samples,
+        parameters.folding_factor + parameters.salt_size,
+        parameters.stopping_degree,
+        per_round_function("repetitions", &parameters.repetitions),
+        per_round_function("powBits", &parameters.pow_bits)
+    )
+}
+
+// The Solidity source of a verifier contract for the given parameters. Its verify function takes
+// the words of evm::calldata::encode_calldata.
+pub fn generate_verifier(parameters: &EvmParameters) -> Result<String, UnsupportedParameters> {
+    assert_eq!(
+        Field256::MODULUS_BIT_SIZE.div_ceil(8),
+        32,
+        "the contract reads challenges from 48 bytes"
+    );
+    if parameters.degree_correction().is_some() {
+        return Err(UnsupportedParameters::DegreeCorrection);
+    }
+    if parameters.final_phase != FinalPhase::Coefficients {
+        return Err(UnsupportedParameters::FinalPhase);
+    }
+    let description: String = parameters
+        .to_string()
+        .lines()
+        .map(|line| format!("// {}\n", line).replace("// \n", "//\n"))
+        .collect();
+
+    Ok(TEMPLATE
+        .replace("/* @parameters */\n", &description)
+        .replace("/* @constants */\n", &constants(parameters)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evm::test_parameters;
+
+    #[test]
+    fn test_constants() {
+        let parameters = test_parameters(false);
+        let source = generate_verifier(&parameters).unwrap();
+        assert!(!source.contains("@constants") && !source.contains("@parameters"));
+
+        assert!(source
+            .contains("P = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001;"));
+        assert!(source.contains(&format!("NUM_ROUNDS = {};", parameters.num_rounds)));
+        for (round, repetitions) in parameters.repetitions[..parameters.num_rounds]
+            .iter()
+            .enumerate()
+        {
+            assert!(source.contains(&format!("if (round == {}) return {};", round, repetitions)));
+        }
+    }
+
+    #[test]
+    fn test_unsupported_parameters() {
+        let mut parameters = test_parameters(false);
+        parameters.degree_bound = 3 << 6;
+        assert_eq!(
+            generate_verifier(&parameters).err(),
+            Some(UnsupportedParameters::DegreeCorrection)
+        );
+
+        let parameters = test_parameters(false).with_final_phase(FinalPhase::Direct);
+        assert_eq!(
+            generate_verifier(&parameters).err(),
+            Some(UnsupportedParameters::FinalPhase)
+        );
+    }
+}
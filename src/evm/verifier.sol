@@ -0,0 +1,486 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+// STIR verifier generated by src/bin/solidity.rs, for the parameters below. Proofs use Keccak-256
+// Merkle trees (crypto::merkle_tree::keccak) and the Keccak-256 duplex sponge transcript
+// (crypto::fs::keccak) over the BLS12-381 scalar field, and are passed as the words produced by
+// evm::calldata::encode_calldata. The checks follow stir::verifier::StirVerifier step by step.
+//
+/* @parameters */
+contract StirVerifier {
+    error InvalidProof();
+
+/* @constants */
+    uint256 internal constant NO_NONCE = 1 << 64;
+
+    // The duplex sponge of crypto::fs::duplex
+    struct Transcript {
+        bytes32 chainingValue;
+        bytes pending;
+    }
+
+    // The verifier state between rounds, as in stir::verifier::VerificationState, together with
+    // the transcript, the position in the proof and the root of the current oracle
+    struct Context {
+        Transcript transcript;
+        uint256 pos;
+        bytes32 root;
+        uint256 round;
+        uint256 domainGen;
+        uint256 domainSize;
+        uint256 domainOffset;
+        uint256 foldingRandomness;
+        bool initial;
+        uint256 combRandomness;
+        uint256[] quotientSet;
+        uint256[] ansPolynomial;
+    }
+
+    // The messages and challenges of a round
+    struct Round {
+        bytes32 gRoot;
+        uint256[] oodPoints;
+        uint256[] betas;
+        uint256 combRandomness;
+        uint256 nextFoldingRandomness;
+        uint256[] ansPolynomial;
+        uint256[] shakePolynomial;
+        uint256[] indexes;
+        uint256 shakeRandomness;
+    }
+
+    // Returns true if the proof is accepted, and reverts with InvalidProof otherwise
+    function verify(uint256[] calldata proof) external view returns (bool) {
+        Context memory c;
+        c.transcript = Transcript(INITIAL_CHAINING_VALUE, "");
+        c.root = bytes32(readWord(c, proof));
+        absorb(c.transcript, abi.encodePacked(c.root));
+        c.foldingRandomness = challengeScalars(c.transcript, 1)[0];
+        c.domainGen = DOMAIN_GEN;
+        c.domainSize = DOMAIN_SIZE;
+        c.domainOffset = 1;
+        c.initial = true;
+
+        for (uint256 i = 0; i < NUM_ROUNDS; i++) {
+            round(c, proof);
+        }
+        finalRound(c, proof);
+
+        if (c.pos != proof.length) revert InvalidProof();
+        return true;
+    }
+
+    function round(Context memory c, uint256[] calldata proof) internal view {
+        Round memory r;
+        r.gRoot = bytes32(readWord(c, proof));
+        absorb(c.transcript, abi.encodePacked(r.gRoot));
+        r.oodPoints = challengeScalars(c.transcript, OOD_SAMPLES);
+        r.betas = readElements(c, proof, OOD_SAMPLES);
+        absorb(c.transcript, encodeElements(r.betas));
+        r.combRandomness = challengeScalars(c.transcript, 1)[0];
+        r.nextFoldingRandomness = challengeScalars(c.transcript, 1)[0];
+        r.ansPolynomial = readPolynomial(c, proof);
+        r.shakePolynomial = readPolynomial(c, proof);
+        uint256 nonce = readWord(c, proof);
+        r.indexes = challengeIndices(c.transcript, repetitions(c.round), c.domainSize / FOLDING_FACTOR);
+        checkGrind(c.transcript, powBits(c.round), nonce);
+        r.shakeRandomness = challengeScalars(c.transcript, 1)[0];
+
+        (uint256[] memory points, uint256[] memory values) = openQueries(c, proof, r.indexes);
+        checkShake(r, points, values);
+
+        // The next oracle is the quotient by the OOD and folded answers
+        uint256[] memory quotientSet = new uint256[](OOD_SAMPLES + points.length);
+        for (uint256 i = 0; i < OOD_SAMPLES; i++) {
+            quotientSet[i] = r.oodPoints[i];
+        }
+        for (uint256 i = 0; i < points.length; i++) {
+            quotientSet[OOD_SAMPLES + i] = points[i];
+        }
+        c.quotientSet = quotientSet;
+        c.ansPolynomial = r.ansPolynomial;
+        c.combRandomness = r.combRandomness;
+        c.initial = false;
+        c.root = r.gRoot;
+        c.domainOffset = mulmod(mulmod(c.domainOffset, c.domainOffset, P), DOMAIN_GEN, P);
+        c.domainGen = mulmod(c.domainGen, c.domainGen, P);
+        c.domainSize /= 2;
+        c.foldingRandomness = r.nextFoldingRandomness;
+        c.round += 1;
+    }
+
+    function finalRound(Context memory c, uint256[] calldata proof) internal view {
+        uint256[] memory finalPolynomial = readPolynomial(c, proof);
+        if (finalPolynomial.length > STOPPING_DEGREE) revert InvalidProof();
+        uint256 nonce = readWord(c, proof);
+        uint256[] memory indexes =
+            challengeIndices(c.transcript, repetitions(NUM_ROUNDS), c.domainSize / FOLDING_FACTOR);
+        checkGrind(c.transcript, powBits(NUM_ROUNDS), nonce);
+
+        (uint256[] memory points, uint256[] memory values) = openQueries(c, proof, indexes);
+        for (uint256 i = 0; i < points.length; i++) {
+            if (evaluate(finalPolynomial, points[i]) != values[i]) revert InvalidProof();
+        }
+    }
+
+    // The shake polynomial is the sum of (ans - y) / (X - x) over the quotient answers (x, y)
+    function checkShake(Round memory r, uint256[] memory points, uint256[] memory values) internal view {
+        uint256 n = OOD_SAMPLES + points.length;
+        uint256[] memory denominators = new uint256[](n);
+        for (uint256 i = 0; i < OOD_SAMPLES; i++) {
+            denominators[i] = sub(r.shakeRandomness, r.oodPoints[i]);
+        }
+        for (uint256 i = 0; i < points.length; i++) {
+            denominators[OOD_SAMPLES + i] = sub(r.shakeRandomness, points[i]);
+        }
+        batchInverse(denominators);
+
+        uint256 ansEval = evaluate(r.ansPolynomial, r.shakeRandomness);
+        uint256 sum = 0;
+        for (uint256 i = 0; i < n; i++) {
+            uint256 y = i < OOD_SAMPLES ? r.betas[i] : values[i - OOD_SAMPLES];
+            sum = addmod(sum, mulmod(sub(ansEval, y), denominators[i], P), P);
+        }
+        if (sum != evaluate(r.shakePolynomial, r.shakeRandomness)) revert InvalidProof();
+    }
+
+    // Opens the leaves of the current oracle at the sampled indexes, and returns the folded
+    // evaluations: the points they are at and their values
+    function openQueries(Context memory c, uint256[] calldata proof, uint256[] memory indexes)
+        internal
+        view
+        returns (uint256[] memory points, uint256[] memory values)
+    {
+        if (readWord(c, proof) != indexes.length) revert InvalidProof();
+        uint256 depth = log2(c.domainSize / FOLDING_FACTOR);
+        points = new uint256[](indexes.length);
+        values = new uint256[](indexes.length);
+        for (uint256 i = 0; i < indexes.length; i++) {
+            if (readWord(c, proof) != indexes[i]) revert InvalidProof();
+            uint256[] memory leaf = readElements(c, proof, LEAF_SIZE);
+            checkPath(c, proof, indexes[i], leaf, depth);
+            (points[i], values[i]) = fold(c, indexes[i], leaf);
+        }
+    }
+
+    // Reads the sibling of the leaf and the authentication path (from the top of the tree down),
+    // as in ark_crypto_primitives::merkle_tree::Path, and checks them against the current root
+    function checkPath(
+        Context memory c,
+        uint256[] calldata proof,
+        uint256 index,
+        uint256[] memory leaf,
+        uint256 depth
+    ) internal pure {
+        bytes32 node = hashNode(index, hashLeaf(leaf), bytes32(readWord(c, proof)));
+        uint256 end = c.pos + depth - 1;
+        c.pos = end;
+        for (uint256 level = 1; level < depth; level++) {
+            node = hashNode(index >> level, node, bytes32(proof[end - level]));
+        }
+        if (node != c.root) revert InvalidProof();
+    }
+
+    // The folded evaluation at the point index of the next domain, from the leaf of the current
+    // oracle at index
+    function fold(Context memory c, uint256 index, uint256[] memory leaf)
+        internal
+        view
+        returns (uint256 point, uint256 value)
+    {
+        uint256 generator = pow(c.domainGen, c.domainSize / FOLDING_FACTOR);
+        uint256[] memory xs = new uint256[](FOLDING_FACTOR);
+        xs[0] = mulmod(c.domainOffset, pow(c.domainGen, index), P);
+        for (uint256 j = 1; j < FOLDING_FACTOR; j++) {
+            xs[j] = mulmod(xs[j - 1], generator, P);
+        }
+        point = pow(xs[0], FOLDING_FACTOR);
+        uint256[] memory evaluations = c.initial ? leaf : virtualEvaluations(c, xs, leaf);
+        value = interpolate(xs, evaluations, point, c.foldingRandomness);
+    }
+
+    // The evaluations of the virtual oracle at xs, given those of the previous oracle, as in
+    // stir::verifier::VerificationState::query
+    function virtualEvaluations(Context memory c, uint256[] memory xs, uint256[] memory leaf)
+        internal
+        view
+        returns (uint256[] memory evaluations)
+    {
+        uint256 m = c.quotientSet.length;
+        uint256[] memory inverses = new uint256[](2 * FOLDING_FACTOR);
+        for (uint256 j = 0; j < FOLDING_FACTOR; j++) {
+            uint256 denominator = 1;
+            for (uint256 i = 0; i < m; i++) {
+                uint256 difference = sub(xs[j], c.quotientSet[i]);
+                // The native verifier does not define the quotient there either
+                if (difference == 0) revert InvalidProof();
+                denominator = mulmod(denominator, difference, P);
+            }
+            inverses[j] = denominator;
+            inverses[FOLDING_FACTOR + j] = sub(1, mulmod(c.combRandomness, xs[j], P));
+        }
+        batchInverse(inverses);
+
+        evaluations = new uint256[](FOLDING_FACTOR);
+        for (uint256 j = 0; j < FOLDING_FACTOR; j++) {
+            uint256 quotient = mulmod(sub(leaf[j], evaluate(c.ansPolynomial, xs[j])), inverses[j], P);
+            uint256 commonFactor = mulmod(c.combRandomness, xs[j], P);
+            uint256 scale = commonFactor != 1
+                ? mulmod(sub(1, pow(commonFactor, m + 1)), inverses[FOLDING_FACTOR + j], P)
+                : (m + 1) % P;
+            evaluations[j] = mulmod(quotient, scale, P);
+        }
+    }
+
+    // Evaluates at r the polynomial of degree < FOLDING_FACTOR with the given evaluations on the
+    // coset xs, of which offsetPow is the FOLDING_FACTOR-th power of any element. With
+    // Z = X^k - offsetPow, the Lagrange basis is L_j(r) = Z(r) * x_j / (k * offsetPow * (r - x_j)).
+    function interpolate(uint256[] memory xs, uint256[] memory evaluations, uint256 offsetPow, uint256 r)
+        internal
+        view
+        returns (uint256)
+    {
+        uint256[] memory inverses = new uint256[](FOLDING_FACTOR + 1);
+        for (uint256 j = 0; j < FOLDING_FACTOR; j++) {
+            uint256 difference = sub(r, xs[j]);
+            if (difference == 0) return evaluations[j];
+            inverses[j] = difference;
+        }
+        inverses[FOLDING_FACTOR] = mulmod(FOLDING_FACTOR, offsetPow, P);
+        batchInverse(inverses);
+
+        uint256 sum = 0;
+        for (uint256 j = 0; j < FOLDING_FACTOR; j++) {
+            sum = addmod(sum, mulmod(mulmod(evaluations[j], xs[j], P), inverses[j], P), P);
+        }
+        uint256 vanishing = sub(pow(r, FOLDING_FACTOR), offsetPow);
+        return mulmod(mulmod(vanishing, inverses[FOLDING_FACTOR], P), sum, P);
+    }
+
+    // Reading the proof
+
+    function readWord(Context memory c, uint256[] calldata proof) internal pure returns (uint256 word) {
+        word = proof[c.pos];
+        c.pos += 1;
+    }
+
+    // Field elements have to be canonical, as in their serialization
+    function readElements(Context memory c, uint256[] calldata proof, uint256 count)
+        internal
+        pure
+        returns (uint256[] memory elements)
+    {
+        elements = new uint256[](count);
+        for (uint256 i = 0; i < count; i++) {
+            elements[i] = readWord(c, proof);
+            if (elements[i] >= P) revert InvalidProof();
+        }
+    }
+
+    // A length followed by the coefficients, from the constant term up
+    function readPolynomial(Context memory c, uint256[] calldata proof)
+        internal
+        pure
+        returns (uint256[] memory coefficients)
+    {
+        uint256 length = readWord(c, proof);
+        if (length > proof.length - c.pos) revert InvalidProof();
+        coefficients = readElements(c, proof, length);
+    }
+
+    // Merkle trees
+
+    // The hash of the compressed serialization of the leaf: its length as a little-endian u64,
+    // then its elements in little-endian
+    function hashLeaf(uint256[] memory leaf) internal pure returns (bytes32) {
+        return keccak256(abi.encodePacked(le64(leaf.length), encodeElements(leaf)));
+    }
+
+    // The parent of node and sibling, where node is a left child if index is even
+    function hashNode(uint256 index, bytes32 node, bytes32 sibling) internal pure returns (bytes32) {
+        if ((index & 1) == 0) {
+            return keccak256(abi.encodePacked(node, sibling));
+        }
+        return keccak256(abi.encodePacked(sibling, node));
+    }
+
+    // Transcript
+
+    function absorb(Transcript memory t, bytes memory message) internal pure {
+        t.pending = abi.encodePacked(t.pending, le64(message.length), message);
+    }
+
+    function squeeze(Transcript memory t, uint256 length) internal pure returns (bytes memory output) {
+        if (t.pending.length > 0) {
+            t.chainingValue = keccak256(abi.encodePacked(bytes1(0x01), t.chainingValue, t.pending));
+            t.pending = "";
+        }
+        // Keccak-256 in counter mode
+        output = new bytes(length);
+        for (uint256 counter = 0; 32 * counter < length; counter++) {
+            bytes32 block_ =
+                keccak256(abi.encodePacked(bytes1(0x02), t.chainingValue, le64(length), le64(counter)));
+            for (uint256 i = 0; i < 32 && 32 * counter + i < length; i++) {
+                output[32 * counter + i] = block_[i];
+            }
+        }
+        t.chainingValue = keccak256(abi.encodePacked(bytes1(0x03), t.chainingValue));
+    }
+
+    // Each element is read from 48 bytes in little-endian, reduced modulo P
+    function challengeScalars(Transcript memory t, uint256 count) internal pure returns (uint256[] memory scalars) {
+        bytes memory output = squeeze(t, 48 * count);
+        scalars = new uint256[](count);
+        for (uint256 i = 0; i < count; i++) {
+            uint256 low = readLittleEndian(output, 48 * i, 32);
+            uint256 high = readLittleEndian(output, 48 * i + 32, 16);
+            scalars[i] = addmod(low % P, mulmod(high, TWO_256_MOD_P, P), P);
+        }
+    }
+
+    // Each index is a little-endian u64 reduced modulo range, which is a power of two. As in the
+    // native transcript, the indexes are deduplicated and sorted.
+    function challengeIndices(Transcript memory t, uint256 count, uint256 range)
+        internal
+        pure
+        returns (uint256[] memory)
+    {
+        uint256[] memory indexes = new uint256[](count);
+        for (uint256 i = 0; i < count; i++) {
+            indexes[i] = readLittleEndian(squeeze(t, 8), 0, 8) % range;
+        }
+        return sortedDistinct(indexes);
+    }
+
+    // Sorts the values by insertion, and drops the repeated ones
+    function sortedDistinct(uint256[] memory values) internal pure returns (uint256[] memory distinct) {
+        uint256 count = 0;
+        for (uint256 i = 0; i < values.length; i++) {
+            uint256 value = values[i];
+            uint256 j = i;
+            for (; j > 0 && values[j - 1] > value; j--) {
+                values[j] = values[j - 1];
+            }
+            values[j] = value;
+        }
+        for (uint256 i = 0; i < values.length; i++) {
+            if (i == 0 || values[i] != values[i - 1]) {
+                count++;
+            }
+        }
+        distinct = new uint256[](count);
+        count = 0;
+        for (uint256 i = 0; i < values.length; i++) {
+            if (i == 0 || values[i] != values[i - 1]) {
+                distinct[count] = values[i];
+                count++;
+            }
+        }
+    }
+
+    // The squeezed little-endian u32 has to have at least bits trailing zeros
+    function checkGrind(Transcript memory t, uint256 bits, uint256 nonce) internal pure {
+        if (bits == 0) return;
+        if (nonce >= NO_NONCE) revert InvalidProof();
+        absorb(t, abi.encodePacked(le64(nonce)));
+        uint256 pow_ = readLittleEndian(squeeze(t, 4), 0, 4);
+        if ((pow_ & ((1 << bits) - 1)) != 0) revert InvalidProof();
+    }
+
+    // Encoding
+
+    function readLittleEndian(bytes memory data, uint256 offset, uint256 length)
+        internal
+        pure
+        returns (uint256 value)
+    {
+        for (uint256 i = length; i > 0; i--) {
+            value = (value << 8) | uint256(uint8(data[offset + i - 1]));
+        }
+    }
+
+    function le64(uint256 value) internal pure returns (bytes8) {
+        uint64 v = uint64(value);
+        v = ((v >> 8) & 0x00ff00ff00ff00ff) | ((v & 0x00ff00ff00ff00ff) << 8);
+        v = ((v >> 16) & 0x0000ffff0000ffff) | ((v & 0x0000ffff0000ffff) << 16);
+        v = (v >> 32) | (v << 32);
+        return bytes8(v);
+    }
+
+    // The 32 byte little-endian encodings of the elements
+    function encodeElements(uint256[] memory elements) internal pure returns (bytes memory output) {
+        for (uint256 i = 0; i < elements.length; i++) {
+            output = abi.encodePacked(output, reverseBytes(elements[i]));
+        }
+    }
+
+    function reverseBytes(uint256 v) internal pure returns (uint256) {
+        uint256 m8 = 0x00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff00ff;
+        uint256 m16 = 0x0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff0000ffff;
+        uint256 m32 = 0x00000000ffffffff00000000ffffffff00000000ffffffff00000000ffffffff;
+        uint256 m64 = 0x0000000000000000ffffffffffffffff0000000000000000ffffffffffffffff;
+        v = ((v >> 8) & m8) | ((v & m8) << 8);
+        v = ((v >> 16) & m16) | ((v & m16) << 16);
+        v = ((v >> 32) & m32) | ((v & m32) << 32);
+        v = ((v >> 64) & m64) | ((v & m64) << 64);
+        return (v >> 128) | (v << 128);
+    }
+
+    // Arithmetic
+
+    function sub(uint256 a, uint256 b) internal pure returns (uint256) {
+        return addmod(a, P - b, P);
+    }
+
+    function pow(uint256 base, uint256 exponent) internal pure returns (uint256 result) {
+        result = 1;
+        uint256 square = base;
+        for (uint256 e = exponent; e > 0; e >>= 1) {
+            if ((e & 1) == 1) {
+                result = mulmod(result, square, P);
+            }
+            square = mulmod(square, square, P);
+        }
+    }
+
+    // Horner evaluation, coefficients from the constant term up
+    function evaluate(uint256[] memory coefficients, uint256 x) internal pure returns (uint256 result) {
+        for (uint256 i = coefficients.length; i > 0; i--) {
+            result = addmod(mulmod(result, x, P), coefficients[i - 1], P);
+        }
+    }
+
+    // Inverts the non-zero elements in place, like ark_ff::batch_inversion
+    function batchInverse(uint256[] memory elements) internal view {
+        uint256[] memory prefixes = new uint256[](elements.length);
+        uint256 product = 1;
+        for (uint256 i = 0; i < elements.length; i++) {
+            prefixes[i] = product;
+            if (elements[i] != 0) {
+                product = mulmod(product, elements[i], P);
+            }
+        }
+        uint256 inverse_ = inverse(product);
+        for (uint256 i = elements.length; i > 0; i--) {
+            uint256 element = elements[i - 1];
+            if (element != 0) {
+                elements[i - 1] = mulmod(inverse_, prefixes[i - 1], P);
+                inverse_ = mulmod(inverse_, element, P);
+            }
+        }
+    }
+
+    // x^(P - 2) with the modexp precompile
+    function inverse(uint256 x) internal view returns (uint256) {
+        // The lengths of the base, exponent and modulus, then their values
+        (bool success, bytes memory result) = address(0x05).staticcall(abi.encode(32, 32, 32, x, P - 2, P));
+        require(success);
+        return abi.decode(result, (uint256));
+    }
+
+    function log2(uint256 x) internal pure returns (uint256 result) {
+        for (uint256 y = x; y > 1; y >>= 1) {
+            result += 1;
+        }
+    }
+}
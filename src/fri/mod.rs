@@ -36,8 +36,8 @@ where
     F: FftField + PrimeField + Absorb,
     MerkleConfig: Config<Leaf = Vec<F>>,
     MerkleConfig::InnerDigest: Absorb,
-    FSConfig: CryptographicSponge,
-    FSConfig::Config: Clone,
+    FSConfig: CryptographicSponge + Send,
+    FSConfig::Config: Clone + Send + Sync,
 {
     type Prover = prover::FriProver<F, MerkleConfig, FSConfig>;
     type Verifier = verifier::FriVerifier<F, MerkleConfig, FSConfig>;
@@ -6,6 +6,7 @@ use std::fmt::Display;
 use std::ops::Deref;
 
 use crate::parameters::Parameters;
+use crate::transcript::IOPattern;
 use crate::utils;
 
 #[derive(Derivative)]
@@ -79,6 +80,25 @@ where
     }
 }
 
+impl<F, MerkleConfig, FSConfig> FullParameters<F, MerkleConfig, FSConfig>
+where
+    F: FftField,
+    MerkleConfig: Config,
+    FSConfig: CryptographicSponge,
+    FSConfig::Config: Clone,
+{
+    // The Fiat-Shamir interaction of the prover and the verifier
+    pub fn io_pattern(&self) -> IOPattern {
+        let mut pattern = IOPattern::new().commitment().challenge_scalars(1);
+        for _ in 0..self.num_rounds {
+            pattern = pattern.commitment().challenge_scalars(1);
+        }
+        pattern
+            .challenge_indices(self.repetitions)
+            .grind(self.pow_bits)
+    }
+}
+
 impl<F, MerkleConfig, FSConfig> Deref for FullParameters<F, MerkleConfig, FSConfig>
 where
     F: FftField,
@@ -8,8 +8,10 @@ use derivative::Derivative;
 
 use crate::{
     fri::{common::*, parameters::FullParameters},
-    ldt::Prover,
-    poly_utils, utils,
+    ldt::{Prover, ProverError},
+    poly_utils,
+    transcript::Transcript,
+    utils,
 };
 
 use crate::{domain::Domain, parameters::Parameters};
@@ -18,7 +20,8 @@ use crate::{domain::Domain, parameters::Parameters};
 #[derivative(Clone(bound = "F: Clone"))]
 pub struct Witness<F: FftField, MerkleConfig: Config> {
     pub(crate) domain: Domain<F>,
-    pub(crate) polynomial: DensePolynomial<F>,
+    // None if we committed to evaluations directly
+    pub(crate) polynomial: Option<DensePolynomial<F>>,
     pub(crate) merkle_tree: MerkleTree<MerkleConfig>,
     pub(crate) folded_evals: Vec<Vec<F>>,
 }
@@ -70,44 +73,56 @@ where
         let evals = witness_polynomial
             .evaluate_over_domain_by_ref(domain.backing_domain)
             .evals;
-        let folded_evals = utils::stack_evaluations(evals, self.parameters.folding_factor);
-        let merkle_tree = MerkleTree::<MerkleConfig>::new(
-            &self.parameters.leaf_hash_params,
-            &self.parameters.two_to_one_params,
-            &folded_evals,
-        )
-        .unwrap();
 
-        let initial_commitment = merkle_tree.root();
+        self.commit_to_evaluations(domain, evals, Some(witness_polynomial))
+    }
 
-        (
-            Commitment {
-                root: initial_commitment,
-            },
-            Witness {
-                domain,
-                polynomial: witness_polynomial,
-                merkle_tree,
-                folded_evals,
-            },
+    fn commit_evaluations(
+        &self,
+        evals: Vec<F>,
+    ) -> (Commitment<MerkleConfig>, Witness<F, MerkleConfig>) {
+        let domain = Domain::<F>::new(
+            self.parameters.starting_degree,
+            self.parameters.starting_rate,
         )
+        .unwrap();
+        assert_eq!(evals.len(), domain.size());
+
+        self.commit_to_evaluations(domain, evals, None)
     }
 
-    fn prove(&self, witness: Witness<F, MerkleConfig>) -> Proof<F, MerkleConfig> {
-        assert!(witness.polynomial.degree() < self.parameters.starting_degree);
+    fn try_prove(
+        &self,
+        witness: Witness<F, MerkleConfig>,
+    ) -> Result<Proof<F, MerkleConfig>, ProverError> {
+        let polynomial = match witness.polynomial {
+            Some(polynomial) if polynomial.degree() < self.parameters.starting_degree => polynomial,
+            // If we committed to evaluations (or to a polynomial of too large degree), we find
+            // the closest codeword to what we committed to. This is the only point where we
+            // interpolate.
+            _ => poly_utils::decoding::decode(
+                utils::unstack_evaluations(&witness.folded_evals),
+                witness.domain.backing_domain,
+                self.parameters.starting_degree,
+            )
+            .ok_or(ProverError::NotDecodable)?,
+        };
 
-        let mut sponge = FSConfig::new(&self.parameters.fiat_shamir_config);
-        sponge.absorb(&witness.merkle_tree.root());
+        let mut transcript = Transcript::<FSConfig>::new(
+            &self.parameters.fiat_shamir_config,
+            self.parameters.io_pattern(),
+        );
+        transcript.append_commitment(&witness.merkle_tree.root())?;
 
         let mut g_domain = witness.domain.clone();
-        let mut g_poly = witness.polynomial.clone();
+        let mut g_poly = polynomial;
 
         // Commit phase
         let mut commitments = vec![];
         let mut merkle_trees = vec![witness.merkle_tree.clone()];
         let mut folded_evals = vec![witness.folded_evals];
 
-        let mut folding_randomness = sponge.squeeze_field_elements(1)[0];
+        let mut folding_randomness = transcript.challenge_scalar()?;
         for _ in 0..self.parameters.num_rounds {
             // Fold the initial polynomial
             g_poly = poly_utils::folding::poly_fold(
@@ -196,9 +211,9 @@ where
             )
             .unwrap();
             let g_root = g_merkle.root();
-            sponge.absorb(&g_root);
+            transcript.append_commitment(&g_root)?;
 
-            folding_randomness = sponge.squeeze_field_elements(1)[0];
+            folding_randomness = transcript.challenge_scalar()?;
 
             commitments.push(g_root);
             merkle_trees.push(g_merkle);
@@ -213,10 +228,8 @@ where
 
         // Query phase
         let mut folded_evals_len = witness.domain.size() / self.parameters.folding_factor;
-        let mut query_indexes = utils::dedup(
-            (0..self.parameters.repetitions)
-                .map(|_| utils::squeeze_integer(&mut sponge, folded_evals_len)),
-        );
+        let mut query_indexes =
+            transcript.challenge_indices(self.parameters.repetitions, folded_evals_len)?;
 
         // Note that we include final round as well
         let mut round_proofs = vec![];
@@ -236,11 +249,109 @@ where
             round_proofs.push(RoundProofs { queries_to_prev });
         }
 
-        Proof {
+        let pow_nonce = transcript.grind(self.parameters.pow_bits)?;
+        transcript.finish()?;
+
+        Ok(Proof {
             final_polynomial: g_poly,
             commitments,
             round_proofs,
-            pow_nonce: utils::proof_of_work(&mut sponge, self.parameters.pow_bits),
-        }
+            pow_nonce,
+        })
+    }
+}
+
+impl<F, MerkleConfig, FSConfig> FriProver<F, MerkleConfig, FSConfig>
+where
+    F: FftField + PrimeField + Absorb,
+    MerkleConfig: Config<Leaf = Vec<F>>,
+    MerkleConfig::InnerDigest: Absorb,
+    FSConfig: CryptographicSponge,
+    FSConfig::Config: Clone,
+{
+    fn commit_to_evaluations(
+        &self,
+        domain: Domain<F>,
+        evals: Vec<F>,
+        polynomial: Option<DensePolynomial<F>>,
+    ) -> (Commitment<MerkleConfig>, Witness<F, MerkleConfig>) {
+        let folded_evals = utils::stack_evaluations(evals, self.parameters.folding_factor);
+        let merkle_tree = MerkleTree::<MerkleConfig>::new(
+            &self.parameters.leaf_hash_params,
+            &self.parameters.two_to_one_params,
+            &folded_evals,
+        )
+        .unwrap();
+
+        let initial_commitment = merkle_tree.root();
+
+        (
+            Commitment {
+                root: initial_commitment,
+            },
+            Witness {
+                domain,
+                polynomial,
+                merkle_tree,
+                folded_evals,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::{fields::Field64 as TestField, fs, merkle_tree::blake3 as mt},
+        fri::verifier::FriVerifier,
+        ldt::Verifier,
+        parameters::SoundnessType,
+    };
+    use ark_poly::DenseUVPolynomial;
+    use ark_serialize::CanonicalSerialize;
+
+    #[test]
+    fn test_commit_evaluations() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = mt::default_config::<TestField>(&mut rng, 4);
+        let parameters: Parameters<TestField, mt::MerkleTreeParams<TestField>, fs::blake3::Sponge> =
+            Parameters {
+                security_level: 32,
+                protocol_security_level: 32,
+                starting_degree: 64,
+                stopping_degree: 4,
+                folding_factor: 4,
+                starting_rate: 2,
+                soundness_type: SoundnessType::Conjecture,
+                leaf_hash_params,
+                two_to_one_params,
+                fiat_shamir_config: fs::blake3::default_fs_config(),
+                _field: Default::default(),
+            };
+        let prover = FriProver::new(parameters.clone());
+        let verifier = FriVerifier::new(parameters);
+
+        let polynomial = DensePolynomial::rand(63, &mut rng);
+        let domain = Domain::<TestField>::new(64, 2).unwrap();
+        let evals = polynomial
+            .evaluate_over_domain_by_ref(domain.backing_domain)
+            .evals;
+
+        let (commitment, witness) = prover.commit(polynomial);
+        let (evals_commitment, evals_witness) = prover.commit_evaluations(evals);
+        assert_eq!(commitment.root, evals_commitment.root);
+
+        let proof = prover.prove(evals_witness);
+        assert!(verifier.verify(&evals_commitment, &proof));
+
+        let mut proof_bytes = vec![];
+        let mut evals_proof_bytes = vec![];
+        prover
+            .prove(witness)
+            .serialize_compressed(&mut proof_bytes)
+            .unwrap();
+        proof.serialize_compressed(&mut evals_proof_bytes).unwrap();
+        assert_eq!(proof_bytes, evals_proof_bytes);
     }
 }
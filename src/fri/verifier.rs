@@ -1,16 +1,71 @@
 use std::collections::BTreeMap;
 
 use ark_crypto_primitives::{
-    merkle_tree::Config,
+    merkle_tree::{Config, LeafParam, TwoToOneParam},
     sponge::{Absorb, CryptographicSponge},
 };
-use ark_ff::{batch_inversion, FftField, PrimeField};
+use ark_ff::{FftField, PrimeField};
 use ark_poly::{EvaluationDomain, Polynomial};
-
-use crate::{domain::Domain, ldt::Verifier, parameters::Parameters, poly_utils, utils};
+use ark_std::cfg_iter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    domain::Domain,
+    ldt::{batch_result, Verifier, VerifierCostReport, VerifierError},
+    metrics,
+    parameters::Parameters,
+    poly_utils,
+    transcript::Transcript,
+    utils,
+};
 
 use super::{common::*, parameters::FullParameters};
 
+// The domains that the rounds fold, and the generators of their cosets. They only depend on the
+// parameters, so are shared between the proofs of a batch.
+struct RoundDomains<F: FftField> {
+    // The domain of every round, then the domain the final polynomial is checked over
+    domains: Vec<Domain<F>>,
+    generators: Vec<F>,
+}
+
+// The queries of a proof whose transcript was replayed, waiting for their inversions
+struct OpenedProof<F: FftField> {
+    folding_randomnessness: Vec<F>,
+    query_indexes: Vec<usize>,
+    coset_offsets: Vec<Vec<F>>,
+}
+
+// Checks the Merkle paths of every round against the initial commitment and the roots of the
+// proof. They do not depend on the transcript, so the paths of many proofs can be checked in
+// parallel.
+fn verify_merkle_paths<F, MerkleConfig>(
+    leaf_hash_params: &LeafParam<MerkleConfig>,
+    two_to_one_params: &TwoToOneParam<MerkleConfig>,
+    commitment: &Commitment<MerkleConfig>,
+    proof: &Proof<F, MerkleConfig>,
+    report: &mut VerifierCostReport,
+) -> bool
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = Vec<F>>,
+{
+    // We adjoin the initial commitment
+    let commitments = std::iter::once(&commitment.root).chain(&proof.commitments);
+    commitments
+        .zip(&proof.round_proofs)
+        .enumerate()
+        .all(|(num_round, (root, round_proof))| {
+            let (answers, proof) = &round_proof.queries_to_prev;
+            report.scope(num_round, || {
+                proof
+                    .verify(leaf_hash_params, two_to_one_params, root, answers)
+                    .unwrap()
+            })
+        })
+}
+
 pub struct FriVerifier<F, MerkleConfig, FSConfig>
 where
     F: FftField,
@@ -27,8 +82,8 @@ where
     F: FftField + PrimeField + Absorb,
     MerkleConfig: Config<Leaf = Vec<F>>,
     MerkleConfig::InnerDigest: Absorb,
-    FSConfig: CryptographicSponge,
-    FSConfig::Config: Clone,
+    FSConfig: CryptographicSponge + Send,
+    FSConfig::Config: Clone + Send + Sync,
 {
     type FullParameter = FullParameters<F, MerkleConfig, FSConfig>;
     type Commitment = Commitment<MerkleConfig>;
@@ -46,104 +101,270 @@ where
         }
     }
 
-    fn verify(
+    fn try_verify_with_cost_report(
         &self,
         commitment: &Commitment<MerkleConfig>,
         proof: &Proof<F, MerkleConfig>,
-    ) -> bool {
-        if proof.final_polynomial.degree() + 1 > self.parameters.stopping_degree {
-            return false;
-        }
+    ) -> (Result<(), VerifierError>, VerifierCostReport) {
+        let mut report = VerifierCostReport::new(self.parameters.num_rounds + 1);
+        let (result, total) =
+            metrics::with_counter(|| self.verify_and_report(commitment, proof, &mut report));
+        report.total = total;
+        (result, report)
+    }
 
-        // We do FS
-        let mut sponge = FSConfig::new(&self.parameters.fiat_shamir_config);
-        sponge.absorb(&commitment.root);
+    // Verifies many proofs, sharing the domains of every round and doing a single batch inversion
+    // for all of them. The Merkle paths of the proofs are checked in parallel.
+    fn verify_batch(
+        &self,
+        instances: &[(Commitment<MerkleConfig>, Proof<F, MerkleConfig>)],
+    ) -> Result<(), Vec<usize>> {
+        let num_rounds = self.parameters.num_rounds;
+        let (leaf_hash_params, two_to_one_params) = (
+            &self.parameters.leaf_hash_params,
+            &self.parameters.two_to_one_params,
+        );
+        let well_formed: Vec<_> = instances
+            .iter()
+            .map(|(_, proof)| self.check_shape(proof))
+            .collect();
+        let merkle_paths: Vec<bool> = cfg_iter!(instances)
+            .zip(&well_formed)
+            .map(|((commitment, proof), well_formed)| {
+                *well_formed
+                    && verify_merkle_paths(
+                        leaf_hash_params,
+                        two_to_one_params,
+                        commitment,
+                        proof,
+                        &mut VerifierCostReport::new(num_rounds + 1),
+                    )
+            })
+            .collect();
 
-        let mut folding_randomnessness: Vec<F> = vec![];
-        folding_randomnessness.push(sponge.squeeze_field_elements(1)[0]);
-        // Absorb the roots
-        for commitment in &proof.commitments {
-            sponge.absorb(&commitment);
-            folding_randomnessness.push(sponge.squeeze_field_elements(1)[0]);
+        let (indexes, candidates): (Vec<_>, Vec<_>) = instances
+            .iter()
+            .zip(merkle_paths)
+            .enumerate()
+            .filter(|(_, (_, merkle_paths))| *merkle_paths)
+            .map(|(i, ((commitment, proof), _))| (i, (commitment, proof)))
+            .unzip();
+        let mut accepted = vec![false; instances.len()];
+        for (i, result) in indexes.into_iter().zip(self.verify_many(
+            &self.round_domains(),
+            &candidates,
+            &mut VerifierCostReport::new(num_rounds + 1),
+        )) {
+            accepted[i] = result.is_ok();
         }
+        batch_result(&accepted)
+    }
+}
 
-        // We adjoin the initial commitment
-        let commitments: Vec<_> = std::iter::once(commitment.root.clone())
-            .chain(proof.commitments.iter().cloned())
-            .collect();
+impl<F, MerkleConfig, FSConfig> FriVerifier<F, MerkleConfig, FSConfig>
+where
+    F: FftField + PrimeField + Absorb,
+    MerkleConfig: Config<Leaf = Vec<F>>,
+    MerkleConfig::InnerDigest: Absorb,
+    FSConfig: CryptographicSponge + Send,
+    FSConfig::Config: Clone + Send + Sync,
+{
+    fn verify_and_report(
+        &self,
+        commitment: &Commitment<MerkleConfig>,
+        proof: &Proof<F, MerkleConfig>,
+        report: &mut VerifierCostReport,
+    ) -> Result<(), VerifierError> {
+        if !self.check_shape(proof) {
+            return Err(VerifierError::Rejected);
+        }
 
         // Verify merkle commitments
-        for num_round in 0..=self.parameters.num_rounds {
-            let (answers, proof) = &proof.round_proofs[num_round].queries_to_prev;
-
-            if !proof
-                .verify(
-                    &self.parameters.leaf_hash_params,
-                    &self.parameters.two_to_one_params,
-                    &commitments[num_round],
-                    answers,
-                )
-                .unwrap()
-            {
-                return false;
-            }
+        if !verify_merkle_paths(
+            &self.parameters.leaf_hash_params,
+            &self.parameters.two_to_one_params,
+            commitment,
+            proof,
+            report,
+        ) {
+            return Err(VerifierError::Rejected);
         }
 
+        self.verify_many(&self.round_domains(), &[(commitment, proof)], report)
+            .pop()
+            .unwrap()
+    }
+
+    // Checks the parts of the proof whose sizes are fixed by the parameters
+    fn check_shape(&self, proof: &Proof<F, MerkleConfig>) -> bool {
+        proof.final_polynomial.degree() < self.parameters.stopping_degree
+            && proof.commitments.len() == self.parameters.num_rounds
+            && proof.round_proofs.len() == self.parameters.num_rounds + 1
+    }
+
+    // The domains that the rounds fold, and the generators of their cosets
+    fn round_domains(&self) -> RoundDomains<F> {
         let mut g_domain = Domain::<F>::new(
             self.parameters.starting_degree,
             self.parameters.starting_rate,
         )
         .unwrap();
 
-        let mut folded_evals_len = g_domain.size() / self.parameters.folding_factor;
-        let query_indexes = utils::dedup(
-            (0..self.parameters.repetitions)
-                .map(|_| utils::squeeze_integer(&mut sponge, folded_evals_len)),
+        let mut domains = vec![];
+        let mut generators = vec![];
+        for _ in 0..=self.parameters.num_rounds {
+            generators.push(g_domain.element(g_domain.size() / self.parameters.folding_factor));
+            let next_domain = g_domain.scale(self.parameters.folding_factor);
+            domains.push(g_domain);
+            g_domain = next_domain;
+        }
+        domains.push(g_domain);
+
+        RoundDomains {
+            domains,
+            generators,
+        }
+    }
+
+    // Replays the transcripts of proofs whose shapes and Merkle paths were already checked, and
+    // inverts the elements that folding all of them needs at once
+    fn verify_many(
+        &self,
+        domains: &RoundDomains<F>,
+        instances: &[(&Commitment<MerkleConfig>, &Proof<F, MerkleConfig>)],
+        report: &mut VerifierCostReport,
+    ) -> Vec<Result<(), VerifierError>> {
+        let opened: Vec<_> = instances
+            .iter()
+            .map(|(commitment, proof)| self.open(domains, commitment, proof))
+            .collect();
+
+        let size = F::from(self.parameters.folding_factor as u64);
+        let mut inverses = metrics::batch_inversion_grouped(
+            opened
+                .iter()
+                .flatten()
+                .map(|opened| opened.coset_offsets.concat())
+                .chain([domains.generators.iter().copied().chain([size]).collect()]),
+        );
+        let mut generators_inv = inverses.pop().unwrap();
+        let size_inv = generators_inv.pop().unwrap();
+
+        let mut inverses = inverses.into_iter();
+        opened
+            .into_iter()
+            .zip(instances)
+            .map(|(opened, (_, proof))| {
+                let opened = opened?;
+                let coset_offsets_inv = inverses.next().unwrap();
+                if self.close(
+                    domains,
+                    &generators_inv,
+                    size_inv,
+                    proof,
+                    opened,
+                    coset_offsets_inv,
+                    report,
+                ) {
+                    Ok(())
+                } else {
+                    Err(VerifierError::Rejected)
+                }
+            })
+            .collect()
+    }
+
+    // Replays the transcript of a proof, and checks that it opens the sampled positions
+    fn open(
+        &self,
+        domains: &RoundDomains<F>,
+        commitment: &Commitment<MerkleConfig>,
+        proof: &Proof<F, MerkleConfig>,
+    ) -> Result<OpenedProof<F>, VerifierError> {
+        // We do FS
+        let mut transcript = Transcript::<FSConfig>::new(
+            &self.parameters.fiat_shamir_config,
+            self.parameters.io_pattern(),
         );
+        transcript.append_commitment(&commitment.root)?;
 
-        // Precomputation
-        let (generators, coset_offsets) = {
+        let mut folding_randomnessness: Vec<F> = vec![transcript.challenge_scalar()?];
+        // Absorb the roots
+        for commitment in &proof.commitments {
+            transcript.append_commitment(commitment)?;
+            folding_randomnessness.push(transcript.challenge_scalar()?);
+        }
+
+        let folded_evals_len = domains.domains[0].size() / self.parameters.folding_factor;
+        let query_indexes =
+            transcript.challenge_indices(self.parameters.repetitions, folded_evals_len)?;
+
+        // Check that the prover opened the sampled positions in every round, and compute the
+        // offsets of the cosets they fold
+        let mut coset_offsets = vec![];
+        {
             let mut folded_evals_len = folded_evals_len;
             let mut query_indexes = query_indexes.clone();
-            let mut generators = vec![];
-            let mut coset_offsets = vec![];
-            for _ in 0..=self.parameters.num_rounds {
-                let generator = g_domain.element(g_domain.size() / self.parameters.folding_factor);
-
-                generators.push(generator);
+            for (g_domain, round_proof) in domains.domains.iter().zip(&proof.round_proofs) {
+                let (answers, path) = &round_proof.queries_to_prev;
+                if path.leaf_indexes != query_indexes
+                    || answers.len() != query_indexes.len()
+                    || answers
+                        .iter()
+                        .any(|answer| answer.len() != self.parameters.folding_factor)
+                {
+                    return Err(VerifierError::Rejected);
+                }
 
-                let round_offsets: Vec<_> =
-                    query_indexes.iter().map(|i| g_domain.element(*i)).collect();
+                let round_offsets: Vec<_> = query_indexes
+                    .iter()
+                    .map(|i| metrics::domain_element(g_domain, *i))
+                    .collect();
                 coset_offsets.push(round_offsets);
 
-                g_domain = g_domain.scale(self.parameters.folding_factor);
-                folded_evals_len = folded_evals_len / self.parameters.folding_factor;
+                folded_evals_len /= self.parameters.folding_factor;
                 query_indexes =
                     utils::dedup(query_indexes.into_iter().map(|i| i % folded_evals_len));
             }
+        }
 
-            (generators, coset_offsets)
-        };
-
-        let size = F::from(self.parameters.folding_factor as u64);
-
-        let mut to_invert: Vec<F> = vec![];
+        // Proof of work
+        transcript.check_grind(self.parameters.pow_bits, proof.pow_nonce)?;
+        transcript.finish()?;
+
+        Ok(OpenedProof {
+            folding_randomnessness,
+            query_indexes,
+            coset_offsets,
+        })
+    }
 
-        for co in &coset_offsets {
-            to_invert.extend(co);
-        }
-        to_invert.extend(&generators);
-        to_invert.push(size);
-        batch_inversion(&mut to_invert);
-        let size_inv = to_invert.pop().unwrap();
-        let generators_inv = to_invert.split_off(to_invert.len() - generators.len());
-        let mut coset_offsets_inv = vec![];
-        for co in coset_offsets.iter().rev() {
-            let co_inv = to_invert.split_off(to_invert.len() - co.len());
-            coset_offsets_inv.push(co_inv);
-        }
-        coset_offsets_inv.reverse();
+    // Folds the answers of every round, given the inverses of the coset offsets, and checks them
+    // against the next round and the final polynomial
+    #[allow(clippy::too_many_arguments)]
+    fn close(
+        &self,
+        domains: &RoundDomains<F>,
+        generators_inv: &[F],
+        size_inv: F,
+        proof: &Proof<F, MerkleConfig>,
+        opened: OpenedProof<F>,
+        coset_offsets_inv: Vec<F>,
+        report: &mut VerifierCostReport,
+    ) -> bool {
+        let OpenedProof {
+            folding_randomnessness,
+            query_indexes,
+            coset_offsets,
+        } = opened;
+
+        let mut coset_offsets_inv = coset_offsets_inv.into_iter();
+        let coset_offsets_inv: Vec<Vec<_>> = coset_offsets
+            .iter()
+            .map(|co| coset_offsets_inv.by_ref().take(co.len()).collect())
+            .collect();
 
+        let mut folded_evals_len = domains.domains[0].size() / self.parameters.folding_factor;
         let mut query_indexes: Vec<_> = query_indexes.into_iter().map(|i| (i, 0)).collect();
         let mut folded_answers: Option<Vec<F>> = None;
 
@@ -165,27 +386,31 @@ where
                 }
             }
 
-            let generator = generators[num_round];
+            let generator = domains.generators[num_round];
             let generator_inv = generators_inv[num_round];
 
-            let unordeded_folded_answers: Vec<_> = answers
-                .iter()
-                .zip(coset_offsets[num_round].iter())
-                .zip(coset_offsets_inv[num_round].iter())
-                .map(|(((_, answer), coset_offset), coset_offset_inv)| {
-                    let folded_answer = poly_utils::interpolation::fft_interpolate(
-                        generator,
-                        *coset_offset,
-                        generator_inv,
-                        *coset_offset_inv,
-                        size_inv,
-                        answer,
-                    )
-                    .evaluate(&folding_randomness);
-
-                    folded_answer
-                })
-                .collect();
+            let unordeded_folded_answers: Vec<_> = report.scope(num_round, || {
+                answers
+                    .iter()
+                    .zip(coset_offsets[num_round].iter())
+                    .zip(coset_offsets_inv[num_round].iter())
+                    .map(|(((_, answer), coset_offset), coset_offset_inv)| {
+                        let folded_answer = metrics::evaluate(
+                            &poly_utils::interpolation::fft_interpolate(
+                                generator,
+                                *coset_offset,
+                                generator_inv,
+                                *coset_offset_inv,
+                                size_inv,
+                                answer,
+                            ),
+                            &folding_randomness,
+                        );
+
+                        folded_answer
+                    })
+                    .collect()
+            });
 
             folded_evals_len = folded_evals_len / self.parameters.folding_factor;
 
@@ -205,24 +430,75 @@ where
         }
 
         let folded_answers = folded_answers.unwrap();
+        let g_domain = &domains.domains[self.parameters.num_rounds + 1];
 
-        let answers: Vec<_> = query_indexes
-            .into_iter()
-            .map(|(index, checking_index)| {
-                proof
-                    .final_polynomial
-                    .evaluate(&g_domain.element(index + checking_index * folded_evals_len))
+        report.scope(self.parameters.num_rounds, || {
+            let answers: Vec<_> = query_indexes
+                .into_iter()
+                .map(|(index, checking_index)| {
+                    metrics::evaluate(
+                        &proof.final_polynomial,
+                        &metrics::domain_element(
+                            g_domain,
+                            index + checking_index * folded_evals_len,
+                        ),
+                    )
+                })
+                .collect();
+            folded_answers
+                .iter()
+                .zip(answers)
+                .all(|(folded_answer, poly_answer)| poly_answer == *folded_answer)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::{fields::Field64 as TestField, fs, merkle_tree::blake3 as mt},
+        fri::prover::FriProver,
+        ldt::{assert_batch_rejects, Prover},
+        parameters::SoundnessType,
+    };
+    use ark_ff::Field;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = mt::default_config::<TestField>(&mut rng, 4);
+        let parameters: Parameters<TestField, mt::MerkleTreeParams<TestField>, fs::blake3::Sponge> =
+            Parameters {
+                security_level: 32,
+                protocol_security_level: 32,
+                starting_degree: 64,
+                stopping_degree: 4,
+                folding_factor: 4,
+                starting_rate: 2,
+                soundness_type: SoundnessType::Conjecture,
+                leaf_hash_params,
+                two_to_one_params,
+                fiat_shamir_config: fs::blake3::default_fs_config(),
+                _field: Default::default(),
+            };
+        let prover = FriProver::new(parameters.clone());
+        let verifier = FriVerifier::new(parameters);
+
+        let mut instances: Vec<_> = (0..8)
+            .map(|_| {
+                let (commitment, witness) = prover.commit(DensePolynomial::rand(63, &mut rng));
+                (commitment, prover.prove(witness))
             })
             .collect();
-        if !folded_answers
-            .iter()
-            .zip(answers)
-            .all(|(folded_answer, poly_answer)| poly_answer == *folded_answer)
-        {
-            return false;
-        }
-
-        // Proof of work
-        utils::proof_of_work_verify(&mut sponge, self.parameters.pow_bits, proof.pow_nonce)
+        assert_batch_rejects(&verifier, &instances, &[]);
+
+        // A Merkle path, a consistency check and the shape of the proof fail respectively
+        let (commitment, proof) = &mut instances[0];
+        std::mem::swap(&mut commitment.root, &mut proof.commitments[0]);
+        instances[6].1.final_polynomial.coeffs[0] += TestField::ONE;
+        instances[7].1.commitments.pop();
+        assert_batch_rejects(&verifier, &instances, &[0, 6, 7]);
     }
 }
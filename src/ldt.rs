@@ -2,8 +2,176 @@ use ark_crypto_primitives::{merkle_tree::Config, sponge::CryptographicSponge};
 use ark_ff::FftField;
 use ark_poly::univariate::DensePolynomial;
 use ark_serialize::CanonicalSerialize;
+use std::fmt::Display;
 
-use crate::parameters::Parameters;
+use crate::{
+    metrics::{self, Counters},
+    parameters::Parameters,
+    transcript::TranscriptError,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProverError {
+    // The committed function is not within the unique decoding radius of the code
+    NotDecodable,
+    // The prover diverged from the declared IO pattern
+    Transcript(TranscriptError),
+}
+
+impl From<TranscriptError> for ProverError {
+    fn from(err: TranscriptError) -> Self {
+        ProverError::Transcript(err)
+    }
+}
+
+impl Display for ProverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProverError::NotDecodable => write!(
+                f,
+                "committed function is not within the unique decoding radius of the code"
+            ),
+            ProverError::Transcript(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ProverError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifierError {
+    // The proof is malformed, or fails one of the checks of the verifier
+    Rejected,
+    // The verifier diverged from the declared IO pattern, or the proof of work is invalid
+    Transcript(TranscriptError),
+}
+
+impl From<TranscriptError> for VerifierError {
+    fn from(err: TranscriptError) -> Self {
+        VerifierError::Transcript(err)
+    }
+}
+
+impl Display for VerifierError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifierError::Rejected => write!(f, "proof rejected"),
+            VerifierError::Transcript(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for VerifierError {}
+
+// Cost of a single verification. rounds[i] is the work attributed to the i-th round (its Merkle
+// openings, transcript operations and folding), and the last entry is the final phase. total also
+// includes the work shared between rounds, such as batched inversions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifierCostReport {
+    pub rounds: Vec<Counters>,
+    pub total: Counters,
+}
+
+impl VerifierCostReport {
+    pub(crate) fn new(num_rounds: usize) -> Self {
+        Self {
+            rounds: vec![Counters::default(); num_rounds],
+            total: Counters::default(),
+        }
+    }
+
+    // Runs f, attributing what it records to the given round
+    pub(crate) fn scope<R: Send>(&mut self, round: usize, f: impl FnOnce() -> R + Send) -> R {
+        let (result, counters) = metrics::with_counter(f);
+        self.rounds[round] += counters;
+        result
+    }
+
+    // Work not attributed to any round
+    pub fn shared(&self) -> Counters {
+        self.rounds
+            .iter()
+            .fold(self.total, |shared, &round| shared - round)
+    }
+}
+
+// The outcome of a batch verification given whether each proof was accepted: the indexes of the
+// rejected proofs, if any
+pub(crate) fn batch_result(accepted: &[bool]) -> Result<(), Vec<usize>> {
+    let failed: Vec<_> = accepted
+        .iter()
+        .enumerate()
+        .filter(|(_, accepted)| !**accepted)
+        .map(|(i, _)| i)
+        .collect();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
+    }
+}
+
+// Checks that batch verification rejects exactly the proofs that are rejected on their own
+#[cfg(test)]
+pub(crate) fn assert_batch_rejects<F, MerkleConfig, FSConfig, V>(
+    verifier: &V,
+    instances: &[(V::Commitment, V::Proof)],
+    rejected: &[usize],
+) where
+    F: FftField,
+    MerkleConfig: Config,
+    FSConfig: CryptographicSponge,
+    FSConfig::Config: Clone,
+    V: Verifier<F, MerkleConfig, FSConfig>,
+{
+    for (i, (commitment, proof)) in instances.iter().enumerate() {
+        assert_eq!(
+            verifier.verify(commitment, proof),
+            !rejected.contains(&i),
+            "proof {}",
+            i
+        );
+    }
+    let expected = if rejected.is_empty() {
+        Ok(())
+    } else {
+        Err(rejected.to_vec())
+    };
+    assert_eq!(verifier.verify_batch(instances), expected);
+}
+
+impl Display for VerifierCostReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>8} {:>8} {:>12} {:>12} {:>10} {:>10} {:>6}",
+            "round", "leaves", "compressions", "permutations", "mults", "inversions", "pow"
+        )?;
+        let rows = self
+            .rounds
+            .iter()
+            .enumerate()
+            .map(|(i, counters)| (i.to_string(), *counters))
+            .chain([
+                ("shared".to_string(), self.shared()),
+                ("total".to_string(), self.total),
+            ]);
+        for (name, counters) in rows {
+            writeln!(
+                f,
+                "{:>8} {:>8} {:>12} {:>12} {:>10} {:>10} {:>6}",
+                name,
+                counters.leaf_hashes,
+                counters.compressions,
+                counters.permutations,
+                counters.field_multiplications,
+                counters.field_inversions,
+                counters.pow_checks
+            )?;
+        }
+        Ok(())
+    }
+}
 
 pub trait LowDegreeTest<F, MerkleConfig, FSConfig>
 where
@@ -52,7 +220,18 @@ where
 
     fn commit(&self, polynomial: DensePolynomial<F>) -> (Self::Commitment, Self::Witness);
 
-    fn prove(&self, witness: Self::Witness) -> Self::Proof;
+    // Commits to a function given by its evaluations over the starting domain, skipping the FFT.
+    // The coefficients are only recovered when proving.
+    fn commit_evaluations(&self, evals: Vec<F>) -> (Self::Commitment, Self::Witness);
+
+    fn prove(&self, witness: Self::Witness) -> Self::Proof {
+        self.try_prove(witness).unwrap()
+    }
+
+    // Proves that the committed function is close to a low degree polynomial. If the function is
+    // not a codeword, the prover uses the closest codeword within the unique decoding radius
+    // (which the verifier will then likely reject), and errors if there is none.
+    fn try_prove(&self, witness: Self::Witness) -> Result<Self::Proof, ProverError>;
 }
 
 pub trait Verifier<F, MerkleConfig, FSConfig>
@@ -71,5 +250,47 @@ where
     fn new(parameters: Parameters<F, MerkleConfig, FSConfig>) -> Self;
     fn new_full(full_parameters: Self::FullParameter) -> Self;
 
-    fn verify(&self, commitment: &Self::Commitment, proof: &Self::Proof) -> bool;
+    fn verify(&self, commitment: &Self::Commitment, proof: &Self::Proof) -> bool {
+        self.try_verify(commitment, proof).is_ok()
+    }
+
+    // Verifies the proof, and returns why it was rejected. A transcript error other than an
+    // invalid proof of work means that the verifier does not follow its own IO pattern.
+    fn try_verify(
+        &self,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> Result<(), VerifierError> {
+        self.try_verify_with_cost_report(commitment, proof).0
+    }
+
+    fn verify_with_cost_report(
+        &self,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> (bool, VerifierCostReport) {
+        let (result, report) = self.try_verify_with_cost_report(commitment, proof);
+        (result.is_ok(), report)
+    }
+
+    // Verifies the proof and reports the hashes, field operations and proof of work checks it
+    // took. The counts are zero without the metrics feature.
+    fn try_verify_with_cost_report(
+        &self,
+        commitment: &Self::Commitment,
+        proof: &Self::Proof,
+    ) -> (Result<(), VerifierError>, VerifierCostReport);
+
+    // Verifies many proofs, and returns the indexes of those that were rejected. Verifiers can
+    // share work between the proofs; by default each is verified on its own.
+    fn verify_batch(
+        &self,
+        instances: &[(Self::Commitment, Self::Proof)],
+    ) -> Result<(), Vec<usize>> {
+        let accepted: Vec<_> = instances
+            .iter()
+            .map(|(commitment, proof)| self.verify(commitment, proof))
+            .collect();
+        batch_result(&accepted)
+    }
 }
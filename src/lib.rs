@@ -1,8 +1,19 @@
 pub mod crypto;
 pub mod domain;
+pub mod evm;
 pub mod fri;
 pub mod ldt;
+pub mod metrics;
 pub mod parameters;
 pub mod poly_utils;
+#[cfg(feature = "r1cs")]
+pub mod r1cs;
 pub mod stir;
+pub mod sweep;
+pub mod transcript;
 pub mod utils;
+
+#[cfg(test)]
+mod adversarial;
+#[cfg(test)]
+mod test_vectors;
@@ -0,0 +1,300 @@
+// Scoped instrumentation counters.
+//
+// Counts are kept per thread: `with_counter` runs a closure and returns what it recorded, so that
+// provers and verifiers running concurrently (e.g. in tests) do not mix their counts. Nested
+// scopes are supported, and each scope also sees the counts of the scopes it contains. With the
+// `parallel` feature, the closure runs in a rayon thread pool of its own, so that the work it
+// hands to rayon (including inside arkworks) is counted too. Starting a pool per scope is slow, so
+// timings taken with both features are not representative. Threads the closure spawns itself are
+// not counted.
+//
+// Without the `metrics` feature, recording is a no-op and all counts are zero.
+
+use std::ops::{Add, AddAssign, Sub};
+
+use ark_ff::{FftField, Field};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Counters {
+    pub leaf_hashes: usize,
+    pub compressions: usize,
+    pub permutations: usize,
+    pub field_multiplications: usize,
+    pub field_inversions: usize,
+    pub pow_checks: usize,
+}
+
+impl Counters {
+    // Number of Merkle tree hash invocations
+    pub fn hashes(&self) -> usize {
+        self.leaf_hashes + self.compressions
+    }
+}
+
+impl AddAssign for Counters {
+    fn add_assign(&mut self, other: Self) {
+        self.leaf_hashes += other.leaf_hashes;
+        self.compressions += other.compressions;
+        self.permutations += other.permutations;
+        self.field_multiplications += other.field_multiplications;
+        self.field_inversions += other.field_inversions;
+        self.pow_checks += other.pow_checks;
+    }
+}
+
+impl Add for Counters {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl Sub for Counters {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            leaf_hashes: self.leaf_hashes - other.leaf_hashes,
+            compressions: self.compressions - other.compressions,
+            permutations: self.permutations - other.permutations,
+            field_multiplications: self.field_multiplications - other.field_multiplications,
+            field_inversions: self.field_inversions - other.field_inversions,
+            pow_checks: self.pow_checks - other.pow_checks,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    LeafHash,
+    Compression,
+    Permutation,
+    FieldMultiplication,
+    FieldInversion,
+    ProofOfWorkCheck,
+}
+
+impl Counters {
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    fn record(&mut self, metric: Metric, count: usize) {
+        let counter = match metric {
+            Metric::LeafHash => &mut self.leaf_hashes,
+            Metric::Compression => &mut self.compressions,
+            Metric::Permutation => &mut self.permutations,
+            Metric::FieldMultiplication => &mut self.field_multiplications,
+            Metric::FieldInversion => &mut self.field_inversions,
+            Metric::ProofOfWorkCheck => &mut self.pow_checks,
+        };
+        *counter += count;
+    }
+}
+
+#[cfg(feature = "metrics")]
+mod scopes {
+    use std::cell::RefCell;
+
+    use super::{Counters, Metric};
+
+    thread_local! {
+        // The first entry accumulates everything recorded on this thread, the others are the
+        // currently open scopes
+        static SCOPES: RefCell<Vec<Counters>> = RefCell::new(vec![Counters::default()]);
+    }
+
+    // Records count occurrences of metric on the current thread and in all its open scopes
+    pub fn record(metric: Metric, count: usize) {
+        SCOPES.with(|scopes| {
+            for counters in scopes.borrow_mut().iter_mut() {
+                counters.record(metric, count);
+            }
+        });
+    }
+
+    // Runs f and returns what it recorded on the current thread
+    #[cfg(not(feature = "parallel"))]
+    pub fn with_counter<R: Send>(f: impl FnOnce() -> R + Send) -> (R, Counters) {
+        SCOPES.with(|scopes| scopes.borrow_mut().push(Counters::default()));
+        let result = f();
+        let counters = SCOPES.with(|scopes| scopes.borrow_mut().pop().unwrap());
+        (result, counters)
+    }
+
+    // Runs f in a thread pool of its own, and returns what its threads recorded. Each of them
+    // sends its counts when the pool shuts down, and the scopes open on the current thread see
+    // them as if they were recorded here.
+    #[cfg(feature = "parallel")]
+    pub fn with_counter<R: Send>(f: impl FnOnce() -> R + Send) -> (R, Counters) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .exit_handler(move |_| sender.send(thread_counters()).unwrap())
+            .build()
+            .unwrap();
+        let num_threads = pool.current_num_threads();
+        let result = pool.install(f);
+        drop(pool);
+
+        let counters = receiver
+            .iter()
+            .take(num_threads)
+            .fold(Counters::default(), |total, counters| total + counters);
+        SCOPES.with(|scopes| {
+            for scope in scopes.borrow_mut().iter_mut() {
+                *scope += counters;
+            }
+        });
+        (result, counters)
+    }
+
+    // Everything recorded on the current thread
+    #[cfg(feature = "parallel")]
+    fn thread_counters() -> Counters {
+        SCOPES.with(|scopes| scopes.borrow()[0])
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod scopes {
+    use super::{Counters, Metric};
+
+    #[inline(always)]
+    pub fn record(_metric: Metric, _count: usize) {}
+
+    pub fn with_counter<R: Send>(f: impl FnOnce() -> R + Send) -> (R, Counters) {
+        (f(), Counters::default())
+    }
+}
+
+pub use scopes::{record, with_counter};
+
+// Field arithmetic that records its cost. Additions are free, and multiplication counts (which
+// include squarings) follow the arkworks implementations.
+
+pub fn mul<F: Field>(a: F, b: F) -> F {
+    record(Metric::FieldMultiplication, 1);
+    a * b
+}
+
+// The product of the values, starting from one
+pub fn product<F: Field>(values: impl IntoIterator<Item = F>) -> F {
+    values.into_iter().fold(F::ONE, mul)
+}
+
+fn pow_multiplications(exponent: u64) -> usize {
+    // Square and multiply, starting from the most significant bit
+    (u64::BITS - exponent.leading_zeros() + exponent.count_ones()) as usize
+}
+
+pub fn pow<F: Field>(base: F, exponent: u64) -> F {
+    record(Metric::FieldMultiplication, pow_multiplications(exponent));
+    base.pow([exponent])
+}
+
+// The offset times a power of the generator
+pub fn domain_element<F: FftField>(domain: &GeneralEvaluationDomain<F>, index: usize) -> F {
+    record(
+        Metric::FieldMultiplication,
+        pow_multiplications(index as u64) + 1,
+    );
+    domain.element(index)
+}
+
+pub fn evaluate<F: Field>(polynomial: &DensePolynomial<F>, point: &F) -> F {
+    // Horner's rule
+    record(Metric::FieldMultiplication, polynomial.coeffs.len());
+    ark_poly::Polynomial::evaluate(polynomial, point)
+}
+
+pub fn batch_inversion<F: Field>(values: &mut [F]) {
+    // Montgomery's trick: one inversion and three multiplications per element
+    record(Metric::FieldInversion, 1);
+    record(Metric::FieldMultiplication, 3 * values.len());
+    ark_ff::batch_inversion(values);
+}
+
+// Inverts the elements of several groups with a single batch inversion, and returns the inverses
+// grouped the same way
+pub fn batch_inversion_grouped<F: Field>(groups: impl IntoIterator<Item = Vec<F>>) -> Vec<Vec<F>> {
+    let groups: Vec<_> = groups.into_iter().collect();
+    let mut values = groups.concat();
+    batch_inversion(&mut values);
+
+    let mut values = values.into_iter();
+    groups
+        .into_iter()
+        .map(|group| values.by_ref().take(group.len()).collect())
+        .collect()
+}
+
+// Records the cost of a (coset) FFT or inverse FFT over a domain of the given size: the
+// butterflies and the scaling by the powers of the offset
+pub fn record_fft(size: usize) {
+    record(
+        Metric::FieldMultiplication,
+        size / 2 * size.ilog2() as usize + 2 * size,
+    );
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_scopes() {
+        let (_, outer) = with_counter(|| {
+            record(Metric::LeafHash, 2);
+            let (_, inner) = with_counter(|| {
+                record(Metric::Compression, 3);
+                record(Metric::FieldInversion, 1);
+            });
+            assert_eq!(
+                inner,
+                Counters {
+                    compressions: 3,
+                    field_inversions: 1,
+                    ..Default::default()
+                }
+            );
+        });
+        assert_eq!(
+            outer,
+            Counters {
+                leaf_hashes: 2,
+                compressions: 3,
+                field_inversions: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(outer.hashes(), 5);
+    }
+
+    #[test]
+    fn test_threads_are_separate() {
+        let (_, counters) = with_counter(|| {
+            record(Metric::Permutation, 1);
+            std::thread::spawn(|| record(Metric::Permutation, 10))
+                .join()
+                .unwrap();
+        });
+        assert_eq!(counters.permutations, 1);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_rayon_workers_are_counted() {
+        use rayon::prelude::*;
+
+        let (_, outer) = with_counter(|| {
+            let (_, inner) = with_counter(|| {
+                (0..64)
+                    .into_par_iter()
+                    .for_each(|_| record(Metric::Permutation, 1))
+            });
+            assert_eq!(inner.permutations, 64);
+            record(Metric::Permutation, 1);
+        });
+        assert_eq!(outer.permutations, 65);
+    }
+}
@@ -65,7 +65,15 @@ where
     }
 
     pub(crate) fn pow_bits(&self, log_inv_rate: usize) -> usize {
-        let repetitions = self.repetitions(log_inv_rate);
+        self.pow_bits_for_repetitions(log_inv_rate, self.repetitions(log_inv_rate))
+    }
+
+    // The grinding that makes up for the security that this many repetitions lack
+    pub(crate) fn pow_bits_for_repetitions(
+        &self,
+        log_inv_rate: usize,
+        repetitions: usize,
+    ) -> usize {
         // TODO: This will change with eta
         let scaling_factor = match self.soundness_type {
             SoundnessType::Provable => 2.,
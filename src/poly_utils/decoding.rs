@@ -0,0 +1,118 @@
+use ark_ff::{FftField, Zero};
+use ark_poly::{
+    univariate::{DenseOrSparsePolynomial, DensePolynomial},
+    DenseUVPolynomial, EvaluationDomain, Evaluations, GeneralEvaluationDomain, Polynomial,
+};
+
+// Finds the unique polynomial of degree < degree whose evaluations over domain agree with evals in
+// all but at most (|domain| - degree) / 2 positions, if it exists.
+// We first try plain interpolation (which is just an IFFT) and only run Gao's decoder if evals is
+// not a codeword. Note that the decoder uses quadratic polynomial arithmetic.
+pub fn decode<F: FftField>(
+    evals: Vec<F>,
+    domain: GeneralEvaluationDomain<F>,
+    degree: usize,
+) -> Option<DensePolynomial<F>> {
+    assert_eq!(evals.len(), domain.size());
+    let size = domain.size();
+    if degree > size {
+        return None;
+    }
+
+    let interpolated = Evaluations::from_vec_and_domain(evals.clone(), domain).interpolate();
+    if interpolated.degree() < degree {
+        return Some(interpolated);
+    }
+
+    // Gao's algorithm: run the extended Euclidean algorithm on (Z_L, interpolated) and stop as soon
+    // as the remainder has degree < (|L| + degree) / 2
+    let vanishing_poly: DensePolynomial<F> = domain.vanishing_polynomial().into();
+    let stopping_degree = (size + degree).div_ceil(2);
+
+    let mut r_prev = vanishing_poly;
+    let mut r = interpolated;
+    let mut t_prev = DensePolynomial::zero();
+    let mut t = DensePolynomial::from_coefficients_slice(&[F::ONE]);
+
+    while !r.is_zero() && r.degree() >= stopping_degree {
+        let (quotient, remainder) = DenseOrSparsePolynomial::from(&r_prev)
+            .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&r))?;
+        let t_next = &t_prev - &(&quotient * &t);
+
+        r_prev = r;
+        r = remainder;
+        t_prev = t;
+        t = t_next;
+    }
+
+    let (decoded, remainder) = DenseOrSparsePolynomial::from(&r)
+        .divide_with_q_and_r(&DenseOrSparsePolynomial::from(&t))?;
+    if !remainder.is_zero() || (!decoded.is_zero() && decoded.degree() >= degree) {
+        return None;
+    }
+
+    // Sanity check that we are within the unique decoding radius
+    let disagreements = decoded
+        .evaluate_over_domain_by_ref(domain)
+        .evals
+        .iter()
+        .zip(evals.iter())
+        .filter(|(a, b)| a != b)
+        .count();
+    if 2 * disagreements > size - degree {
+        return None;
+    }
+
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::{Field, UniformRand};
+    use rand::seq::SliceRandom;
+
+    use super::*;
+    use crate::crypto::fields::Field64 as TestField;
+
+    fn corrupt(evals: &mut [TestField], errors: usize) {
+        let mut rng = ark_std::test_rng();
+        let mut indexes: Vec<_> = (0..evals.len()).collect();
+        indexes.shuffle(&mut rng);
+        for &i in indexes.iter().take(errors) {
+            evals[i] += TestField::rand(&mut rng) + TestField::ONE;
+        }
+    }
+
+    #[test]
+    fn test_decode_codeword() {
+        let mut rng = ark_std::test_rng();
+        let domain = GeneralEvaluationDomain::<TestField>::new(64).unwrap();
+        let poly = DensePolynomial::rand(15, &mut rng);
+        let evals = poly.evaluate_over_domain_by_ref(domain).evals;
+
+        assert_eq!(decode(evals, domain, 16), Some(poly));
+    }
+
+    #[test]
+    fn test_decode_within_radius() {
+        let mut rng = ark_std::test_rng();
+        let domain = GeneralEvaluationDomain::<TestField>::new(64).unwrap();
+        let poly = DensePolynomial::rand(15, &mut rng);
+        let mut evals = poly.evaluate_over_domain_by_ref(domain).evals;
+        // (64 - 16) / 2 = 24 errors can be corrected
+        corrupt(&mut evals, 24);
+
+        assert_eq!(decode(evals, domain, 16), Some(poly));
+    }
+
+    #[test]
+    fn test_decode_beyond_radius() {
+        let mut rng = ark_std::test_rng();
+        let domain = GeneralEvaluationDomain::<TestField>::new(64).unwrap();
+        let poly = DensePolynomial::rand(15, &mut rng);
+        let mut evals = poly.evaluate_over_domain_by_ref(domain).evals;
+        corrupt(&mut evals, 40);
+
+        assert_eq!(decode(evals, domain, 16), None);
+    }
+}
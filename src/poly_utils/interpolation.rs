@@ -3,7 +3,7 @@ use ark_poly::{
     univariate::DensePolynomial, DenseUVPolynomial, Evaluations, Polynomial, Radix2EvaluationDomain,
 };
 
-use crate::utils;
+use crate::{metrics, utils};
 
 // Computes a polynomial that vanishes on points
 pub fn vanishing_poly<'a, F: Field>(points: impl IntoIterator<Item = &'a F>) -> DensePolynomial<F> {
@@ -106,9 +106,10 @@ pub fn fft_interpolate<'a, F: FftField>(
         group_gen_inv: generator_inv,
         offset: coset_offset,
         offset_inv: coset_offset_inv,
-        offset_pow_size: coset_offset.pow([folding_factor as u64]),
+        offset_pow_size: metrics::pow(coset_offset, folding_factor as u64),
     };
 
+    metrics::record_fft(folding_factor);
     let evaluations = Evaluations::from_vec_and_domain(points, domain);
 
     evaluations.interpolate()
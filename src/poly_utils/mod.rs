@@ -1,4 +1,5 @@
 pub mod bs08;
+pub mod decoding;
 pub mod fft;
 pub mod folding;
 pub mod interpolation;
@@ -2,6 +2,7 @@ use ark_ff::{FftField, Field};
 use ark_poly::{univariate::DensePolynomial, Polynomial};
 
 use super::interpolation;
+use crate::metrics;
 
 // Compute the quotient
 pub fn poly_quotient<F: FftField>(poly: &DensePolynomial<F>, points: &[F]) -> DensePolynomial<F> {
@@ -61,7 +62,7 @@ pub fn quotient_with_hint<'a, F: Field>(
 
     let num = claimed_eval - ans_eval;
 
-    num * denom_hint
+    metrics::mul(num, denom_hint)
 }
 
 #[cfg(test)]
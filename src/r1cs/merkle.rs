@@ -0,0 +1,206 @@
+use std::borrow::Borrow;
+
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig},
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::fp::FpVar,
+    select::CondSelectGadget,
+    R1CSVar,
+};
+use ark_relations::r1cs::{Namespace, SynthesisError};
+
+use crate::crypto::merkle_tree::poseidon::PoseidonDigest;
+
+// We need 2 field elements for security
+const DIGEST_SIZE: usize = 2;
+
+// A digest of crypto::merkle_tree::poseidon
+#[derive(Clone, Debug)]
+pub struct PoseidonDigestVar<F: PrimeField>(pub [FpVar<F>; DIGEST_SIZE]);
+
+impl<F: PrimeField> AllocVar<PoseidonDigest<F>, F> for PoseidonDigestVar<F> {
+    fn new_variable<T: Borrow<PoseidonDigest<F>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let digest = f().map(|digest| digest.borrow().0);
+        let elements =
+            Vec::<FpVar<F>>::new_variable(cs, || digest.map(|digest| digest.to_vec()), mode)?;
+        Ok(Self([elements[0].clone(), elements[1].clone()]))
+    }
+}
+
+impl<F: PrimeField> PoseidonDigestVar<F> {
+    fn select(condition: &Boolean<F>, first: &Self, second: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self([
+            FpVar::conditionally_select(condition, &first.0[0], &second.0[0])?,
+            FpVar::conditionally_select(condition, &first.0[1], &second.0[1])?,
+        ]))
+    }
+
+    pub fn is_eq(&self, other: &Self) -> Result<Boolean<F>, SynthesisError> {
+        self.0.as_slice().is_eq(other.0.as_slice())
+    }
+}
+
+// The leaf hash, PoseidonCRH
+pub fn hash_leaf<F: PrimeField>(
+    leaf_hash_params: &PoseidonConfig<F>,
+    leaf: &[FpVar<F>],
+) -> Result<PoseidonDigestVar<F>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(leaf.cs(), leaf_hash_params);
+    sponge.absorb(&leaf)?;
+    let res = sponge.squeeze_field_elements(DIGEST_SIZE)?;
+    Ok(PoseidonDigestVar([res[0].clone(), res[1].clone()]))
+}
+
+// The compression function, PoseidonTwoToOneCRH
+pub fn compress<F: PrimeField>(
+    two_to_one_params: &PoseidonConfig<F>,
+    left: &PoseidonDigestVar<F>,
+    right: &PoseidonDigestVar<F>,
+) -> Result<PoseidonDigestVar<F>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(left.0.cs(), two_to_one_params);
+    sponge.absorb(&left.0.as_slice())?;
+    sponge.absorb(&right.0.as_slice())?;
+    let res = sponge.squeeze_field_elements(DIGEST_SIZE)?;
+    Ok(PoseidonDigestVar([res[0].clone(), res[1].clone()]))
+}
+
+// An authentication path, as in ark_crypto_primitives::merkle_tree::Path: the sibling of the leaf,
+// then the siblings of the inner nodes from the top of the tree down
+#[derive(Clone, Debug)]
+pub struct PathVar<F: PrimeField> {
+    pub leaf_sibling: PoseidonDigestVar<F>,
+    pub auth_path: Vec<PoseidonDigestVar<F>>,
+}
+
+impl<F: PrimeField> PathVar<F> {
+    // Allocates a path of path_len inner nodes. f is only called when the assignment is needed.
+    pub fn new_witness(
+        cs: impl Into<Namespace<F>>,
+        path_len: usize,
+        f: impl FnOnce() -> Result<(PoseidonDigest<F>, Vec<PoseidonDigest<F>>), SynthesisError>,
+    ) -> Result<Self, SynthesisError> {
+        let cs = cs.into().cs();
+        let value = if cs.is_in_setup_mode() {
+            None
+        } else {
+            Some(f()?)
+        };
+        let leaf_sibling = PoseidonDigestVar::new_witness(cs.clone(), || {
+            value
+                .as_ref()
+                .map(|(leaf_sibling, _)| *leaf_sibling)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let auth_path = (0..path_len)
+            .map(|i| {
+                PoseidonDigestVar::new_witness(cs.clone(), || {
+                    value
+                        .as_ref()
+                        .map(|(_, auth_path)| auth_path[i])
+                        .ok_or(SynthesisError::AssignmentMissing)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            leaf_sibling,
+            auth_path,
+        })
+    }
+
+    // Whether leaf is at the given index (in little-endian bits, one per level) of the tree with
+    // the given root
+    pub fn verify(
+        &self,
+        leaf_hash_params: &PoseidonConfig<F>,
+        two_to_one_params: &PoseidonConfig<F>,
+        root: &PoseidonDigestVar<F>,
+        leaf: &[FpVar<F>],
+        index: &[Boolean<F>],
+    ) -> Result<Boolean<F>, SynthesisError> {
+        assert_eq!(index.len(), self.auth_path.len() + 1);
+
+        // A zero bit means that the current node is a left child
+        let siblings = std::iter::once(&self.leaf_sibling).chain(self.auth_path.iter().rev());
+        let mut node = hash_leaf(leaf_hash_params, leaf)?;
+        for (bit, sibling) in index.iter().zip(siblings) {
+            let left = PoseidonDigestVar::select(bit, sibling, &node)?;
+            let right = PoseidonDigestVar::select(bit, &node, sibling)?;
+            node = compress(two_to_one_params, &left, &right)?;
+        }
+        node.is_eq(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_crypto_primitives::{crh::CRHScheme, merkle_tree::MerkleTree};
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::crypto::{
+        fields::Field64 as TestField,
+        merkle_tree::{decode_paths, poseidon},
+    };
+
+    #[test]
+    fn test_matches_native() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) =
+            poseidon::default_config::<TestField>(&mut rng, 4);
+        let leaves: Vec<Vec<TestField>> = (0..16)
+            .map(|_| (0..4).map(|_| TestField::rand(&mut rng)).collect())
+            .collect();
+        let tree = MerkleTree::<poseidon::MerkleTreeParams<TestField>>::new(
+            &leaf_hash_params,
+            &two_to_one_params,
+            &leaves,
+        )
+        .unwrap();
+
+        let indexes = vec![1, 2, 3, 9, 14];
+        let multi_path = tree.generate_multi_proof(indexes.clone()).unwrap();
+        let paths = decode_paths(&multi_path).unwrap();
+
+        let cs = ConstraintSystem::<TestField>::new_ref();
+        let root = PoseidonDigestVar::new_input(cs.clone(), || Ok(tree.root())).unwrap();
+        for (&index, (leaf_sibling, auth_path)) in indexes.iter().zip(&paths) {
+            let path =
+                PathVar::new_witness(cs.clone(), 3, || Ok((*leaf_sibling, auth_path.clone())))
+                    .unwrap();
+            let leaf =
+                Vec::<FpVar<_>>::new_witness(cs.clone(), || Ok(leaves[index].clone())).unwrap();
+            let bits = (0..4)
+                .map(|i| Boolean::new_witness(cs.clone(), || Ok((index >> i) & 1 == 1)))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let leaf_hash = hash_leaf(&leaf_hash_params, &leaf).unwrap();
+            let expected = poseidon::LeafH::evaluate(&leaf_hash_params, &leaves[index]).unwrap();
+            assert_eq!(leaf_hash.0.value().unwrap(), expected.0);
+
+            let valid = path
+                .verify(&leaf_hash_params, &two_to_one_params, &root, &leaf, &bits)
+                .unwrap();
+            assert!(valid.value().unwrap());
+
+            // The same path does not open another leaf
+            let other = Vec::<FpVar<_>>::new_witness(cs.clone(), || Ok(leaves[0].clone())).unwrap();
+            let invalid = path
+                .verify(&leaf_hash_params, &two_to_one_params, &root, &other, &bits)
+                .unwrap();
+            assert!(!invalid.value().unwrap());
+        }
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,99 @@
+// Constraints verifying STIR proofs, for recursion. The constraint field is the field of the
+// proof, and the gadgets mirror the native verifier for the Poseidon instantiation: Poseidon
+// Merkle trees (crypto::merkle_tree::poseidon) and the Poseidon sponge for Fiat-Shamir
+// (crypto::fs::poseidon).
+
+use std::fmt::Display;
+
+use ark_relations::r1cs::SynthesisError;
+
+use crate::transcript::TranscriptError;
+
+pub mod merkle;
+pub mod transcript;
+pub mod verifier;
+
+#[derive(Debug)]
+pub enum GadgetError {
+    // The constraint system failed, e.g. because an assignment is missing
+    Synthesis(SynthesisError),
+    // The gadget diverged from the declared IO pattern
+    Transcript(TranscriptError),
+    // The proof does not have the shape the parameters require. The native verifier rejects such
+    // proofs, but they cannot be assigned to the circuit.
+    MalformedProof,
+}
+
+impl From<SynthesisError> for GadgetError {
+    fn from(err: SynthesisError) -> Self {
+        GadgetError::Synthesis(err)
+    }
+}
+
+impl From<TranscriptError> for GadgetError {
+    fn from(err: TranscriptError) -> Self {
+        GadgetError::Transcript(err)
+    }
+}
+
+impl Display for GadgetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GadgetError::Synthesis(err) => write!(f, "{}", err),
+            GadgetError::Transcript(err) => write!(f, "{}", err),
+            GadgetError::MalformedProof => write!(f, "malformed proof"),
+        }
+    }
+}
+
+impl std::error::Error for GadgetError {}
+
+// Number of constraints spent on each part of a round
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConstraintCounts {
+    // Sponge permutations for Fiat-Shamir, and the proof of work
+    pub transcript: usize,
+    // Opening the queried leaves
+    pub merkle: usize,
+    // Evaluating the previous oracle and folding
+    pub folding: usize,
+    // The shake polynomial check, or the final polynomial check in the last round
+    pub consistency: usize,
+}
+
+impl ConstraintCounts {
+    pub fn total(&self) -> usize {
+        self.transcript + self.merkle + self.folding + self.consistency
+    }
+}
+
+// Constraints of a verification, split by round like ldt::VerifierCostReport. total also includes
+// allocating the commitment and the initial transcript operations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstraintReport {
+    pub rounds: Vec<ConstraintCounts>,
+    pub total: usize,
+}
+
+impl Display for ConstraintReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            "round", "transcript", "merkle", "folding", "consistency", "total"
+        )?;
+        for (i, counts) in self.rounds.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>6} {:>12} {:>12} {:>12} {:>12} {:>12}",
+                i,
+                counts.transcript,
+                counts.merkle,
+                counts.folding,
+                counts.consistency,
+                counts.total()
+            )?;
+        }
+        writeln!(f, "{:>6} {:>64}", "total", self.total)
+    }
+}
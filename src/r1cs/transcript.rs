@@ -0,0 +1,196 @@
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig},
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    boolean::Boolean, convert::ToBitsGadget, eq::EqGadget, fields::fp::FpVar, uint8::UInt8,
+};
+use ark_relations::r1cs::ConstraintSystemRef;
+
+use super::GadgetError;
+use crate::transcript::{IOPattern, Op, TranscriptError};
+
+// The Fiat-Shamir transcript in constraints, for the Poseidon sponge. It performs the same sponge
+// operations as transcript::Transcript, so that both derive the same challenges, and checks them
+// against the same IO pattern (which only depends on the parameters, so adds no constraints).
+pub struct TranscriptVar<F: PrimeField> {
+    sponge: PoseidonSpongeVar<F>,
+    pattern: IOPattern,
+    position: usize,
+}
+
+impl<F: PrimeField> TranscriptVar<F> {
+    pub fn new(
+        cs: ConstraintSystemRef<F>,
+        fiat_shamir_config: &PoseidonConfig<F>,
+        pattern: IOPattern,
+    ) -> Self {
+        Self {
+            sponge: PoseidonSpongeVar::new(cs, fiat_shamir_config),
+            pattern,
+            position: 0,
+        }
+    }
+
+    fn expect(&mut self, op: Op) -> Result<(), TranscriptError> {
+        self.pattern.expect(&mut self.position, op)
+    }
+
+    // A Merkle root, given by the field elements of its digest
+    pub fn append_commitment(&mut self, root: &[FpVar<F>]) -> Result<(), GadgetError> {
+        self.expect(Op::Commitment)?;
+        self.sponge.absorb(&root)?;
+        Ok(())
+    }
+
+    pub fn append_field_elements(&mut self, elements: &[FpVar<F>]) -> Result<(), GadgetError> {
+        self.expect(Op::FieldElements(elements.len()))?;
+        self.sponge.absorb(&elements)?;
+        Ok(())
+    }
+
+    pub fn challenge_scalar(&mut self) -> Result<FpVar<F>, GadgetError> {
+        Ok(self.challenge_scalars(1)?.remove(0))
+    }
+
+    pub fn challenge_scalars(&mut self, count: usize) -> Result<Vec<FpVar<F>>, GadgetError> {
+        self.expect(Op::ChallengeScalars(count))?;
+        Ok(self.sponge.squeeze_field_elements(count)?)
+    }
+
+    // Samples count indexes in 0..range (which must be a power of two), as little-endian bits.
+    // Unlike the native transcript, the indexes are neither deduplicated nor sorted: that depends
+    // on the assignment, so callers have to handle repeated indexes themselves.
+    pub fn challenge_indices(
+        &mut self,
+        count: usize,
+        range: usize,
+    ) -> Result<Vec<Vec<Boolean<F>>>, GadgetError> {
+        assert!(range.is_power_of_two());
+        self.expect(Op::ChallengeIndices(count))?;
+        let log_range = range.ilog2() as usize;
+        (0..count)
+            .map(|_| {
+                // As in utils::squeeze_integer, the index is the low bits of 8 squeezed bytes
+                let mut bits = self.sponge.squeeze_bytes(8)?.to_bits_le()?;
+                bits.truncate(log_range);
+                Ok(bits)
+            })
+            .collect()
+    }
+
+    // Verifier side of the proof of work, as in utils::proof_of_work_verify. The nonce is a
+    // witness, so a missing nonce is a malformed proof rather than a failed check.
+    pub fn check_grind(
+        &mut self,
+        bits: usize,
+        pow_nonce: Option<usize>,
+    ) -> Result<(), GadgetError> {
+        assert!(bits <= 32);
+        self.expect(Op::Grind(bits))?;
+        if bits == 0 {
+            return Ok(());
+        }
+
+        let nonce = pow_nonce.ok_or(GadgetError::MalformedProof)?;
+        let nonce_bytes = UInt8::new_witness_vec(self.sponge.cs(), &nonce.to_le_bytes())?;
+        self.sponge.absorb(&nonce_bytes.as_slice())?;
+        let pow_bytes = self.sponge.squeeze_bytes(4)?;
+        // The squeezed u32 must have at least bits trailing zeros
+        for bit in &pow_bytes.to_bits_le()?[..bits] {
+            bit.enforce_equal(&Boolean::FALSE)?;
+        }
+        Ok(())
+    }
+
+    // Checks that the whole IO pattern was used
+    pub fn finish(self) -> Result<(), GadgetError> {
+        Ok(self.pattern.finish(self.position)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::{
+        crypto::{fields::Field64 as TestField, fs, merkle_tree::poseidon::PoseidonDigest},
+        transcript::Transcript,
+        utils,
+    };
+
+    fn pattern() -> IOPattern {
+        IOPattern::new()
+            .commitment()
+            .challenge_scalars(2)
+            .field_elements(2)
+            .challenge_indices(4)
+            .grind(4)
+    }
+
+    // Runs the gadget on the given messages, returning the (deduplicated) indexes it samples
+    fn run_gadget(
+        cs: ConstraintSystemRef<TestField>,
+        root: [TestField; 2],
+        elements: &[TestField],
+        pow_nonce: Option<usize>,
+        expected_scalars: &[TestField],
+    ) -> Vec<usize> {
+        let config = fs::poseidon::default_fs_config::<TestField>();
+        let mut transcript = TranscriptVar::new(cs.clone(), &config, pattern());
+        let root = Vec::<FpVar<_>>::new_witness(cs.clone(), || Ok(root.to_vec())).unwrap();
+        transcript.append_commitment(&root).unwrap();
+        let scalars = transcript.challenge_scalars(2).unwrap();
+        assert_eq!(scalars.value().unwrap(), expected_scalars);
+        let elements = Vec::<FpVar<_>>::new_witness(cs.clone(), || Ok(elements.to_vec())).unwrap();
+        transcript.append_field_elements(&elements).unwrap();
+        let indexes = transcript.challenge_indices(4, 16).unwrap();
+        transcript.check_grind(4, pow_nonce).unwrap();
+        transcript.finish().unwrap();
+
+        utils::dedup(indexes.iter().map(|bits| {
+            bits.iter()
+                .rev()
+                .fold(0, |acc, bit| 2 * acc + bit.value().unwrap() as usize)
+        }))
+    }
+
+    #[test]
+    fn test_matches_native() {
+        let config = fs::poseidon::default_fs_config::<TestField>();
+        let root = [TestField::from(1), TestField::from(2)];
+        let elements = [TestField::from(3), TestField::from(4)];
+
+        let mut transcript = Transcript::<fs::poseidon::Sponge<TestField>>::new(&config, pattern());
+        transcript.append_commitment(&PoseidonDigest(root)).unwrap();
+        let scalars: Vec<TestField> = transcript.challenge_scalars(2).unwrap();
+        transcript.append_field_elements(&elements).unwrap();
+        let indexes = transcript.challenge_indices(4, 16).unwrap();
+        let nonce = transcript.grind(4).unwrap();
+        assert!(nonce.is_some());
+
+        let cs = ConstraintSystem::new_ref();
+        let gadget_indexes = run_gadget(cs.clone(), root, &elements, nonce, &scalars);
+        assert_eq!(gadget_indexes, indexes);
+        assert!(cs.is_satisfied().unwrap());
+
+        // Some nonce in 0..16 fails a 4 bit proof of work
+        let bad_nonce = (0..16)
+            .find(|&nonce| {
+                let mut transcript =
+                    Transcript::<fs::poseidon::Sponge<TestField>>::new(&config, pattern());
+                transcript.append_commitment(&PoseidonDigest(root)).unwrap();
+                transcript.challenge_scalars::<TestField>(2).unwrap();
+                transcript.append_field_elements(&elements).unwrap();
+                transcript.challenge_indices(4, 16).unwrap();
+                transcript.check_grind(4, Some(nonce)).is_err()
+            })
+            .unwrap();
+        let cs = ConstraintSystem::new_ref();
+        run_gadget(cs.clone(), root, &elements, Some(bad_nonce), &scalars);
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
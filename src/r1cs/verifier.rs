@@ -0,0 +1,850 @@
+use ark_crypto_primitives::{merkle_tree::MultiPath, sponge::Absorb};
+use ark_ff::{FftField, PrimeField};
+use ark_poly::EvaluationDomain;
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    select::CondSelectGadget,
+    R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use super::{
+    merkle::{PathVar, PoseidonDigestVar},
+    transcript::TranscriptVar,
+    ConstraintCounts, ConstraintReport, GadgetError,
+};
+use crate::{
+    crypto::{
+        fs::poseidon::Sponge,
+        merkle_tree::{decode_paths, poseidon::MerkleTreeParams},
+    },
+    domain::Domain,
+    parameters::Parameters,
+    stir::{
+        common::{Commitment, Proof, RoundProof},
+        parameters::{FinalPhase, FullParameters},
+    },
+    utils,
+};
+
+type StirParameters<F> = FullParameters<F, MerkleTreeParams<F>, Sponge<F>>;
+
+// A point and the evaluation of an oracle there
+type EvaluationVar<F> = (FpVar<F>, FpVar<F>);
+
+// The quotient set of a virtual function, see stir::verifier::VirtualFunction. The folded points of
+// a round are queried at possibly repeated indexes, and only the first occurrence of each is part
+// of the quotient set.
+struct VirtualFunctionVar<F: PrimeField> {
+    comb_randomness: FpVar<F>,
+    ans_coefficients: Vec<FpVar<F>>,
+    ood_points: Vec<FpVar<F>>,
+    stir_points: Vec<FpVar<F>>,
+    is_new: Vec<Boolean<F>>,
+}
+
+// Mirrors stir::verifier::DegreeCorrection
+struct DegreeCorrectionVar<F: PrimeField> {
+    randomness: FpVar<F>,
+    degree: usize,
+}
+
+enum OracleVar<F: PrimeField> {
+    Initial(Option<DegreeCorrectionVar<F>>),
+    Virtual(VirtualFunctionVar<F>),
+}
+
+// The domain of each round only depends on the parameters, so it is kept as constants
+struct VerificationStateVar<F: PrimeField> {
+    oracle: OracleVar<F>,
+    domain_gen: F,
+    domain_size: usize,
+    domain_offset: F,
+    root_of_unity: F,
+    folding_randomness: FpVar<F>,
+    num_round: usize,
+}
+
+impl<F: PrimeField> VerificationStateVar<F> {
+    // Mirrors stir::verifier::VerificationState::query
+    fn query(
+        &self,
+        evaluation_point: &FpVar<F>,
+        value_of_prev_oracle: &FpVar<F>,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        let virtual_function = match &self.oracle {
+            OracleVar::Initial(None) => return Ok(value_of_prev_oracle.clone()),
+            OracleVar::Initial(Some(correction)) => {
+                let common_factor = evaluation_point * &correction.randomness;
+                return Ok(value_of_prev_oracle * geometric_sum(&common_factor, correction.degree)?);
+            }
+            OracleVar::Virtual(virtual_function) => virtual_function,
+        };
+
+        let mut denominator = FpVar::one();
+        for point in &virtual_function.ood_points {
+            denominator *= evaluation_point - point;
+        }
+        for (point, is_new) in virtual_function
+            .stir_points
+            .iter()
+            .zip(&virtual_function.is_new)
+        {
+            denominator *=
+                FpVar::conditionally_select(is_new, &(evaluation_point - point), &FpVar::one())?;
+        }
+        let ans_eval = evaluate(&virtual_function.ans_coefficients, evaluation_point);
+        let quotient_evaluation =
+            quotient_with_hint(value_of_prev_oracle, &denominator, &ans_eval)?;
+
+        // The native scale factor (1 - common_factor^(n + 1)) / (1 - common_factor), for a quotient
+        // set of size n, as the geometric sum (which also covers common_factor = 1)
+        let common_factor = evaluation_point * &virtual_function.comb_randomness;
+        let mut power = FpVar::one();
+        let mut scale_factor = FpVar::one();
+        for _ in &virtual_function.ood_points {
+            power *= &common_factor;
+            scale_factor += &power;
+        }
+        for is_new in &virtual_function.is_new {
+            power = FpVar::conditionally_select(is_new, &(&power * &common_factor), &power)?;
+            scale_factor += FpVar::from(is_new.clone()) * &power;
+        }
+
+        Ok(quotient_evaluation * scale_factor)
+    }
+}
+
+// 1 + c + c^2 + ... + c^n, as (1 - c^(n + 1)) / (1 - c), or n + 1 if c = 1. The sums of the
+// virtual functions above depend on which stir points are new, so they are added up instead.
+fn geometric_sum<F: PrimeField>(
+    common_factor: &FpVar<F>,
+    n: usize,
+) -> Result<FpVar<F>, SynthesisError> {
+    let is_one = common_factor.is_one()?;
+    let numerator = FpVar::one() - common_factor.pow_by_constant([(n + 1) as u64])?;
+    let denominator =
+        FpVar::conditionally_select(&is_one, &FpVar::one(), &(FpVar::one() - common_factor))?;
+    FpVar::conditionally_select(
+        &is_one,
+        &FpVar::constant(F::from((n + 1) as u64)),
+        &numerator.mul_by_inverse(&denominator)?,
+    )
+}
+
+// An opened leaf of the previous oracle
+struct QueryVar<F: PrimeField> {
+    // The index in little-endian bits
+    index: Vec<Boolean<F>>,
+    // The evaluations on the coset, without the salt
+    answers: Vec<FpVar<F>>,
+}
+
+// Horner's rule
+fn evaluate<F: PrimeField>(coefficients: &[FpVar<F>], point: &FpVar<F>) -> FpVar<F> {
+    coefficients
+        .iter()
+        .rev()
+        .fold(FpVar::zero(), |acc, coefficient| acc * point + coefficient)
+}
+
+// The counterpart of poly_utils::quotient::quotient_with_hint. In constraints, the quotient itself
+// is the hint: it is a witness, checked against the denominator with a single constraint (and the
+// denominator is enforced to be non-zero, where the native version panics).
+fn quotient_with_hint<F: PrimeField>(
+    claimed_eval: &FpVar<F>,
+    denominator: &FpVar<F>,
+    ans_eval: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    (claimed_eval - ans_eval).mul_by_inverse(denominator)
+}
+
+// The counterpart of poly_utils::interpolation::evaluate_interpolation, for points on the coset
+// coset_offset * <generator> of size values.len(). In Lagrange form, the denominators
+// prod_{m != j} (x_j - x_m) are coset_offset^(k - 1) times constants, so a single inversion is
+// needed.
+fn evaluate_interpolation<F: PrimeField>(
+    coset_offset: &FpVar<F>,
+    generator: F,
+    values: &[FpVar<F>],
+    point: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let size = values.len();
+    assert!(utils::is_power_of_two(size));
+    let roots: Vec<F> = (0..size).map(|j| generator.pow([j as u64])).collect();
+    let differences: Vec<_> = roots
+        .iter()
+        .map(|root| point - coset_offset * *root)
+        .collect();
+
+    // prod_{m != j} (point - x_m), from prefix and suffix products
+    let mut prefixes = vec![FpVar::one()];
+    for difference in &differences {
+        prefixes.push(prefixes.last().unwrap() * difference);
+    }
+    let mut suffixes = vec![FpVar::one()];
+    for difference in differences.iter().rev() {
+        suffixes.push(suffixes.last().unwrap() * difference);
+    }
+    suffixes.reverse();
+
+    let mut sum = FpVar::zero();
+    for (j, value) in values.iter().enumerate() {
+        let denominator: F = (0..size)
+            .filter(|&m| m != j)
+            .map(|m| roots[j] - roots[m])
+            .product();
+        sum += value * (&prefixes[j] * &suffixes[j + 1]) * denominator.inverse().unwrap();
+    }
+
+    // coset_offset^(k - 1) = coset_offset * coset_offset^2 * ... * coset_offset^(k / 2)
+    let mut offset_power = FpVar::one();
+    let mut square = coset_offset.clone();
+    for _ in 0..size.ilog2() {
+        offset_power *= &square;
+        square = square.square()?;
+    }
+    sum.mul_by_inverse(&offset_power)
+}
+
+// The index as a field element, for each query, and whether it is the first query at that index
+fn first_occurrences<F: PrimeField>(
+    queries: &[QueryVar<F>],
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let indexes = queries
+        .iter()
+        .map(|query| Boolean::le_bits_to_fp(&query.index))
+        .collect::<Result<Vec<_>, _>>()?;
+    (0..indexes.len())
+        .map(|t| {
+            let distinct = (0..t)
+                .map(|s| indexes[t].is_neq(&indexes[s]))
+                .collect::<Result<Vec<_>, _>>()?;
+            if distinct.is_empty() {
+                Ok(Boolean::TRUE)
+            } else {
+                Boolean::kary_and(&distinct)
+            }
+        })
+        .collect()
+}
+
+// Adds the number of constraints that f generates to counter
+fn measure<F: PrimeField, R>(
+    cs: &ConstraintSystemRef<F>,
+    counter: &mut usize,
+    f: impl FnOnce() -> R,
+) -> R {
+    let before = cs.num_constraints();
+    let result = f();
+    *counter += cs.num_constraints() - before;
+    result
+}
+
+// Verifies STIR proofs for the Poseidon instantiation in constraints over the field of the proof.
+// The commitment is a public input, and the proof is a witness.
+//
+// The constraints are satisfied exactly when StirVerifier accepts, with two differences. The
+// queried leaves are opened one authentication path at a time, so the gadget is at least as strict
+// as MultiPath::verify. And the ans and shake polynomials must have fewer than ood_samples +
+// repetitions coefficients (which honest proofs do), as the circuit needs a bound on their size.
+pub struct StirVerifierGadget<F>
+where
+    F: FftField + PrimeField + Absorb,
+{
+    parameters: StirParameters<F>,
+}
+
+impl<F> StirVerifierGadget<F>
+where
+    F: FftField + PrimeField + Absorb,
+{
+    // The final phase is pinned to the one the gadget supports, whichever From would choose
+    pub fn new(parameters: Parameters<F, MerkleTreeParams<F>, Sponge<F>>) -> Self {
+        Self::new_full(StirParameters::from(parameters).with_final_phase(FinalPhase::Coefficients))
+    }
+
+    pub fn new_full(full_parameters: StirParameters<F>) -> Self {
+        assert_eq!(
+            full_parameters.final_phase,
+            FinalPhase::Coefficients,
+            "the gadget only supports sending the final polynomial in coefficient form"
+        );
+        Self {
+            parameters: full_parameters,
+        }
+    }
+
+    // Enforces that proof is accepted for commitment. Proofs that do not have the shape the
+    // parameters require cannot be assigned and are reported as malformed.
+    pub fn verify(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        commitment: &Commitment<MerkleTreeParams<F>>,
+        proof: &Proof<F, MerkleTreeParams<F>>,
+    ) -> Result<ConstraintReport, GadgetError> {
+        self.check_shape(proof)?;
+
+        let start = cs.num_constraints();
+        let mut report = ConstraintReport {
+            rounds: vec![ConstraintCounts::default(); self.parameters.num_rounds + 1],
+            total: 0,
+        };
+
+        let mut current_root = PoseidonDigestVar::new_input(cs.clone(), || Ok(commitment.root))?;
+        let mut transcript = TranscriptVar::new(
+            cs.clone(),
+            &self.parameters.fiat_shamir_config,
+            self.parameters.io_pattern(),
+        );
+        transcript.append_commitment(&current_root.0)?;
+        let degree_correction = match self.parameters.degree_correction() {
+            Some(degree) => Some(DegreeCorrectionVar {
+                randomness: transcript.challenge_scalar()?,
+                degree,
+            }),
+            None => None,
+        };
+        let folding_randomness = transcript.challenge_scalar()?;
+
+        let domain = Domain::<F>::new(
+            self.parameters.starting_degree,
+            self.parameters.starting_rate,
+        )
+        .unwrap();
+        let domain_gen = domain.element(1);
+
+        let mut verification_state = VerificationStateVar {
+            oracle: OracleVar::Initial(degree_correction),
+            domain_gen,
+            domain_size: domain.size(),
+            domain_offset: F::ONE,
+            root_of_unity: domain_gen,
+            folding_randomness,
+            num_round: 0,
+        };
+
+        for (num_round, round_proof) in proof.round_proofs.iter().enumerate() {
+            (verification_state, current_root) = self.round(
+                &cs,
+                &mut transcript,
+                round_proof,
+                verification_state,
+                &current_root,
+                &mut report.rounds[num_round],
+            )?;
+        }
+
+        // The final queries
+        let counts = &mut report.rounds[self.parameters.num_rounds];
+        let final_repetitions = self.parameters.repetitions[self.parameters.num_rounds];
+        let scaling_factor = verification_state.domain_size / self.parameters.folding_factor;
+        let final_randomness_indexes = measure(&cs, &mut counts.transcript, || {
+            let indexes = transcript.challenge_indices(final_repetitions, scaling_factor)?;
+            transcript.check_grind(
+                self.parameters.pow_bits[self.parameters.num_rounds],
+                proof.pow_nonce,
+            )?;
+            Ok::<_, GadgetError>(indexes)
+        })?;
+        transcript.finish()?;
+
+        let queries = measure(&cs, &mut counts.merkle, || {
+            self.open_queries(
+                &cs,
+                &proof.queries_to_final,
+                final_randomness_indexes,
+                &current_root,
+            )
+        })?;
+        let folded_answers = measure(&cs, &mut counts.folding, || {
+            self.compute_folded_evaluations(&verification_state, &queries)
+        })?;
+
+        let final_coefficients = allocate_polynomial(
+            &cs,
+            &proof.final_polynomial,
+            self.parameters.stopping_degree,
+        )?;
+        measure(&cs, &mut counts.consistency, || {
+            folded_answers.iter().try_for_each(|(point, value)| {
+                evaluate(&final_coefficients, point).enforce_equal(value)
+            })
+        })?;
+
+        report.total = cs.num_constraints() - start;
+        Ok(report)
+    }
+
+    // The sizes of the proof that the circuit depends on. Everything else is checked when the
+    // queries are opened.
+    fn check_shape(&self, proof: &Proof<F, MerkleTreeParams<F>>) -> Result<(), GadgetError> {
+        let ood_samples = self.parameters.ood_samples;
+        let well_formed = proof.round_proofs.len() == self.parameters.num_rounds
+            && proof.final_polynomial.len() <= self.parameters.stopping_degree
+            && proof
+                .round_proofs
+                .iter()
+                .zip(&self.parameters.repetitions)
+                .all(|(round_proof, repetitions)| {
+                    round_proof.betas.len() == ood_samples
+                        && round_proof.ans_polynomial.coeffs.len() <= ood_samples + repetitions
+                        && round_proof.shake_polynomial.coeffs.len() <= ood_samples + repetitions
+                });
+        if !well_formed {
+            return Err(GadgetError::MalformedProof);
+        }
+        Ok(())
+    }
+
+    // Opens the leaves at the sampled indexes, enforcing their authentication paths
+    fn open_queries(
+        &self,
+        cs: &ConstraintSystemRef<F>,
+        queries: &(Vec<Vec<F>>, MultiPath<MerkleTreeParams<F>>),
+        indexes: Vec<Vec<Boolean<F>>>,
+        root: &PoseidonDigestVar<F>,
+    ) -> Result<Vec<QueryVar<F>>, GadgetError> {
+        let (answers, multi_path) = queries;
+        let leaf_size = self.parameters.folding_factor + self.parameters.salt_size;
+        let path_len = indexes[0].len() - 1;
+
+        // The leaf and path opened at each sampled index. Without an assignment the indexes are
+        // unknown, but then the witnesses are not needed either.
+        let mut witnesses = vec![None; indexes.len()];
+        if !cs.is_in_setup_mode() {
+            let sampled = indexes
+                .iter()
+                .map(|bits| {
+                    Ok(bits
+                        .value()?
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, &bit| 2 * acc + bit as usize))
+                })
+                .collect::<Result<Vec<_>, SynthesisError>>()?;
+            let leaf_indexes = &multi_path.leaf_indexes;
+            if *leaf_indexes != utils::dedup(sampled.iter().copied())
+                || answers.len() != leaf_indexes.len()
+                || multi_path.leaf_siblings_hashes.len() != leaf_indexes.len()
+                || multi_path.auth_paths_prefix_lenghts.len() != leaf_indexes.len()
+                || multi_path.auth_paths_suffixes.len() != leaf_indexes.len()
+                || answers.iter().any(|answer| answer.len() != leaf_size)
+            {
+                return Err(GadgetError::MalformedProof);
+            }
+            let Some(paths) = decode_paths(multi_path) else {
+                return Err(GadgetError::MalformedProof);
+            };
+            if paths
+                .iter()
+                .any(|(_, auth_path)| auth_path.len() != path_len)
+            {
+                return Err(GadgetError::MalformedProof);
+            }
+            for (witness, index) in witnesses.iter_mut().zip(sampled) {
+                let position = leaf_indexes.binary_search(&index).unwrap();
+                *witness = Some((&answers[position], paths[position].clone()));
+            }
+        }
+
+        indexes
+            .into_iter()
+            .zip(witnesses)
+            .map(|(index, witness)| {
+                let leaf = (0..leaf_size)
+                    .map(|j| {
+                        FpVar::new_witness(cs.clone(), || {
+                            witness
+                                .as_ref()
+                                .map(|(answer, _)| answer[j])
+                                .ok_or(SynthesisError::AssignmentMissing)
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let path = PathVar::new_witness(cs.clone(), path_len, || {
+                    witness
+                        .as_ref()
+                        .map(|(_, path)| path.clone())
+                        .ok_or(SynthesisError::AssignmentMissing)
+                })?;
+                path.verify(
+                    &self.parameters.leaf_hash_params,
+                    &self.parameters.two_to_one_params,
+                    root,
+                    &leaf,
+                    &index,
+                )?
+                .enforce_equal(&Boolean::TRUE)?;
+
+                Ok(QueryVar {
+                    index,
+                    answers: leaf[..self.parameters.folding_factor].to_vec(),
+                })
+            })
+            .collect()
+    }
+
+    // Mirrors StirVerifier::prepare_folding and StirVerifier::fold: the point of the next oracle
+    // that each query determines, and its value
+    fn compute_folded_evaluations(
+        &self,
+        verification_state: &VerificationStateVar<F>,
+        queries: &[QueryVar<F>],
+    ) -> Result<Vec<EvaluationVar<F>>, SynthesisError> {
+        let folding_factor = self.parameters.folding_factor;
+        let scaling_factor = verification_state.domain_size / folding_factor;
+        let generator = verification_state.domain_gen.pow([scaling_factor as u64]);
+
+        queries
+            .iter()
+            .map(|query| {
+                // domain_offset * domain_gen^index, one bit at a time
+                let mut coset_offset = FpVar::constant(verification_state.domain_offset);
+                let mut power = verification_state.domain_gen;
+                for bit in &query.index {
+                    coset_offset *= FpVar::from(bit.clone()) * (power - F::ONE) + F::ONE;
+                    power.square_in_place();
+                }
+
+                let f_answers = (0..folding_factor)
+                    .zip(&query.answers)
+                    .map(|(j, answer)| {
+                        let point = &coset_offset * generator.pow([j as u64]);
+                        verification_state.query(&point, answer)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let folded_answer = evaluate_interpolation(
+                    &coset_offset,
+                    generator,
+                    &f_answers,
+                    &verification_state.folding_randomness,
+                )?;
+
+                // This is the point that we are querying at, scaled_offset * domain_gen^(k * index)
+                let mut stir_randomness = coset_offset;
+                for _ in 0..folding_factor.ilog2() {
+                    stir_randomness = stir_randomness.square()?;
+                }
+
+                Ok((stir_randomness, folded_answer))
+            })
+            .collect()
+    }
+
+    fn round(
+        &self,
+        cs: &ConstraintSystemRef<F>,
+        transcript: &mut TranscriptVar<F>,
+        round_proof: &RoundProof<F, MerkleTreeParams<F>>,
+        verification_state: VerificationStateVar<F>,
+        root: &PoseidonDigestVar<F>,
+        counts: &mut ConstraintCounts,
+    ) -> Result<(VerificationStateVar<F>, PoseidonDigestVar<F>), GadgetError> {
+        let num_round = verification_state.num_round;
+        let ood_samples = self.parameters.ood_samples;
+        let num_repetitions = self.parameters.repetitions[num_round];
+        let scaling_factor = verification_state.domain_size / self.parameters.folding_factor;
+
+        let g_root = PoseidonDigestVar::new_witness(cs.clone(), || Ok(round_proof.g_root))?;
+        let betas = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(round_proof.betas.clone()))?;
+        let ans_coefficients = allocate_polynomial(
+            cs,
+            &round_proof.ans_polynomial.coeffs,
+            ood_samples + num_repetitions,
+        )?;
+        let shake_coefficients = allocate_polynomial(
+            cs,
+            &round_proof.shake_polynomial.coeffs,
+            ood_samples + num_repetitions,
+        )?;
+
+        // Redo FS
+        let (
+            ood_randomness,
+            comb_randomness,
+            new_folding_randomness,
+            stir_randomness_indexes,
+            shake_randomness,
+        ) = measure(cs, &mut counts.transcript, || {
+            transcript.append_commitment(&g_root.0)?;
+            let ood_randomness = transcript.challenge_scalars(ood_samples)?;
+            transcript.append_field_elements(&betas)?;
+            let comb_randomness = transcript.challenge_scalar()?;
+            let new_folding_randomness = transcript.challenge_scalar()?;
+            let stir_randomness_indexes =
+                transcript.challenge_indices(num_repetitions, scaling_factor)?;
+            transcript.check_grind(self.parameters.pow_bits[num_round], round_proof.pow_nonce)?;
+            let shake_randomness = transcript.challenge_scalar()?;
+            Ok::<_, GadgetError>((
+                ood_randomness,
+                comb_randomness,
+                new_folding_randomness,
+                stir_randomness_indexes,
+                shake_randomness,
+            ))
+        })?;
+
+        let queries = measure(cs, &mut counts.merkle, || {
+            self.open_queries(
+                cs,
+                &round_proof.queries_to_prev,
+                stir_randomness_indexes,
+                root,
+            )
+        })?;
+        let folded_answers = measure(cs, &mut counts.folding, || {
+            self.compute_folded_evaluations(&verification_state, &queries)
+        })?;
+
+        // The shake polynomial check, over the OOD answers and the folded answers at distinct
+        // indexes
+        let is_new = measure(cs, &mut counts.consistency, || {
+            let is_new = first_occurrences(&queries)?;
+            let ans_eval = evaluate(&ans_coefficients, &shake_randomness);
+            let shake_eval = evaluate(&shake_coefficients, &shake_randomness);
+
+            let mut expected = FpVar::zero();
+            for (alpha, beta) in ood_randomness.iter().zip(&betas) {
+                expected += (&ans_eval - beta).mul_by_inverse(&(&shake_randomness - alpha))?;
+            }
+            for ((point, value), is_new) in folded_answers.iter().zip(&is_new) {
+                let term = (&ans_eval - value).mul_by_inverse(&(&shake_randomness - point))?;
+                expected += FpVar::from(is_new.clone()) * term;
+            }
+            shake_eval.enforce_equal(&expected)?;
+            Ok::<_, SynthesisError>(is_new)
+        })?;
+
+        let next_state = VerificationStateVar {
+            oracle: OracleVar::Virtual(VirtualFunctionVar {
+                comb_randomness,
+                ans_coefficients,
+                ood_points: ood_randomness,
+                stir_points: folded_answers.into_iter().map(|(point, _)| point).collect(),
+                is_new,
+            }),
+            domain_gen: verification_state.domain_gen.square(),
+            domain_size: verification_state.domain_size / 2,
+            domain_offset: verification_state.domain_offset.square()
+                * verification_state.root_of_unity,
+            root_of_unity: verification_state.root_of_unity,
+            folding_randomness: new_folding_randomness,
+            num_round: num_round + 1,
+        };
+        Ok((next_state, g_root))
+    }
+}
+
+// The coefficients of polynomial, padded with zeros to len
+fn allocate_polynomial<F: PrimeField>(
+    cs: &ConstraintSystemRef<F>,
+    coefficients: &[F],
+    len: usize,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let mut coefficients = coefficients.to_vec();
+    coefficients.resize(len, F::ZERO);
+    Vec::new_witness(cs.clone(), || Ok(coefficients))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_crypto_primitives::merkle_tree::Config;
+    use ark_ff::Field;
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::{
+        crypto::{
+            fields::Field64 as TestField,
+            fs,
+            merkle_tree::poseidon::{self as mt, PoseidonDigest},
+        },
+        ldt::{Prover, Verifier},
+        parameters::SoundnessType,
+        stir::{prover::StirProver, verifier::StirVerifier},
+    };
+
+    type MerkleConfig = mt::MerkleTreeParams<TestField>;
+
+    fn parameters() -> Parameters<TestField, MerkleConfig, Sponge<TestField>> {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = mt::default_config::<TestField>(&mut rng, 4);
+        Parameters {
+            security_level: 40,
+            protocol_security_level: 32,
+            starting_degree: 64,
+            stopping_degree: 4,
+            folding_factor: 4,
+            starting_rate: 1,
+            soundness_type: SoundnessType::Conjecture,
+            leaf_hash_params,
+            two_to_one_params,
+            fiat_shamir_config: fs::poseidon::default_fs_config(),
+            _field: Default::default(),
+        }
+    }
+
+    fn gadget_accepts(
+        gadget: &StirVerifierGadget<TestField>,
+        commitment: &Commitment<MerkleConfig>,
+        proof: &Proof<TestField, MerkleConfig>,
+    ) -> bool {
+        let cs = ConstraintSystem::new_ref();
+        match gadget.verify(cs.clone(), commitment, proof) {
+            Ok(_) => cs.is_satisfied().unwrap(),
+            Err(GadgetError::MalformedProof) => false,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    fn perturb(digest: &mut <MerkleConfig as Config>::InnerDigest) {
+        *digest = PoseidonDigest([digest.0[0] + TestField::ONE, digest.0[1]]);
+    }
+
+    #[test]
+    fn test_accepts_as_native() {
+        let mut rng = ark_std::test_rng();
+        let full_parameters =
+            StirParameters::from(parameters()).with_final_phase(FinalPhase::Coefficients);
+        let prover = StirProver::new_full(full_parameters.clone());
+        let verifier = StirVerifier::new_full(full_parameters.clone());
+        let gadget = StirVerifierGadget::new_full(full_parameters.clone());
+
+        let polynomial = DensePolynomial::rand(63, &mut rng);
+        let (commitment, witness) = prover.commit(polynomial);
+        let proof = prover.prove(witness);
+        assert!(verifier.verify(&commitment, &proof));
+        assert!(gadget_accepts(&gadget, &commitment, &proof));
+        assert!(proof.pow_nonce.is_some());
+        // Some indexes are sampled more than once
+        assert!(
+            proof.round_proofs[0].queries_to_prev.1.leaf_indexes.len()
+                < full_parameters.repetitions[0]
+        );
+
+        let mut tampered = vec![];
+        let mut answer = proof.clone();
+        answer.round_proofs[0].queries_to_prev.0[0][1] += TestField::ONE;
+        tampered.push(answer);
+        let mut beta = proof.clone();
+        beta.round_proofs[0].betas[0] += TestField::ONE;
+        tampered.push(beta);
+        let mut ans_polynomial = proof.clone();
+        ans_polynomial.round_proofs[0].ans_polynomial.coeffs[0] += TestField::ONE;
+        tampered.push(ans_polynomial);
+        let mut shake_polynomial = proof.clone();
+        shake_polynomial.round_proofs[0].shake_polynomial.coeffs[0] += TestField::ONE;
+        tampered.push(shake_polynomial);
+        let mut g_root = proof.clone();
+        perturb(&mut g_root.round_proofs[0].g_root);
+        tampered.push(g_root);
+        let mut final_polynomial = proof.clone();
+        final_polynomial.final_polynomial[0] += TestField::ONE;
+        tampered.push(final_polynomial);
+        let mut final_answer = proof.clone();
+        final_answer.queries_to_final.0[0][0] += TestField::ONE;
+        tampered.push(final_answer);
+        let mut pow_nonce = proof.clone();
+        pow_nonce.pow_nonce = pow_nonce.pow_nonce.map(|nonce| nonce + 1);
+        tampered.push(pow_nonce);
+
+        for proof in &tampered {
+            assert!(!verifier.verify(&commitment, proof));
+            assert!(!gadget_accepts(&gadget, &commitment, proof));
+        }
+
+        let mut other_commitment = commitment.clone();
+        perturb(&mut other_commitment.root);
+        assert!(!verifier.verify(&other_commitment, &proof));
+        assert!(!gadget_accepts(&gadget, &other_commitment, &proof));
+    }
+
+    #[test]
+    fn test_degree_correction() {
+        let mut rng = ark_std::test_rng();
+        let parameters = Parameters {
+            starting_degree: 3 << 4,
+            ..parameters()
+        };
+        let full_parameters =
+            StirParameters::from(parameters).with_final_phase(FinalPhase::Coefficients);
+        assert_eq!(full_parameters.degree_correction(), Some(1 << 4));
+        let prover = StirProver::new_full(full_parameters.clone());
+        let gadget = StirVerifierGadget::new_full(full_parameters);
+
+        let (commitment, witness) = prover.commit(DensePolynomial::rand((3 << 4) - 1, &mut rng));
+        let proof = prover.prove(witness);
+        assert!(gadget_accepts(&gadget, &commitment, &proof));
+
+        let mut answer = proof.clone();
+        answer.round_proofs[0].queries_to_prev.0[0][1] += TestField::ONE;
+        assert!(!gadget_accepts(&gadget, &commitment, &answer));
+    }
+
+    #[test]
+    fn test_zero_knowledge() {
+        let mut rng = ark_std::test_rng();
+        let parameters = Parameters {
+            security_level: 16,
+            protocol_security_level: 16,
+            starting_degree: 256,
+            ..parameters()
+        };
+        let full_parameters = StirParameters::zero_knowledge(parameters);
+        let prover = StirProver::new_full(full_parameters.clone());
+        let gadget = StirVerifierGadget::new_full(full_parameters.clone());
+
+        let polynomial = DensePolynomial::rand(full_parameters.witness_degree() - 1, &mut rng);
+        let (commitment, witness) = prover.commit(polynomial);
+        let proof = prover.prove(witness);
+        assert!(gadget_accepts(&gadget, &commitment, &proof));
+
+        let mut unsalted = proof.clone();
+        unsalted.queries_to_final.0[0].pop();
+        assert!(!gadget_accepts(&gadget, &commitment, &unsalted));
+    }
+
+    #[test]
+    fn test_constraint_report() {
+        let mut rng = ark_std::test_rng();
+        let full_parameters =
+            StirParameters::from(parameters()).with_final_phase(FinalPhase::Coefficients);
+        let prover = StirProver::new_full(full_parameters.clone());
+        let gadget = StirVerifierGadget::new_full(full_parameters.clone());
+
+        let (commitment, witness) = prover.commit(DensePolynomial::rand(63, &mut rng));
+        let proof = prover.prove(witness);
+
+        let cs = ConstraintSystem::new_ref();
+        let report = gadget.verify(cs.clone(), &commitment, &proof).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(report.total, cs.num_constraints());
+        assert_eq!(report.rounds.len(), full_parameters.num_rounds + 1);
+        for counts in &report.rounds {
+            assert!(counts.transcript > 0 && counts.merkle > 0);
+            assert!(counts.folding > 0 && counts.consistency > 0);
+        }
+        assert!(
+            report
+                .rounds
+                .iter()
+                .map(ConstraintCounts::total)
+                .sum::<usize>()
+                < report.total
+        );
+
+        // Malformed proofs cannot be assigned
+        let mut short = proof.clone();
+        short.round_proofs.pop();
+        assert!(matches!(
+            gadget.verify(ConstraintSystem::new_ref(), &commitment, &short),
+            Err(GadgetError::MalformedProof)
+        ));
+    }
+}
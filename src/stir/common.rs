@@ -2,6 +2,9 @@ use ark_crypto_primitives::merkle_tree::{Config, MultiPath};
 use ark_ff::FftField;
 use ark_poly::univariate::DensePolynomial;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use derivative::Derivative;
+
+use super::parameters::FinalPhase;
 
 #[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct Commitment<MerkleConfig>
@@ -11,16 +14,63 @@ where
     pub(crate) root: MerkleConfig::InnerDigest,
 }
 
-#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+#[derive(Debug, Derivative, CanonicalSerialize, CanonicalDeserialize)]
+#[derivative(Clone(bound = "F: Clone"))]
 pub struct Proof<F: FftField, MerkleConfig: Config> {
     pub(crate) round_proofs: Vec<RoundProof<F, MerkleConfig>>,
-    pub(crate) final_polynomial: DensePolynomial<F>,
+    // The final polynomial in the form the final phase sends it: its coefficients, its evaluations,
+    // or nothing if the verifier reads the last oracle directly or the last round sends its
+    // folding
+    pub(crate) final_polynomial: Vec<F>,
     pub(crate) queries_to_final: (Vec<Vec<F>>, MultiPath<MerkleConfig>),
     pub(crate) pow_nonce: Option<usize>,
 }
 
-#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+// A proof that several committed functions are all close to low degree polynomials. proof is a
+// proof for a random linear combination of the functions, whose first queries open the first
+// commitment; queries_to_commitments opens the others at the same leaves.
+#[derive(Debug, Derivative, CanonicalSerialize, CanonicalDeserialize)]
+#[derivative(Clone(bound = "F: Clone"))]
+pub struct AggregateProof<F: FftField, MerkleConfig: Config> {
+    pub(crate) proof: Proof<F, MerkleConfig>,
+    pub(crate) queries_to_commitments: Vec<(Vec<Vec<F>>, MultiPath<MerkleConfig>)>,
+}
+
+impl<F: FftField, MerkleConfig: Config> Proof<F, MerkleConfig> {
+    // The queries to the committed function: those of the first round, or the final ones if there
+    // are no rounds
+    pub(crate) fn queries_to_initial(&self) -> &(Vec<Vec<F>>, MultiPath<MerkleConfig>) {
+        match self.round_proofs.first() {
+            Some(round_proof) => &round_proof.queries_to_prev,
+            None => &self.queries_to_final,
+        }
+    }
+
+    pub(crate) fn queries_to_initial_mut(&mut self) -> &mut (Vec<Vec<F>>, MultiPath<MerkleConfig>) {
+        match self.round_proofs.first_mut() {
+            Some(round_proof) => &mut round_proof.queries_to_prev,
+            None => &mut self.queries_to_final,
+        }
+    }
+
+    // The queries to the last oracle: those of the last round if it commits to no oracle, or the
+    // final ones otherwise
+    pub(crate) fn queries_to_last(
+        &self,
+        final_phase: FinalPhase,
+    ) -> &(Vec<Vec<F>>, MultiPath<MerkleConfig>) {
+        match (final_phase, self.round_proofs.last()) {
+            (FinalPhase::SkipLastCommit, Some(round_proof)) => &round_proof.queries_to_prev,
+            _ => &self.queries_to_final,
+        }
+    }
+}
+
+#[derive(Debug, Derivative, CanonicalSerialize, CanonicalDeserialize)]
+#[derivative(Clone(bound = "F: Clone"))]
 pub struct RoundProof<F: FftField, MerkleConfig: Config> {
+    // The default digest in the last round of FinalPhase::SkipLastCommit, which commits to no
+    // oracle
     pub(crate) g_root: MerkleConfig::InnerDigest,
     pub(crate) betas: Vec<F>,
     pub(crate) ans_polynomial: DensePolynomial<F>,
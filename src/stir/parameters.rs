@@ -1,13 +1,34 @@
 use ark_crypto_primitives::merkle_tree::Config;
 use ark_crypto_primitives::sponge::CryptographicSponge;
 use ark_ff::FftField;
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use derivative::Derivative;
 use std::fmt::Display;
 use std::ops::Deref;
 
+use crate::crypto::fields::field_size_bits;
 use crate::parameters::Parameters;
+use crate::transcript::IOPattern;
 use crate::utils;
 
+// How the prover convinces the verifier that the folding of the last oracle has low degree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalPhase {
+    // The final polynomial is sent in coefficient form, and the folding of the last oracle is
+    // checked against it at final_repetitions random points
+    Coefficients,
+    // As above, with the final polynomial sent as its evaluations over the domain of size
+    // stopping_degree
+    Evaluations,
+    // Nothing is sent: the last oracle is opened entirely, and the verifier checks that the
+    // codeword it folds to has low degree
+    Direct,
+    // The last round commits to no oracle: it sends the folding of the oracle it queries as its
+    // ans polynomial, of degree below the last degree bound, and the verifier checks the queried
+    // foldings against it. Nothing is sent after the last round
+    SkipLastCommit,
+}
+
 #[derive(Derivative)]
 #[derivative(Debug, Clone(bound = ""))]
 pub struct FullParameters<F, MerkleConfig, FSConfig>
@@ -19,12 +40,21 @@ where
 {
     #[derivative(Debug(bound = "F: std::fmt::Debug"))]
     pub(crate) parameters: Parameters<F, MerkleConfig, FSConfig>,
+    // The degree bound the committed function has to satisfy. The starting degree of parameters is
+    // rounded up to the next power of two, and the function is degree corrected to it.
+    pub(crate) degree_bound: usize,
     pub(crate) num_rounds: usize,
     pub(crate) rates: Vec<usize>,
     pub(crate) repetitions: Vec<usize>,
     pub(crate) pow_bits: Vec<usize>,
     pub(crate) ood_samples: usize,
     pub(crate) degrees: Vec<usize>,
+    // Zero-knowledge: the witness is masked with a random polynomial of this degree, and so has
+    // to have degree < starting_degree - masking_degree. 0 if not zero-knowledge
+    pub(crate) masking_degree: usize,
+    // Number of random field elements appended to each Merkle leaf. 0 if not zero-knowledge
+    pub(crate) salt_size: usize,
+    pub(crate) final_phase: FinalPhase,
 }
 
 impl<F, MerkleConfig, FSConfig> Display for FullParameters<F, MerkleConfig, FSConfig>
@@ -36,6 +66,13 @@ where
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         Parameters::fmt(&self.parameters, f)?;
+        if let Some(correction_degree) = self.degree_correction() {
+            writeln!(
+                f,
+                "Degree bound: {}, degree corrected by {}",
+                self.degree_bound, correction_degree
+            )?;
+        }
 
         writeln!(
             f,
@@ -52,7 +89,16 @@ where
                 .join(", ")
         )?;
         writeln!(f, "PoW bits: {:?}", self.pow_bits)?;
-        writeln!(f, "Repetitions: {:?}", self.repetitions)
+        writeln!(f, "Repetitions: {:?}", self.repetitions)?;
+        writeln!(f, "Final phase: {:?}", self.final_phase)?;
+        if self.masking_degree > 0 {
+            writeln!(
+                f,
+                "Zero-knowledge: masking degree: {}, salt size: {}",
+                self.masking_degree, self.salt_size
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -64,10 +110,12 @@ where
     FSConfig: CryptographicSponge,
     FSConfig::Config: Clone,
 {
-    fn from(parameters: Parameters<F, MerkleConfig, FSConfig>) -> Self {
+    fn from(mut parameters: Parameters<F, MerkleConfig, FSConfig>) -> Self {
         assert!(utils::is_power_of_two(parameters.folding_factor));
-        assert!(utils::is_power_of_two(parameters.starting_degree));
         assert!(utils::is_power_of_two(parameters.stopping_degree));
+        let degree_bound = parameters.starting_degree;
+        assert!(degree_bound > 0);
+        parameters.starting_degree = degree_bound.next_power_of_two();
 
         // TODO: I don't even need to iterate but I don't pay for these cycles
         let mut d = parameters.starting_degree;
@@ -103,14 +151,217 @@ where
         assert_eq!(num_rounds + 1, rates.len());
         assert_eq!(num_rounds + 1, repetitions.len());
 
-        Self {
+        let mut full_parameters = Self {
             parameters,
+            degree_bound,
             num_rounds,
             degrees,
             rates,
             pow_bits,
             ood_samples: 2,
             repetitions,
+            masking_degree: 0,
+            salt_size: 0,
+            final_phase: FinalPhase::Coefficients,
+        };
+        // Whichever final phase makes for the smallest proofs
+        for final_phase in [FinalPhase::Direct, FinalPhase::SkipLastCommit] {
+            if final_phase == FinalPhase::SkipLastCommit && full_parameters.num_rounds == 0 {
+                continue;
+            }
+            let candidate = full_parameters.clone().with_final_phase(final_phase);
+            if candidate.proof_size() < full_parameters.proof_size() {
+                full_parameters = candidate;
+            }
+        }
+        full_parameters
+    }
+}
+
+impl<F, MerkleConfig, FSConfig> FullParameters<F, MerkleConfig, FSConfig>
+where
+    F: FftField,
+    MerkleConfig: Config,
+    FSConfig: CryptographicSponge,
+    FSConfig::Config: Clone,
+{
+    // Parameters for zero-knowledge STIR.
+    // The verifier learns the queried leaves, the OOD answers and the final polynomial, all of which
+    // are linear functions of the initial witness. Masking with a random polynomial with at least
+    // as many coefficients makes them uniformly random, as long as they are linearly independent
+    // functions of the mask (which happens with high probability).
+    pub fn zero_knowledge(parameters: Parameters<F, MerkleConfig, FSConfig>) -> Self {
+        let mut full_parameters = Self::from(parameters);
+        // Opening the last oracle entirely, or sending its folding, would reveal it
+        if !matches!(
+            full_parameters.final_phase,
+            FinalPhase::Coefficients | FinalPhase::Evaluations
+        ) {
+            full_parameters = full_parameters.with_final_phase(FinalPhase::Coefficients);
+        }
+
+        let revealed_evaluations: usize = full_parameters
+            .repetitions
+            .iter()
+            .map(|r| r * full_parameters.folding_factor)
+            .sum::<usize>()
+            + full_parameters.num_rounds * full_parameters.ood_samples
+            + full_parameters.stopping_degree;
+        assert!(
+            revealed_evaluations < full_parameters.degree_bound,
+            "starting degree is too small to be masked"
+        );
+
+        full_parameters.masking_degree = revealed_evaluations;
+        full_parameters.salt_size = full_parameters
+            .security_level
+            .div_ceil(field_size_bits::<F>());
+        full_parameters
+    }
+
+    // Overrides the final phase that From chose
+    pub fn with_final_phase(mut self, final_phase: FinalPhase) -> Self {
+        assert!(
+            self.masking_degree == 0
+                || matches!(
+                    final_phase,
+                    FinalPhase::Coefficients | FinalPhase::Evaluations
+                ),
+            "the last oracle cannot be revealed in zero-knowledge"
+        );
+        assert!(
+            self.num_rounds > 0 || final_phase != FinalPhase::SkipLastCommit,
+            "there is no round to skip the commitment of"
+        );
+        self.final_phase = final_phase;
+        if self.num_rounds > 0 {
+            // The repetitions of a round are bounded by the degree of its ans polynomial, which
+            // they determine, unless it is the folding itself
+            let round = self.num_rounds - 1;
+            let log_inv_rate = self.rates[round];
+            let repetitions = self.parameters.repetitions(log_inv_rate);
+            (self.repetitions[round], self.pow_bits[round]) = match final_phase {
+                FinalPhase::SkipLastCommit => (
+                    repetitions,
+                    self.parameters
+                        .pow_bits_for_repetitions(log_inv_rate, repetitions),
+                ),
+                _ => (
+                    repetitions.min(self.degrees[round] / self.folding_factor),
+                    self.parameters.pow_bits(log_inv_rate),
+                ),
+            };
+        }
+        self
+    }
+
+    // The rounds that commit to an oracle: all of them, but the last one in
+    // FinalPhase::SkipLastCommit
+    pub(crate) fn committed_rounds(&self) -> usize {
+        match self.final_phase {
+            FinalPhase::SkipLastCommit => self.num_rounds - 1,
+            _ => self.num_rounds,
+        }
+    }
+
+    // The number of leaves of the oracle of a round (num_rounds for the last oracle), whose domain
+    // is halved in every round
+    pub(crate) fn num_leaves(&self, round: usize) -> usize {
+        ((self.starting_degree << self.starting_rate) >> round) / self.folding_factor
+    }
+
+    pub(crate) fn final_num_leaves(&self) -> usize {
+        self.num_leaves(self.num_rounds)
+    }
+
+    // The domain the final polynomial is evaluated over in FinalPhase::Evaluations
+    pub(crate) fn final_domain(&self) -> Radix2EvaluationDomain<F> {
+        Radix2EvaluationDomain::new(self.stopping_degree).unwrap()
+    }
+
+    // An estimate of the size of a proof, counting field elements and digests alike. An opening of
+    // every leaf has about two digests per leaf: the sibling of the leaf and the suffix of its
+    // authentication path.
+    fn proof_size(&self) -> usize {
+        let leaf_size = self.folding_factor + self.salt_size;
+        let queries_size = |round: usize| {
+            self.repetitions[round] * (leaf_size + self.num_leaves(round).ilog2() as usize)
+        };
+        // The root, the OOD answers, the ans and shake polynomials and the queries
+        let rounds_size: usize = (0..self.committed_rounds())
+            .map(|round| {
+                1 + self.ood_samples
+                    + 2 * (self.ood_samples + self.repetitions[round])
+                    + queries_size(round)
+            })
+            .sum();
+        rounds_size
+            + match self.final_phase {
+                FinalPhase::Coefficients | FinalPhase::Evaluations => {
+                    self.stopping_degree + queries_size(self.num_rounds)
+                }
+                FinalPhase::Direct => self.final_num_leaves() * (leaf_size + 2),
+                FinalPhase::SkipLastCommit => {
+                    self.degrees[self.num_rounds] + queries_size(self.num_rounds - 1)
+                }
+            }
+    }
+
+    // The degree bound that the witness polynomial has to satisfy
+    pub fn witness_degree(&self) -> usize {
+        self.degree_bound - self.masking_degree
+    }
+
+    // The degree of the polynomial 1 + r * x + ... + r^e * x^e that the committed function is
+    // multiplied by, so that it has degree < starting_degree if it has degree < degree_bound (and,
+    // with high probability over r, only then). None if the degree bound is a power of two.
+    pub fn degree_correction(&self) -> Option<usize> {
+        Some(self.starting_degree - self.degree_bound).filter(|&degree| degree > 0)
+    }
+
+    // The Fiat-Shamir interaction of the prover and the verifier
+    pub fn io_pattern(&self) -> IOPattern {
+        self.rounds_io_pattern(IOPattern::new().commitment())
+    }
+
+    // The interaction of a proof aggregating several commitments: all of them are absorbed, and
+    // the randomness that combines them is sampled before the rounds
+    pub fn aggregate_io_pattern(&self, num_commitments: usize) -> IOPattern {
+        let pattern =
+            (0..num_commitments).fold(IOPattern::new(), |pattern, _| pattern.commitment());
+        self.rounds_io_pattern(pattern.challenge_scalars(1))
+    }
+
+    // Appends the degree correction and folding randomness, the rounds and the final queries to a
+    // pattern that absorbed the committed function
+    fn rounds_io_pattern(&self, mut pattern: IOPattern) -> IOPattern {
+        if self.degree_correction().is_some() {
+            pattern = pattern.challenge_scalars(1);
+        }
+        pattern = pattern.challenge_scalars(1);
+        for round in 0..self.committed_rounds() {
+            pattern = pattern
+                .commitment()
+                .challenge_scalars(self.ood_samples)
+                .field_elements(self.ood_samples)
+                // Combination and folding randomness
+                .challenge_scalars(1)
+                .challenge_scalars(1)
+                .challenge_indices(self.repetitions[round])
+                .grind(self.pow_bits[round])
+                // Shake randomness
+                .challenge_scalars(1);
+        }
+        match self.final_phase {
+            FinalPhase::Coefficients | FinalPhase::Evaluations => pattern
+                .challenge_indices(self.repetitions[self.num_rounds])
+                .grind(self.pow_bits[self.num_rounds]),
+            FinalPhase::Direct => pattern,
+            // The ans polynomial of the last round, padded to its degree bound
+            FinalPhase::SkipLastCommit => pattern
+                .field_elements(self.degrees[self.num_rounds])
+                .challenge_indices(self.repetitions[self.num_rounds - 1])
+                .grind(self.pow_bits[self.num_rounds - 1]),
         }
     }
 }
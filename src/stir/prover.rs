@@ -1,5 +1,5 @@
 use ark_crypto_primitives::{
-    merkle_tree::{Config, MerkleTree},
+    merkle_tree::{Config, MerkleTree, MultiPath},
     sponge::{Absorb, CryptographicSponge},
 };
 use ark_ff::{FftField, PrimeField};
@@ -7,9 +7,14 @@ use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain,
 use derivative::Derivative;
 
 use crate::{
-    ldt::Prover,
+    crypto::merkle_tree,
+    ldt::{Prover, ProverError},
     poly_utils::{self},
-    stir::{common::*, parameters::FullParameters},
+    stir::{
+        common::*,
+        parameters::{FinalPhase, FullParameters},
+    },
+    transcript::{Transcript, TranscriptError},
     utils,
 };
 
@@ -19,7 +24,8 @@ use crate::{domain::Domain, parameters::Parameters};
 #[derivative(Clone(bound = "F: Clone"))]
 pub struct Witness<F: FftField, MerkleConfig: Config> {
     pub(crate) domain: Domain<F>,
-    pub(crate) polynomial: DensePolynomial<F>,
+    // None if we committed to evaluations directly
+    pub(crate) polynomial: Option<DensePolynomial<F>>,
     pub(crate) merkle_tree: MerkleTree<MerkleConfig>,
     pub(crate) folded_evals: Vec<Vec<F>>,
 }
@@ -39,6 +45,12 @@ pub struct WitnessExtended<F: FftField, MerkleConfig: Config> {
     pub(crate) folding_randomness: F,
 }
 
+// The witness for the next round, and the proof for the current one
+type RoundOutput<F, MerkleConfig> = (
+    WitnessExtended<F, MerkleConfig>,
+    RoundProof<F, MerkleConfig>,
+);
+
 pub struct StirProver<F, MerkleConfig, FSConfig>
 where
     F: FftField,
@@ -84,53 +96,190 @@ where
         )
         .unwrap();
 
+        let witness_polynomial = match self.masking_polynomial() {
+            Some(masking_polynomial) => {
+                assert!(witness_polynomial.degree() < self.parameters.witness_degree());
+                witness_polynomial + masking_polynomial
+            }
+            None => witness_polynomial,
+        };
+
         let evals = witness_polynomial
             .evaluate_over_domain_by_ref(domain.backing_domain)
             .evals;
-        let folded_evals = utils::stack_evaluations(evals, self.parameters.folding_factor);
 
-        let merkle_tree = MerkleTree::<MerkleConfig>::new(
-            &self.parameters.leaf_hash_params,
-            &self.parameters.two_to_one_params,
-            &folded_evals,
+        self.commit_to_evaluations(domain, evals, Some(witness_polynomial))
+    }
+
+    fn commit_evaluations(
+        &self,
+        evals: Vec<F>,
+    ) -> (Commitment<MerkleConfig>, Witness<F, MerkleConfig>) {
+        let domain = Domain::<F>::new(
+            self.parameters.starting_degree,
+            self.parameters.starting_rate,
         )
         .unwrap();
+        assert_eq!(evals.len(), domain.size());
+
+        let evals = match self.masking_polynomial() {
+            Some(masking_polynomial) => masking_polynomial
+                .evaluate_over_domain_by_ref(domain.backing_domain)
+                .evals
+                .into_iter()
+                .zip(evals)
+                .map(|(m, e)| m + e)
+                .collect(),
+            None => evals,
+        };
 
-        let initial_commitment = merkle_tree.root();
-
-        (
-            Commitment {
-                root: initial_commitment,
-            },
-            Witness {
-                domain,
-                polynomial: witness_polynomial,
-                merkle_tree,
-                folded_evals,
-            },
-        )
+        self.commit_to_evaluations(domain, evals, None)
     }
 
-    fn prove(&self, witness: Witness<F, MerkleConfig>) -> Proof<F, MerkleConfig> {
-        assert!(witness.polynomial.degree() < self.parameters.starting_degree);
+    fn try_prove(
+        &self,
+        mut witness: Witness<F, MerkleConfig>,
+    ) -> Result<Proof<F, MerkleConfig>, ProverError> {
+        let polynomial = self.decode(&mut witness)?;
 
-        let mut sponge = FSConfig::new(&self.parameters.fiat_shamir_config);
+        let mut transcript = Transcript::<FSConfig>::new(
+            &self.parameters.fiat_shamir_config,
+            self.parameters.io_pattern(),
+        );
         // TODO: Add parameters to FS
-        sponge.absorb(&witness.merkle_tree.root());
-        let folding_randomness = sponge.squeeze_field_elements(1)[0];
+        transcript.append_commitment(&witness.merkle_tree.root())?;
+
+        self.prove_rounds(transcript, witness, polynomial)
+    }
+}
+
+impl<F, MerkleConfig, FSConfig> StirProver<F, MerkleConfig, FSConfig>
+where
+    F: FftField + PrimeField + Absorb,
+    MerkleConfig: Config<Leaf = Vec<F>>,
+    MerkleConfig::InnerDigest: Absorb,
+    FSConfig: CryptographicSponge,
+    FSConfig::Config: Clone,
+{
+    pub fn new(parameters: Parameters<F, MerkleConfig, FSConfig>) -> Self {
+        Self {
+            parameters: parameters.into(),
+        }
+    }
+
+    pub fn prove_aggregate(
+        &self,
+        witnesses: Vec<Witness<F, MerkleConfig>>,
+    ) -> AggregateProof<F, MerkleConfig> {
+        self.try_prove_aggregate(witnesses).unwrap()
+    }
+
+    // Proves that all the committed functions are close to low degree polynomials with a single
+    // proof. After absorbing every commitment, the prover proves a random linear combination of
+    // the functions, and answers the queries to it by opening all the commitments.
+    pub fn try_prove_aggregate(
+        &self,
+        mut witnesses: Vec<Witness<F, MerkleConfig>>,
+    ) -> Result<AggregateProof<F, MerkleConfig>, ProverError> {
+        assert!(!witnesses.is_empty());
+        let polynomials = witnesses
+            .iter_mut()
+            .map(|witness| self.decode(witness))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut transcript = Transcript::<FSConfig>::new(
+            &self.parameters.fiat_shamir_config,
+            self.parameters.aggregate_io_pattern(witnesses.len()),
+        );
+        for witness in &witnesses {
+            transcript.append_commitment(&witness.merkle_tree.root())?;
+        }
+        let combination_randomness = transcript.challenge_scalar()?;
+
+        let polynomial = DensePolynomial::from_coefficients_vec(utils::linear_combination(
+            polynomials
+                .iter()
+                .map(|polynomial| polynomial.coeffs.as_slice()),
+            combination_randomness,
+        ));
+
+        // The proof of the combination opens the first commitment
+        let mut witnesses = witnesses.into_iter();
+        let first = witnesses.next().unwrap();
+        let proof = self.prove_rounds(transcript, first, polynomial)?;
+
+        // And the others are opened at the same leaves
+        let leaf_indexes = &proof.queries_to_initial().1.leaf_indexes;
+        let queries_to_commitments = witnesses
+            .map(|witness| {
+                let answers = leaf_indexes
+                    .iter()
+                    .map(|&index| witness.folded_evals[index].clone())
+                    .collect();
+                let path = witness
+                    .merkle_tree
+                    .generate_multi_proof(leaf_indexes.clone())
+                    .unwrap();
+                (answers, path)
+            })
+            .collect();
+
+        Ok(AggregateProof {
+            proof,
+            queries_to_commitments,
+        })
+    }
+
+    // The polynomial to prove for a witness. If we committed to evaluations (or to a polynomial of
+    // too large degree), we find the closest codeword to what we committed to. This is the only
+    // point where we interpolate.
+    fn decode(
+        &self,
+        witness: &mut Witness<F, MerkleConfig>,
+    ) -> Result<DensePolynomial<F>, ProverError> {
+        match witness.polynomial.take() {
+            Some(polynomial) if polynomial.degree() < self.parameters.degree_bound => {
+                Ok(polynomial)
+            }
+            _ => poly_utils::decoding::decode(
+                utils::unstack_evaluations(&self.unsalted(&witness.folded_evals)),
+                witness.domain.backing_domain,
+                self.parameters.degree_bound,
+            )
+            .ok_or(ProverError::NotDecodable),
+        }
+    }
+
+    // Runs the rounds and the final queries for the polynomial, whose evaluations the witness
+    // commits to, from a transcript that absorbed the commitment
+    fn prove_rounds(
+        &self,
+        mut transcript: Transcript<FSConfig>,
+        witness: Witness<F, MerkleConfig>,
+        polynomial: DensePolynomial<F>,
+    ) -> Result<Proof<F, MerkleConfig>, ProverError> {
+        // If the degree bound is not a power of two, we prove that the degree corrected polynomial
+        // has degree < starting_degree. The verifier corrects the answers of the commitment.
+        let polynomial = match self.parameters.degree_correction() {
+            Some(degree) => {
+                let correction_randomness = transcript.challenge_scalar()?;
+                &polynomial * &scaling_polynomial(correction_randomness, degree)
+            }
+            None => polynomial,
+        };
+        let folding_randomness = transcript.challenge_scalar()?;
 
         let mut witness = WitnessExtended {
             domain: witness.domain,
-            polynomial: witness.polynomial,
+            polynomial,
             merkle_tree: witness.merkle_tree,
             folded_evals: witness.folded_evals,
             num_round: 0,
             folding_randomness,
         };
-
         let mut round_proofs = vec![];
-        for _ in 0..self.parameters.num_rounds {
-            let (new_witness, round_proof) = self.round(&mut sponge, &witness);
+        for _ in 0..self.parameters.committed_rounds() {
+            let (new_witness, round_proof) = self.round(&mut transcript, &witness)?;
             witness = new_witness;
             round_proofs.push(round_proof);
         }
@@ -141,61 +290,126 @@ where
             witness.folding_randomness,
         );
 
+        if self.parameters.final_phase == FinalPhase::SkipLastCommit {
+            round_proofs.push(self.uncommitted_round(
+                &mut transcript,
+                &witness,
+                final_polynomial,
+            )?);
+            transcript.finish()?;
+            return Ok(Proof {
+                round_proofs,
+                final_polynomial: vec![],
+                queries_to_final: (vec![], MultiPath::default()),
+                pow_nonce: None,
+            });
+        }
+
         let final_repetitions = self.parameters.repetitions[self.parameters.num_rounds];
         let scaling_factor = witness.domain.size() / self.parameters.folding_factor;
-        let final_randomness_indexes = utils::dedup(
-            (0..final_repetitions).map(|_| utils::squeeze_integer(&mut sponge, scaling_factor)),
-        );
-
-        let queries_to_final_ans: Vec<_> = final_randomness_indexes
-            .iter()
-            .map(|index| witness.folded_evals[*index].clone())
-            .collect();
-
-        let queries_to_final_proof = witness
-            .merkle_tree
-            .generate_multi_proof(final_randomness_indexes)
-            .unwrap();
+        let (final_polynomial, final_randomness_indexes) = match self.parameters.final_phase {
+            FinalPhase::Coefficients => (
+                final_polynomial.coeffs,
+                transcript.challenge_indices(final_repetitions, scaling_factor)?,
+            ),
+            FinalPhase::Evaluations => (
+                final_polynomial
+                    .evaluate_over_domain(self.parameters.final_domain())
+                    .evals,
+                transcript.challenge_indices(final_repetitions, scaling_factor)?,
+            ),
+            // The verifier reads the whole last oracle
+            FinalPhase::Direct => (vec![], (0..scaling_factor).collect()),
+            FinalPhase::SkipLastCommit => unreachable!(),
+        };
 
-        let queries_to_final = (queries_to_final_ans, queries_to_final_proof);
+        let queries_to_final = Self::open(&witness, final_randomness_indexes);
 
-        let pow_nonce = utils::proof_of_work(
-            &mut sponge,
-            self.parameters.pow_bits[self.parameters.num_rounds],
-        );
+        let pow_nonce = match self.parameters.final_phase {
+            FinalPhase::Coefficients | FinalPhase::Evaluations => {
+                transcript.grind(self.parameters.pow_bits[self.parameters.num_rounds])?
+            }
+            _ => None,
+        };
+        transcript.finish()?;
 
-        Proof {
+        Ok(Proof {
             round_proofs,
             final_polynomial,
             queries_to_final,
             pow_nonce,
-        }
+        })
     }
-}
 
-impl<F, MerkleConfig, FSConfig> StirProver<F, MerkleConfig, FSConfig>
-where
-    F: FftField + PrimeField + Absorb,
-    MerkleConfig: Config<Leaf = Vec<F>>,
-    MerkleConfig::InnerDigest: Absorb,
-    FSConfig: CryptographicSponge,
-    FSConfig::Config: Clone,
-{
-    pub fn new(parameters: Parameters<F, MerkleConfig, FSConfig>) -> Self {
-        Self {
-            parameters: parameters.into(),
+    // In zero-knowledge mode, a random polynomial X^(witness_degree) * r(X) with
+    // deg(r) < masking_degree, that is added to the witness
+    fn masking_polynomial(&self) -> Option<DensePolynomial<F>> {
+        if self.parameters.masking_degree == 0 {
+            return None;
         }
+
+        let mut rng = rand::thread_rng();
+        let r = DensePolynomial::rand(self.parameters.masking_degree - 1, &mut rng);
+        Some(poly_utils::scale_and_shift(
+            &r,
+            F::ONE,
+            self.parameters.witness_degree(),
+        ))
+    }
+
+    fn stack_and_salt(&self, evals: Vec<F>) -> Vec<Vec<F>> {
+        let mut folded_evals = utils::stack_evaluations(evals, self.parameters.folding_factor);
+        merkle_tree::salt_leaves(
+            &mut folded_evals,
+            self.parameters.salt_size,
+            &mut rand::thread_rng(),
+        );
+        folded_evals
+    }
+
+    fn unsalted(&self, folded_evals: &[Vec<F>]) -> Vec<Vec<F>> {
+        folded_evals
+            .iter()
+            .map(|leaf| leaf[..self.parameters.folding_factor].to_vec())
+            .collect()
+    }
+
+    fn commit_to_evaluations(
+        &self,
+        domain: Domain<F>,
+        evals: Vec<F>,
+        polynomial: Option<DensePolynomial<F>>,
+    ) -> (Commitment<MerkleConfig>, Witness<F, MerkleConfig>) {
+        let folded_evals = self.stack_and_salt(evals);
+
+        let merkle_tree = MerkleTree::<MerkleConfig>::new(
+            &self.parameters.leaf_hash_params,
+            &self.parameters.two_to_one_params,
+            &folded_evals,
+        )
+        .unwrap();
+
+        let initial_commitment = merkle_tree.root();
+
+        (
+            Commitment {
+                root: initial_commitment,
+            },
+            Witness {
+                domain,
+                polynomial,
+                merkle_tree,
+                folded_evals,
+            },
+        )
     }
 
     // TODO: Rename to better name
     fn round(
         &self,
-        sponge: &mut impl CryptographicSponge,
+        transcript: &mut Transcript<FSConfig>,
         witness: &WitnessExtended<F, MerkleConfig>,
-    ) -> (
-        WitnessExtended<F, MerkleConfig>,
-        RoundProof<F, MerkleConfig>,
-    ) {
+    ) -> Result<RoundOutput<F, MerkleConfig>, TranscriptError> {
         let g_poly = poly_utils::folding::poly_fold(
             &witness.polynomial,
             self.parameters.folding_factor,
@@ -208,8 +422,7 @@ where
             .evaluate_over_domain_by_ref(g_domain.backing_domain)
             .evals;
 
-        let g_folded_evaluations =
-            utils::stack_evaluations(g_evaluations, self.parameters.folding_factor);
+        let g_folded_evaluations = self.stack_and_salt(g_evaluations);
         let g_merkle = MerkleTree::<MerkleConfig>::new(
             &self.parameters.leaf_hash_params,
             &self.parameters.two_to_one_params,
@@ -217,46 +430,36 @@ where
         )
         .unwrap();
         let g_root = g_merkle.root();
-        sponge.absorb(&g_root);
+        transcript.append_commitment(&g_root)?;
 
         // Out of domain sample
-        let ood_randomness = sponge.squeeze_field_elements(self.parameters.ood_samples);
+        let ood_randomness = transcript.challenge_scalars(self.parameters.ood_samples)?;
         let betas: Vec<F> = ood_randomness
             .iter()
             .map(|alpha| g_poly.evaluate(alpha))
             .collect();
-        sponge.absorb(&betas);
+        transcript.append_field_elements(&betas)?;
 
         // Proximity generator
-        let comb_randomness: F = sponge.squeeze_field_elements(1)[0];
+        let comb_randomness: F = transcript.challenge_scalar()?;
 
         // Folding randomness for next round
-        let folding_randomness = sponge.squeeze_field_elements(1)[0];
+        let folding_randomness = transcript.challenge_scalar()?;
 
         // Sample the indexes of L^k that we are going to use for querying the previous Merkle tree
         let scaling_factor = witness.domain.size() / self.parameters.folding_factor;
         let num_repetitions = self.parameters.repetitions[witness.num_round];
-        let stir_randomness_indexes = utils::dedup(
-            (0..num_repetitions).map(|_| utils::squeeze_integer(sponge, scaling_factor)),
-        );
+        let stir_randomness_indexes =
+            transcript.challenge_indices(num_repetitions, scaling_factor)?;
 
-        let pow_nonce = utils::proof_of_work(sponge, self.parameters.pow_bits[witness.num_round]);
+        let pow_nonce = transcript.grind(self.parameters.pow_bits[witness.num_round])?;
 
         // Not used
-        let _shake_randomness: F = sponge.squeeze_field_elements(1)[0];
+        let _shake_randomness: F = transcript.challenge_scalar()?;
 
         // The verifier queries the previous oracle at the indexes of L^k (reading the
         // corresponding evals)
-        let queries_to_prev_ans: Vec<_> = stir_randomness_indexes
-            .iter()
-            .map(|&index| witness.folded_evals[index].clone())
-            .collect();
-
-        let queries_to_prev_proof = witness
-            .merkle_tree
-            .generate_multi_proof(stir_randomness_indexes.clone())
-            .unwrap();
-        let queries_to_prev = (queries_to_prev_ans, queries_to_prev_proof);
+        let queries_to_prev = Self::open(witness, stir_randomness_indexes.clone());
 
         // Here, we update the witness
         // First, compute the set of points we are actually going to query at
@@ -270,7 +473,6 @@ where
             })
             .collect();
 
-
         let beta_answers = betas
             .iter()
             .zip(ood_randomness.iter())
@@ -304,16 +506,10 @@ where
         let numerator = &g_poly + &ans_polynomial;
         let quotient_polynomial = &numerator / &vanishing_poly;
 
-        // This is the polynomial 1 + r * x + r^2 * x^2 + ... + r^n * x^n where n = |quotient_set|
-        let scaling_polynomial = DensePolynomial::from_coefficients_vec(
-            (0..quotient_set.len() + 1)
-                .map(|i| comb_randomness.pow([i as u64]))
-                .collect(),
-        );
-
-        let witness_polynomial = &quotient_polynomial * &scaling_polynomial;
+        let witness_polynomial =
+            &quotient_polynomial * &scaling_polynomial(comb_randomness, quotient_set.len());
 
-        (
+        Ok((
             WitnessExtended {
                 domain: g_domain,
                 polynomial: witness_polynomial,
@@ -330,15 +526,72 @@ where
                 shake_polynomial,
                 pow_nonce,
             },
-        )
+        ))
+    }
+
+    // The last round in FinalPhase::SkipLastCommit: instead of committing to the next oracle, the
+    // prover sends the folding of the current one as the ans polynomial, which the verifier checks
+    // at the queries to the current oracle.
+    fn uncommitted_round(
+        &self,
+        transcript: &mut Transcript<FSConfig>,
+        witness: &WitnessExtended<F, MerkleConfig>,
+        g_poly: DensePolynomial<F>,
+    ) -> Result<RoundProof<F, MerkleConfig>, TranscriptError> {
+        // Padded, so that the transcript absorbs the same number of elements for any folding
+        let mut coefficients = g_poly.coeffs.clone();
+        coefficients.resize(self.parameters.degrees[self.parameters.num_rounds], F::ZERO);
+        transcript.append_field_elements(&coefficients)?;
+
+        let scaling_factor = witness.domain.size() / self.parameters.folding_factor;
+        let num_repetitions = self.parameters.repetitions[witness.num_round];
+        let indexes = transcript.challenge_indices(num_repetitions, scaling_factor)?;
+        let pow_nonce = transcript.grind(self.parameters.pow_bits[witness.num_round])?;
+
+        Ok(RoundProof {
+            g_root: Default::default(),
+            betas: vec![],
+            ans_polynomial: g_poly,
+            queries_to_prev: Self::open(witness, indexes),
+            shake_polynomial: DensePolynomial::from_coefficients_vec(vec![]),
+            pow_nonce,
+        })
     }
+
+    // The leaves of the oracle of the witness at the indexes, and their authentication paths
+    fn open(
+        witness: &WitnessExtended<F, MerkleConfig>,
+        indexes: Vec<usize>,
+    ) -> (Vec<Vec<F>>, MultiPath<MerkleConfig>) {
+        let answers = indexes
+            .iter()
+            .map(|&index| witness.folded_evals[index].clone())
+            .collect();
+        let proof = witness.merkle_tree.generate_multi_proof(indexes).unwrap();
+        (answers, proof)
+    }
+}
+
+// This is the polynomial 1 + r * x + r^2 * x^2 + ... + r^n * x^n
+fn scaling_polynomial<F: FftField>(randomness: F, degree: usize) -> DensePolynomial<F> {
+    DensePolynomial::from_coefficients_vec(
+        (0..degree + 1)
+            .map(|i| randomness.pow([i as u64]))
+            .collect(),
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::crypto::fields::Field64 as TestField;
-    use ark_ff::Field;
+    use crate::{
+        crypto::{fields::Field64 as TestField, fs, merkle_tree::blake3 as mt},
+        ldt::Verifier,
+        parameters::SoundnessType,
+        stir::verifier::StirVerifier,
+    };
+    use ark_ff::{Field, UniformRand};
+    use ark_serialize::CanonicalSerialize;
 
     fn fold_evaluations<F: FftField>(folding: usize, evals: Vec<F>) -> Vec<Vec<F>> {
         let size_of_new_domain = evals.len() / folding;
@@ -374,4 +627,155 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_commit_evaluations() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = mt::default_config::<TestField>(&mut rng, 4);
+        let parameters: Parameters<TestField, mt::MerkleTreeParams<TestField>, fs::blake3::Sponge> =
+            Parameters {
+                security_level: 32,
+                protocol_security_level: 32,
+                starting_degree: 64,
+                stopping_degree: 4,
+                folding_factor: 4,
+                starting_rate: 2,
+                soundness_type: SoundnessType::Conjecture,
+                leaf_hash_params,
+                two_to_one_params,
+                fiat_shamir_config: fs::blake3::default_fs_config(),
+                _field: Default::default(),
+            };
+        let prover = StirProver::new(parameters.clone());
+        let verifier = StirVerifier::new(parameters);
+
+        let polynomial = DensePolynomial::rand(63, &mut rng);
+        let domain = Domain::<TestField>::new(64, 2).unwrap();
+        let evals = polynomial
+            .evaluate_over_domain_by_ref(domain.backing_domain)
+            .evals;
+
+        let (commitment, witness) = prover.commit(polynomial);
+        let (evals_commitment, evals_witness) = prover.commit_evaluations(evals);
+        assert_eq!(commitment.root, evals_commitment.root);
+
+        let proof = prover.prove(evals_witness);
+        assert!(verifier.verify(&evals_commitment, &proof));
+
+        let mut proof_bytes = vec![];
+        let mut evals_proof_bytes = vec![];
+        prover
+            .prove(witness)
+            .serialize_compressed(&mut proof_bytes)
+            .unwrap();
+        proof.serialize_compressed(&mut evals_proof_bytes).unwrap();
+        assert_eq!(proof_bytes, evals_proof_bytes);
+    }
+
+    #[test]
+    fn test_zero_knowledge() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = mt::default_config::<TestField>(&mut rng, 4);
+        let parameters: Parameters<TestField, mt::MerkleTreeParams<TestField>, fs::blake3::Sponge> =
+            Parameters {
+                security_level: 16,
+                protocol_security_level: 16,
+                starting_degree: 1 << 10,
+                stopping_degree: 4,
+                folding_factor: 4,
+                starting_rate: 2,
+                soundness_type: SoundnessType::Conjecture,
+                leaf_hash_params,
+                two_to_one_params,
+                fiat_shamir_config: fs::blake3::default_fs_config(),
+                _field: Default::default(),
+            };
+        let full_parameters = FullParameters::zero_knowledge(parameters);
+        assert!(full_parameters.masking_degree > 0);
+        assert!(full_parameters.salt_size > 0);
+
+        let prover = StirProver::new_full(full_parameters.clone());
+        let verifier = StirVerifier::new_full(full_parameters.clone());
+
+        let polynomial = DensePolynomial::rand(full_parameters.witness_degree() - 1, &mut rng);
+        let (commitment, witness) = prover.commit(polynomial.clone());
+        let (other_commitment, other_witness) = prover.commit(polynomial);
+        assert_ne!(commitment.root, other_commitment.root);
+
+        assert!(verifier.verify(&commitment, &prover.prove(witness)));
+        assert!(verifier.verify(&other_commitment, &prover.prove(other_witness)));
+    }
+
+    #[test]
+    fn test_degree_correction() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = mt::default_config::<TestField>(&mut rng, 4);
+        let parameters: Parameters<TestField, mt::MerkleTreeParams<TestField>, fs::blake3::Sponge> =
+            Parameters {
+                security_level: 32,
+                protocol_security_level: 32,
+                starting_degree: 3 << 6,
+                stopping_degree: 4,
+                folding_factor: 4,
+                starting_rate: 2,
+                soundness_type: SoundnessType::Conjecture,
+                leaf_hash_params,
+                two_to_one_params,
+                fiat_shamir_config: fs::blake3::default_fs_config(),
+                _field: Default::default(),
+            };
+        let full_parameters = FullParameters::from(parameters);
+        assert_eq!(full_parameters.starting_degree, 1 << 8);
+        assert_eq!(full_parameters.degree_correction(), Some(1 << 6));
+
+        let prover = StirProver::new_full(full_parameters.clone());
+        let verifier = StirVerifier::new_full(full_parameters);
+
+        let polynomial = DensePolynomial::rand((3 << 6) - 1, &mut rng);
+        let domain = Domain::<TestField>::new(1 << 8, 2).unwrap();
+        let evals = polynomial
+            .evaluate_over_domain_by_ref(domain.backing_domain)
+            .evals;
+        let (commitment, witness) = prover.commit(polynomial);
+        assert!(verifier.verify(&commitment, &prover.prove(witness)));
+        let (commitment, witness) = prover.commit_evaluations(evals);
+        assert!(verifier.verify(&commitment, &prover.prove(witness)));
+
+        // Below the next power of two, but above the degree bound
+        let (_, witness) = prover.commit(DensePolynomial::rand(3 << 6, &mut rng));
+        assert_eq!(
+            prover.try_prove(witness).err(),
+            Some(ProverError::NotDecodable)
+        );
+    }
+
+    // A function far from the code cannot be hidden in the combination
+    #[test]
+    fn test_try_prove_aggregate() {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = mt::default_config::<TestField>(&mut rng, 4);
+        let parameters: Parameters<TestField, mt::MerkleTreeParams<TestField>, fs::blake3::Sponge> =
+            Parameters {
+                security_level: 32,
+                protocol_security_level: 32,
+                starting_degree: 64,
+                stopping_degree: 4,
+                folding_factor: 4,
+                starting_rate: 2,
+                soundness_type: SoundnessType::Conjecture,
+                leaf_hash_params,
+                two_to_one_params,
+                fiat_shamir_config: fs::blake3::default_fs_config(),
+                _field: Default::default(),
+            };
+        let prover = StirProver::new(parameters);
+
+        let (_, close) = prover.commit(DensePolynomial::rand(63, &mut rng));
+        let evals = (0..64 << 2).map(|_| TestField::rand(&mut rng)).collect();
+        let (_, far) = prover.commit_evaluations(evals);
+        assert_eq!(
+            prover.try_prove_aggregate(vec![close, far]).err(),
+            Some(ProverError::NotDecodable)
+        );
+    }
 }
@@ -1,15 +1,34 @@
+use std::borrow::Cow;
+
 use ark_crypto_primitives::{
-    merkle_tree::Config,
+    merkle_tree::{Config, LeafParam, MultiPath, TwoToOneParam},
     sponge::{Absorb, CryptographicSponge},
 };
-use ark_ff::{batch_inversion, FftField, PrimeField};
-use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+use ark_ff::{FftField, Field, PrimeField};
+use ark_poly::{
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Evaluations,
+    Radix2EvaluationDomain,
+};
 
+use ark_std::cfg_iter;
 use itertools::izip;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-use crate::{domain::Domain, ldt::Verifier, parameters::Parameters, poly_utils, utils};
+use crate::{
+    domain::Domain,
+    ldt::{batch_result, Verifier, VerifierCostReport, VerifierError},
+    metrics,
+    parameters::Parameters,
+    poly_utils,
+    transcript::Transcript,
+    utils,
+};
 
-use super::{common::*, parameters::FullParameters};
+use super::{
+    common::*,
+    parameters::{FinalPhase, FullParameters},
+};
 
 #[derive(Debug)]
 pub struct VirtualFunction<F: FftField> {
@@ -18,12 +37,32 @@ pub struct VirtualFunction<F: FftField> {
     quotient_set: Vec<F>,
 }
 
+// The committed function is multiplied by 1 + r * x + ... + r^degree * x^degree, to correct its
+// degree bound to a power of two
+#[derive(Debug, Clone, Copy)]
+pub struct DegreeCorrection<F: FftField> {
+    randomness: F,
+    degree: usize,
+}
+
 #[derive(Debug)]
 pub enum OracleType<F: FftField> {
-    Initial,
+    Initial(Option<DegreeCorrection<F>>),
     Virtual(VirtualFunction<F>),
 }
 
+// 1 + c + c^2 + ... + c^n, given the inverse of 1 - c
+fn geometric_sum<F: FftField>(common_factor: F, n: usize, common_factor_inverse: F) -> F {
+    if common_factor != F::ONE {
+        metrics::mul(
+            F::ONE - metrics::pow(common_factor, (n + 1) as u64),
+            common_factor_inverse,
+        )
+    } else {
+        F::from((n + 1) as u64)
+    }
+}
+
 impl<F: FftField> VerificationState<F> {
     // Now, I need to query f_i at a given point.
     // This induces some query to the previous oracle, whose answer I get
@@ -36,7 +75,14 @@ impl<F: FftField> VerificationState<F> {
         ans_eval: F,
     ) -> F {
         match &self.oracle {
-            OracleType::Initial => value_of_prev_oracle, // In case this is the initial function, we just return the value of the previous oracle
+            OracleType::Initial(None) => value_of_prev_oracle, // In case this is the initial function, we just return the value of the previous oracle
+            OracleType::Initial(Some(correction)) => {
+                let common_factor = metrics::mul(evaluation_point, correction.randomness);
+                let scale_factor =
+                    geometric_sum(common_factor, correction.degree, common_factors_inverse);
+
+                metrics::mul(value_of_prev_oracle, scale_factor)
+            }
             OracleType::Virtual(virtual_function) => {
                 let num_terms = virtual_function.quotient_set.len();
                 let quotient_evaluation = poly_utils::quotient::quotient_with_hint(
@@ -47,15 +93,11 @@ impl<F: FftField> VerificationState<F> {
                     ans_eval,
                 );
 
-                let common_factor = evaluation_point * virtual_function.comb_randomness;
-
-                let scale_factor = if common_factor != F::ONE {
-                    (F::ONE - common_factor.pow([(num_terms + 1) as u64])) * common_factors_inverse
-                } else {
-                    F::from((num_terms + 1) as u64)
-                };
+                let common_factor =
+                    metrics::mul(evaluation_point, virtual_function.comb_randomness);
+                let scale_factor = geometric_sum(common_factor, num_terms, common_factors_inverse);
 
-                quotient_evaluation * scale_factor
+                metrics::mul(quotient_evaluation, scale_factor)
             }
         }
     }
@@ -72,6 +114,187 @@ pub struct VerificationState<F: FftField> {
     num_round: usize,
 }
 
+// The powers of a base with exponents below 2^bits, split on the low and high bits of the
+// exponent so that each power is a single multiplication
+#[derive(Debug, Clone)]
+struct PowerTable<F: Field> {
+    low_bits: u32,
+    low: Vec<F>,
+    high: Vec<F>,
+}
+
+impl<F: Field> PowerTable<F> {
+    fn new(base: F, bits: u32) -> Self {
+        let low_bits = bits.div_ceil(2);
+        let powers = |base: F, bits: u32| -> Vec<F> {
+            std::iter::successors(Some(F::ONE), |&prev| Some(metrics::mul(prev, base)))
+                .take(1 << bits)
+                .collect()
+        };
+        let low = powers(base, low_bits);
+        let high = powers(metrics::pow(base, 1 << low_bits), bits - low_bits);
+
+        Self {
+            low_bits,
+            low,
+            high,
+        }
+    }
+
+    fn pow(&self, exponent: usize) -> F {
+        metrics::mul(
+            self.low[exponent & ((1 << self.low_bits) - 1)],
+            self.high[exponent >> self.low_bits],
+        )
+    }
+}
+
+// The evaluation domain that a round queries, and the values derived from it that fold its
+// cosets. They depend only on the parameters: StirVerifier derives them in each round, and
+// PreparedStirVerifier once for all verifications.
+#[derive(Debug, Clone)]
+struct RoundDomain<F: FftField> {
+    domain_gen: F,
+    domain_size: usize,
+    domain_offset: F,
+    // The generator of the cosets of size folding_factor, its inverse and its powers
+    generator: F,
+    generator_inv: F,
+    scales: Vec<F>,
+    size_inv: F,
+    // The offset of the folded domain, domain_offset^folding_factor
+    scaled_offset: F,
+    // The powers of domain_gen, if precomputed
+    powers: Option<PowerTable<F>>,
+}
+
+impl<F: FftField> RoundDomain<F> {
+    fn new(domain_gen: F, domain_size: usize, domain_offset: F, folding_factor: usize) -> Self {
+        let generator = metrics::pow(domain_gen, (domain_size / folding_factor) as u64);
+        let mut inverses = [generator, F::from(folding_factor as u64)];
+        metrics::batch_inversion(&mut inverses);
+        let [generator_inv, size_inv] = inverses;
+
+        let scales =
+            std::iter::successors(Some(F::ONE), |&prev| Some(metrics::mul(prev, generator)))
+                .take(folding_factor)
+                .collect();
+
+        Self {
+            domain_gen,
+            domain_size,
+            domain_offset,
+            generator,
+            generator_inv,
+            scales,
+            size_inv,
+            scaled_offset: metrics::pow(domain_offset, folding_factor as u64),
+            powers: None,
+        }
+    }
+
+    fn with_powers(self) -> Self {
+        Self {
+            powers: Some(PowerTable::new(self.domain_gen, self.domain_size.ilog2())),
+            ..self
+        }
+    }
+
+    // domain_gen^exponent, for an exponent below the size of the domain
+    fn gen_pow(&self, exponent: usize) -> F {
+        match &self.powers {
+            Some(powers) => powers.pow(exponent),
+            None => metrics::pow(self.domain_gen, exponent as u64),
+        }
+    }
+}
+
+// The generator, size and offset of the domain of the next round, which is half the size of the
+// current one, and shifted off the folded domain
+fn next_domain<F: FftField>(
+    domain_gen: F,
+    domain_size: usize,
+    domain_offset: F,
+    root_of_unity: F,
+) -> (F, usize, F) {
+    (
+        metrics::mul(domain_gen, domain_gen),
+        domain_size / 2,
+        metrics::mul(metrics::mul(domain_offset, domain_offset), root_of_unity),
+    )
+}
+
+// The points at which a round queries the folding of the previous oracle, the cosets of the
+// previous domain that fold to them, and the elements that folding needs inverted. Elements that
+// the round needs inverted besides are appended to to_invert.
+struct Folding<F: FftField> {
+    coset_offsets: Vec<F>,
+    query_sets: Vec<Vec<F>>,
+    points: Vec<F>,
+    to_invert: Vec<F>,
+}
+
+// The challenges of a round whose transcript was replayed, waiting for its inversions
+struct OpenedRound<F: FftField> {
+    ood_randomness: Vec<F>,
+    comb_randomness: F,
+    folding_randomness: F,
+    shake_randomness: F,
+    folding: Folding<F>,
+}
+
+// Checks the Merkle paths of every round, and of the queries to the final oracle. They do not
+// depend on the transcript, so the paths of many proofs can be checked in parallel. The shape of
+// the proof must have been checked: only its last round may commit to no oracle, if it is not
+// among the committed rounds.
+fn verify_merkle_paths<F, MerkleConfig>(
+    leaf_hash_params: &LeafParam<MerkleConfig>,
+    two_to_one_params: &TwoToOneParam<MerkleConfig>,
+    commitment: &Commitment<MerkleConfig>,
+    proof: &Proof<F, MerkleConfig>,
+    committed_rounds: usize,
+    report: &mut VerifierCostReport,
+) -> bool
+where
+    F: FftField,
+    MerkleConfig: Config<Leaf = Vec<F>>,
+{
+    let mut current_root = commitment.root.clone();
+    for (num_round, round_proof) in proof.round_proofs.iter().enumerate() {
+        if !report.scope(num_round, || {
+            round_proof
+                .queries_to_prev
+                .1
+                .verify(
+                    leaf_hash_params,
+                    two_to_one_params,
+                    &current_root,
+                    round_proof.queries_to_prev.0.clone(),
+                )
+                .unwrap()
+        }) {
+            return false;
+        }
+        // In FinalPhase::SkipLastCommit, there is no oracle left to query
+        if num_round == committed_rounds {
+            return true;
+        }
+        current_root = round_proof.g_root.clone();
+    }
+    report.scope(proof.round_proofs.len(), || {
+        proof
+            .queries_to_final
+            .1
+            .verify(
+                leaf_hash_params,
+                two_to_one_params,
+                &current_root,
+                proof.queries_to_final.0.clone(),
+            )
+            .unwrap()
+    })
+}
+
 pub struct StirVerifier<F, MerkleConfig, FSConfig>
 where
     F: FftField,
@@ -88,8 +311,8 @@ where
     F: FftField + PrimeField + Absorb,
     MerkleConfig: Config<Leaf = Vec<F>>,
     MerkleConfig::InnerDigest: Absorb,
-    FSConfig: CryptographicSponge,
-    FSConfig::Config: Clone,
+    FSConfig: CryptographicSponge + Send,
+    FSConfig::Config: Clone + Send + Sync,
 {
     type FullParameter = FullParameters<F, MerkleConfig, FSConfig>;
     type Commitment = Commitment<MerkleConfig>;
@@ -107,182 +330,589 @@ where
         }
     }
 
-    fn verify(
+    fn try_verify_with_cost_report(
         &self,
         commitment: &Commitment<MerkleConfig>,
         proof: &Proof<F, MerkleConfig>,
-    ) -> bool {
-        if proof.final_polynomial.degree() + 1 > self.parameters.stopping_degree {
-            return false;
-        }
+    ) -> (Result<(), VerifierError>, VerifierCostReport) {
+        let mut report = VerifierCostReport::new(self.parameters.num_rounds + 1);
+        let (result, total) =
+            metrics::with_counter(|| self.verify_and_report(None, commitment, proof, &mut report));
+        report.total = total;
+        (result, report)
+    }
 
-        // First we verify all Merkle paths
-        let mut current_root = commitment.root.clone();
-        for round_proof in &proof.round_proofs {
-            if !round_proof
-                .queries_to_prev
-                .1
-                .verify(
-                    &self.parameters.leaf_hash_params,
-                    &self.parameters.two_to_one_params,
-                    &current_root,
-                    round_proof.queries_to_prev.0.clone(),
-                )
-                .unwrap()
-            {
-                return false;
-            }
-            current_root = round_proof.g_root.clone();
-        }
-        if !proof
-            .queries_to_final
-            .1
-            .verify(
-                &self.parameters.leaf_hash_params,
-                &self.parameters.two_to_one_params,
-                &current_root,
-                proof.queries_to_final.0.clone(),
-            )
-            .unwrap()
-        {
-            return false;
-        }
+    fn verify_batch(
+        &self,
+        instances: &[(Commitment<MerkleConfig>, Proof<F, MerkleConfig>)],
+    ) -> Result<(), Vec<usize>> {
+        self.verify_batch_prepared(&self.prepare_domains(), instances)
+    }
+}
 
-        // Now, we recompute
-        let mut sponge = FSConfig::new(&self.parameters.fiat_shamir_config);
-        sponge.absorb(&commitment.root);
-        let folding_randomness = sponge.squeeze_field_elements(1)[0];
+impl<F, MerkleConfig, FSConfig> StirVerifier<F, MerkleConfig, FSConfig>
+where
+    F: FftField + PrimeField + Absorb,
+    MerkleConfig: Config<Leaf = Vec<F>>,
+    MerkleConfig::InnerDigest: Absorb,
+    FSConfig: CryptographicSponge + Send,
+    FSConfig::Config: Clone + Send + Sync,
+{
+    // Precomputes the domains of every round, for a verifier that checks many proofs
+    pub fn prepare(self) -> PreparedStirVerifier<F, MerkleConfig, FSConfig> {
+        PreparedStirVerifier::new(self)
+    }
 
+    // The domains of every round, with the powers of their generators tabulated
+    fn prepare_domains(&self) -> Vec<RoundDomain<F>> {
         let domain = Domain::<F>::new(
             self.parameters.starting_degree,
             self.parameters.starting_rate,
         )
         .unwrap();
+        let root_of_unity = domain.element(1);
 
-        let domain_gen = domain.element(1);
-        let domain_size = domain.size();
+        // The verifier queries a domain in each round, and one more for the final polynomial
+        let mut domains = Vec::with_capacity(self.parameters.num_rounds + 1);
+        let (mut domain_gen, mut domain_size, mut domain_offset) =
+            (root_of_unity, domain.size(), F::ONE);
+        for _ in 0..=self.parameters.num_rounds {
+            domains.push(
+                RoundDomain::new(
+                    domain_gen,
+                    domain_size,
+                    domain_offset,
+                    self.parameters.folding_factor,
+                )
+                .with_powers(),
+            );
+            (domain_gen, domain_size, domain_offset) =
+                next_domain(domain_gen, domain_size, domain_offset, root_of_unity);
+        }
+        domains
+    }
 
-        let mut verification_state = VerificationState {
-            oracle: OracleType::Initial,
-            domain_gen,
-            domain_size,
-            domain_offset: F::ONE,
-            root_of_unity: domain_gen,
-            num_round: 0,
-            folding_randomness,
+    // The domain of the current round, either precomputed or derived from the verification state
+    fn round_domain<'a>(
+        &self,
+        verification_state: &VerificationState<F>,
+        prepared: Option<&'a [RoundDomain<F>]>,
+    ) -> Cow<'a, RoundDomain<F>> {
+        match prepared {
+            Some(domains) => Cow::Borrowed(&domains[verification_state.num_round]),
+            None => Cow::Owned(RoundDomain::new(
+                verification_state.domain_gen,
+                verification_state.domain_size,
+                verification_state.domain_offset,
+                self.parameters.folding_factor,
+            )),
+        }
+    }
+
+    fn verify_and_report(
+        &self,
+        prepared: Option<&[RoundDomain<F>]>,
+        commitment: &Commitment<MerkleConfig>,
+        proof: &Proof<F, MerkleConfig>,
+        report: &mut VerifierCostReport,
+    ) -> Result<(), VerifierError> {
+        if !self.check_shape(proof) {
+            return Err(VerifierError::Rejected);
+        }
+
+        // First we verify all Merkle paths
+        if !verify_merkle_paths(
+            &self.parameters.leaf_hash_params,
+            &self.parameters.two_to_one_params,
+            commitment,
+            proof,
+            self.parameters.committed_rounds(),
+            report,
+        ) {
+            return Err(VerifierError::Rejected);
+        }
+
+        let start = self.start_transcript(commitment);
+        self.verify_many(prepared, vec![start], &[proof], report)
+            .pop()
+            .unwrap()
+    }
+
+    // Verifies that every committed function is close to a low degree polynomial, with a proof for
+    // a random linear combination of them. The commitments must be in the order the prover took
+    // them in.
+    pub fn verify_aggregate(
+        &self,
+        commitments: &[Commitment<MerkleConfig>],
+        aggregate_proof: &AggregateProof<F, MerkleConfig>,
+    ) -> bool {
+        self.try_verify_aggregate(commitments, aggregate_proof)
+            .is_ok()
+    }
+
+    pub fn try_verify_aggregate(
+        &self,
+        commitments: &[Commitment<MerkleConfig>],
+        aggregate_proof: &AggregateProof<F, MerkleConfig>,
+    ) -> Result<(), VerifierError> {
+        let AggregateProof {
+            proof,
+            queries_to_commitments,
+        } = aggregate_proof;
+        let Some((first, others)) = commitments.split_first() else {
+            return Err(VerifierError::Rejected);
         };
+        if others.len() != queries_to_commitments.len() || !self.check_shape(proof) {
+            return Err(VerifierError::Rejected);
+        }
 
-        for round_proof in &proof.round_proofs {
-            let round_result = self.round(&mut sponge, round_proof, verification_state);
-            if round_result.is_none() {
-                return false;
+        // The proof opens the first commitment, and the others are opened at the same leaves
+        if !verify_merkle_paths(
+            &self.parameters.leaf_hash_params,
+            &self.parameters.two_to_one_params,
+            first,
+            proof,
+            self.parameters.committed_rounds(),
+            &mut VerifierCostReport::new(self.parameters.num_rounds + 1),
+        ) {
+            return Err(VerifierError::Rejected);
+        }
+        // The others are opened at the leaves of the first one. That these are the leaves the
+        // transcript samples is checked when verifying the combined proof below
+        let leaf_indexes = &proof.queries_to_initial().1.leaf_indexes;
+        for (commitment, queries) in others.iter().zip(queries_to_commitments) {
+            if !self.check_queries(queries, leaf_indexes)
+                || !queries
+                    .1
+                    .verify(
+                        &self.parameters.leaf_hash_params,
+                        &self.parameters.two_to_one_params,
+                        &commitment.root,
+                        queries.0.clone(),
+                    )
+                    .unwrap()
+            {
+                return Err(VerifierError::Rejected);
             }
-            verification_state = round_result.unwrap();
         }
 
-        // Now, we sample the last points that we want to check consisntency at
-        let final_repetitions = self.parameters.repetitions[self.parameters.num_rounds];
-        let scaling_factor = verification_state.domain_size / self.parameters.folding_factor;
-        let final_randomness_indexes = utils::dedup(
-            (0..final_repetitions).map(|_| utils::squeeze_integer(&mut sponge, scaling_factor)),
+        let (transcript, combination_randomness) = self.start_aggregate_transcript(commitments)?;
+
+        // The answers of the combination are the combinations of the answers. Answers of the
+        // first commitment beyond the opened leaves are left as they are, and rejected below.
+        let mut combined_proof = proof.clone();
+        let answers = &mut combined_proof.queries_to_initial_mut().0;
+        for (i, answer) in answers.iter_mut().enumerate().take(leaf_indexes.len()) {
+            *answer = utils::linear_combination(
+                std::iter::once(answer.as_slice()).chain(
+                    queries_to_commitments
+                        .iter()
+                        .map(|(answers, _)| answers[i].as_slice()),
+                ),
+                combination_randomness,
+            );
+        }
+
+        self.verify_many(
+            None,
+            vec![Ok(transcript)],
+            &[&combined_proof],
+            &mut VerifierCostReport::new(self.parameters.num_rounds + 1),
+        )
+        .pop()
+        .unwrap()
+    }
+
+    // Verifies many proofs, sharing the domains of every round and doing a single batch inversion
+    // per round for all of them. The Merkle paths of the proofs are checked in parallel.
+    fn verify_batch_prepared(
+        &self,
+        prepared: &[RoundDomain<F>],
+        instances: &[(Commitment<MerkleConfig>, Proof<F, MerkleConfig>)],
+    ) -> Result<(), Vec<usize>> {
+        let (num_rounds, committed_rounds) = (
+            self.parameters.num_rounds,
+            self.parameters.committed_rounds(),
         );
+        let (leaf_hash_params, two_to_one_params) = (
+            &self.parameters.leaf_hash_params,
+            &self.parameters.two_to_one_params,
+        );
+        let well_formed: Vec<_> = instances
+            .iter()
+            .map(|(_, proof)| self.check_shape(proof))
+            .collect();
+        let merkle_paths: Vec<bool> = cfg_iter!(instances)
+            .zip(&well_formed)
+            .map(|((commitment, proof), well_formed)| {
+                *well_formed
+                    && verify_merkle_paths(
+                        leaf_hash_params,
+                        two_to_one_params,
+                        commitment,
+                        proof,
+                        committed_rounds,
+                        &mut VerifierCostReport::new(num_rounds + 1),
+                    )
+            })
+            .collect();
 
-        if !utils::proof_of_work_verify(
-            &mut sponge,
-            self.parameters.pow_bits[self.parameters.num_rounds],
-            proof.pow_nonce,
-        ) {
-            return false;
+        let (indexes, candidates): (Vec<_>, Vec<_>) = instances
+            .iter()
+            .zip(merkle_paths)
+            .enumerate()
+            .filter(|(_, (_, merkle_paths))| *merkle_paths)
+            .map(|(i, ((commitment, proof), _))| (i, (commitment, proof)))
+            .unzip();
+        let starts = candidates
+            .iter()
+            .map(|(commitment, _)| self.start_transcript(commitment))
+            .collect();
+        let proofs: Vec<_> = candidates.into_iter().map(|(_, proof)| proof).collect();
+        let mut accepted = vec![false; instances.len()];
+        for (i, result) in indexes.into_iter().zip(self.verify_many(
+            Some(prepared),
+            starts,
+            &proofs,
+            &mut VerifierCostReport::new(num_rounds + 1),
+        )) {
+            accepted[i] = result.is_ok();
         }
+        batch_result(&accepted)
+    }
 
-        // First, we want to query back the last oracle at this point, which is, again, just a
-        // lookup
-        let oracle_answers = proof.queries_to_final.0.clone();
+    // Checks the parts of the proof whose sizes are fixed by the parameters
+    fn check_shape(&self, proof: &Proof<F, MerkleConfig>) -> bool {
+        let final_len = proof.final_polynomial.len();
+        let final_well_formed = match self.parameters.final_phase {
+            FinalPhase::Coefficients => final_len <= self.parameters.stopping_degree,
+            FinalPhase::Evaluations => final_len == self.parameters.stopping_degree,
+            FinalPhase::Direct => final_len == 0,
+            // Nothing is sent after the last round, which sends the folding of its oracle and no
+            // OOD or shake answers
+            FinalPhase::SkipLastCommit => {
+                let last_round = proof.round_proofs.last();
+                final_len == 0
+                    && proof.queries_to_final.0.is_empty()
+                    && proof.queries_to_final.1.leaf_indexes.is_empty()
+                    && proof.pow_nonce.is_none()
+                    && last_round.is_some_and(|round_proof| {
+                        round_proof.betas.is_empty()
+                            && round_proof.shake_polynomial.coeffs.is_empty()
+                            && round_proof.ans_polynomial.coeffs.len()
+                                <= self.parameters.degrees[self.parameters.num_rounds]
+                    })
+            }
+        };
+        // The answers of a committed round interpolate its OOD samples and its queries
+        let ood_samples = self.parameters.ood_samples;
+        let committed_rounds = self.parameters.committed_rounds();
+        final_well_formed
+            && proof.round_proofs.len() == self.parameters.num_rounds
+            && proof
+                .round_proofs
+                .iter()
+                .zip(&self.parameters.repetitions)
+                .enumerate()
+                .all(|(round, (round_proof, repetitions))| {
+                    if round >= committed_rounds {
+                        return round_proof.g_root == Default::default();
+                    }
+                    round_proof.betas.len() == ood_samples
+                        && round_proof.ans_polynomial.coeffs.len() <= ood_samples + repetitions
+                        && round_proof.shake_polynomial.coeffs.len() <= ood_samples + repetitions
+                })
+    }
 
-        let folded_answers = self.compute_folded_evaluations(
-            &verification_state,
-            final_randomness_indexes,
-            oracle_answers,
+    // The transcript after absorbing the commitment
+    fn start_transcript(
+        &self,
+        commitment: &Commitment<MerkleConfig>,
+    ) -> Result<Transcript<FSConfig>, VerifierError> {
+        let mut transcript = Transcript::<FSConfig>::new(
+            &self.parameters.fiat_shamir_config,
+            self.parameters.io_pattern(),
         );
+        transcript.append_commitment(&commitment.root)?;
+        Ok(transcript)
+    }
+
+    // The transcript after absorbing all the commitments, and the combination randomness it gives
+    fn start_aggregate_transcript(
+        &self,
+        commitments: &[Commitment<MerkleConfig>],
+    ) -> Result<(Transcript<FSConfig>, F), VerifierError> {
+        let mut transcript = Transcript::<FSConfig>::new(
+            &self.parameters.fiat_shamir_config,
+            self.parameters.aggregate_io_pattern(commitments.len()),
+        );
+        for commitment in commitments {
+            transcript.append_commitment(&commitment.root)?;
+        }
+        let combination_randomness = transcript.challenge_scalar()?;
+        Ok((transcript, combination_randomness))
+    }
+
+    // Replays the transcripts of proofs whose shapes and Merkle paths were already checked, from
+    // the point where the committed function was absorbed (or the error the transcript failed with).
+    // The proofs are verified in lock-step, so that each round inverts the elements of all of
+    // them at once.
+    fn verify_many(
+        &self,
+        prepared: Option<&[RoundDomain<F>]>,
+        starts: Vec<Result<Transcript<FSConfig>, VerifierError>>,
+        proofs: &[&Proof<F, MerkleConfig>],
+        report: &mut VerifierCostReport,
+    ) -> Vec<Result<(), VerifierError>> {
+        let (domain_gen, domain_size) = match prepared {
+            Some(domains) => (domains[0].domain_gen, domains[0].domain_size),
+            None => {
+                let domain = Domain::<F>::new(
+                    self.parameters.starting_degree,
+                    self.parameters.starting_rate,
+                )
+                .unwrap();
+                (domain.element(1), domain.size())
+            }
+        };
+
+        // Now, we recompute
+        let mut states: Vec<Result<_, VerifierError>> = starts
+            .into_iter()
+            .map(|transcript| {
+                let mut transcript = transcript?;
+                let degree_correction = match self.parameters.degree_correction() {
+                    Some(degree) => Some(DegreeCorrection {
+                        randomness: transcript.challenge_scalar()?,
+                        degree,
+                    }),
+                    None => None,
+                };
+                let folding_randomness = transcript.challenge_scalar()?;
+
+                let verification_state = VerificationState {
+                    oracle: OracleType::Initial(degree_correction),
+                    domain_gen,
+                    domain_size,
+                    domain_offset: F::ONE,
+                    root_of_unity: domain_gen,
+                    num_round: 0,
+                    folding_randomness,
+                };
+                Ok((transcript, verification_state))
+            })
+            .collect();
+
+        for num_round in 0..self.parameters.committed_rounds() {
+            states = report.scope(num_round, || {
+                let mut opened: Vec<Result<_, VerifierError>> = states
+                    .into_iter()
+                    .zip(proofs)
+                    .map(|(state, proof)| {
+                        let (mut transcript, verification_state) = state?;
+                        let domain = self.round_domain(&verification_state, prepared);
+                        let round = self.open_round(
+                            &mut transcript,
+                            &proof.round_proofs[num_round],
+                            &verification_state,
+                            &domain,
+                        )?;
+                        Ok((transcript, verification_state, domain, round))
+                    })
+                    .collect();
+
+                let mut inverses = metrics::batch_inversion_grouped(
+                    opened
+                        .iter_mut()
+                        .flatten()
+                        .map(|(_, _, _, round)| std::mem::take(&mut round.folding.to_invert)),
+                )
+                .into_iter();
+                opened
+                    .into_iter()
+                    .zip(proofs)
+                    .map(|(opened, proof)| {
+                        let (transcript, verification_state, domain, round) = opened?;
+                        let verification_state = self.close_round(
+                            &proof.round_proofs[num_round],
+                            verification_state,
+                            &domain,
+                            round,
+                            inverses.next().unwrap(),
+                        )?;
+                        Ok((transcript, verification_state))
+                    })
+                    .collect()
+            });
+        }
+
+        report.scope(self.parameters.committed_rounds(), || {
+            let mut opened: Vec<Result<_, VerifierError>> = states
+                .into_iter()
+                .zip(proofs)
+                .map(|(state, proof)| {
+                    let (transcript, verification_state) = state?;
+                    let domain = self.round_domain(&verification_state, prepared);
+                    let folding =
+                        self.open_final(transcript, proof, &verification_state, &domain)?;
+                    Ok((verification_state, domain, folding))
+                })
+                .collect();
+
+            let mut inverses = metrics::batch_inversion_grouped(
+                opened
+                    .iter_mut()
+                    .flatten()
+                    .map(|(_, _, folding)| std::mem::take(&mut folding.to_invert)),
+            )
+            .into_iter();
+            opened
+                .into_iter()
+                .zip(proofs)
+                .map(|(opened, proof)| {
+                    let (verification_state, domain, folding) = opened?;
+                    // We fold the answers of the last oracle, and check that they have low degree
+                    let folded_answers = self.fold(
+                        &verification_state,
+                        &domain,
+                        folding,
+                        inverses.next().unwrap(),
+                        &proof.queries_to_last(self.parameters.final_phase).0,
+                    );
+                    if self.check_final(proof, &domain, folded_answers) {
+                        Ok(())
+                    } else {
+                        Err(VerifierError::Rejected)
+                    }
+                })
+                .collect()
+        })
+    }
 
+    // Checks the folded answers of the last oracle against the final polynomial (the ans polynomial
+    // of the last round in FinalPhase::SkipLastCommit) or, if they are the whole codeword, that
+    // they have low degree
+    fn check_final(
+        &self,
+        proof: &Proof<F, MerkleConfig>,
+        domain: &RoundDomain<F>,
+        folded_answers: Vec<(F, F)>,
+    ) -> bool {
+        let final_polynomial = match self.parameters.final_phase {
+            FinalPhase::Coefficients => {
+                DensePolynomial::from_coefficients_slice(&proof.final_polynomial)
+            }
+            FinalPhase::Evaluations => {
+                let final_domain = self.parameters.final_domain();
+                metrics::record_fft(final_domain.size());
+                Evaluations::from_vec_and_domain(proof.final_polynomial.clone(), final_domain)
+                    .interpolate()
+            }
+            FinalPhase::Direct => {
+                // The answers are the folding at every point of the folded domain, in order
+                let folded_domain = Radix2EvaluationDomain::new(self.parameters.final_num_leaves())
+                    .unwrap()
+                    .get_coset(domain.scaled_offset)
+                    .unwrap();
+                metrics::record_fft(folded_domain.size());
+                let values = folded_answers.into_iter().map(|(_, value)| value).collect();
+                let folded = Evaluations::from_vec_and_domain(values, folded_domain).interpolate();
+                return folded.coeffs.len() <= self.parameters.stopping_degree;
+            }
+            FinalPhase::SkipLastCommit => proof.round_proofs.last().unwrap().ans_polynomial.clone(),
+        };
         folded_answers
             .into_iter()
-            .all(|(point, value)| proof.final_polynomial.evaluate(&point) == value)
+            .all(|(point, value)| metrics::evaluate(&final_polynomial, &point) == value)
     }
-}
 
-impl<F, MerkleConfig, FSConfig> StirVerifier<F, MerkleConfig, FSConfig>
-where
-    F: FftField + PrimeField + Absorb,
-    MerkleConfig: Config<Leaf = Vec<F>>,
-    MerkleConfig::InnerDigest: Absorb,
-    FSConfig: CryptographicSponge,
-    FSConfig::Config: Clone,
-{
-    fn compute_folded_evaluations(
+    // Checks that the opened leaves are exactly the ones at the sampled indexes, and that each of
+    // them contains a full coset (and its salt, in zero-knowledge mode)
+    fn check_queries(
         &self,
-        verification_state: &VerificationState<F>,
-        stir_randomness_indexes: Vec<usize>,
-        oracle_answers: Vec<Vec<F>>,
-    ) -> Vec<(F, F)> {
-        let scaling_factor = verification_state.domain_size / self.parameters.folding_factor;
-        let generator = verification_state.domain_gen.pow([scaling_factor as u64]);
+        queries: &(Vec<Vec<F>>, MultiPath<MerkleConfig>),
+        indexes: &[usize],
+    ) -> bool {
+        let (answers, path) = queries;
+        path.leaf_indexes == indexes
+            && answers.len() == indexes.len()
+            && answers.iter().all(|answer| {
+                answer.len() == self.parameters.folding_factor + self.parameters.salt_size
+            })
+    }
 
-        // We do a single batch inversion
+    // The points at which the folding of the current oracle is queried, and the elements that
+    // folding it there needs inverted
+    fn prepare_folding(
+        &self,
+        verification_state: &VerificationState<F>,
+        domain: &RoundDomain<F>,
+        stir_randomness_indexes: &[usize],
+    ) -> Folding<F> {
+        // The offsets of the cosets that fold to the queried points
         let coset_offsets: Vec<_> = stir_randomness_indexes
             .iter()
             .map(|stir_randomness_index| {
-                verification_state.domain_offset
-                    * verification_state
-                        .domain_gen
-                        .pow([*stir_randomness_index as u64])
+                metrics::mul(domain.domain_offset, domain.gen_pow(*stir_randomness_index))
             })
             .collect();
 
-        // We use this to more efficiently compute query_sets
-        let scales: Vec<F> = std::iter::successors(Some(F::ONE), |&prev| Some(prev * generator))
-            .take(self.parameters.folding_factor)
-            .collect();
-
+        // We use the scales to more efficiently compute query_sets
         let query_sets: Vec<_> = coset_offsets
             .iter()
             .map(|coset_offset| {
-                (0..self.parameters.folding_factor)
-                    .map(|j| *coset_offset * scales[j])
+                domain
+                    .scales
+                    .iter()
+                    .map(|scale| metrics::mul(*coset_offset, *scale))
                     .collect::<Vec<_>>()
             })
             .collect();
 
+        // These are the points that we are querying at
+        let points = stir_randomness_indexes
+            .iter()
+            .map(|stir_randomness_index| {
+                metrics::mul(
+                    domain.scaled_offset,
+                    domain.gen_pow(self.parameters.folding_factor * stir_randomness_index),
+                )
+            })
+            .collect();
+
         let common_factor_scale = match &verification_state.oracle {
-            OracleType::Initial => F::ZERO,
+            OracleType::Initial(None) => F::ZERO,
+            OracleType::Initial(Some(correction)) => correction.randomness,
             OracleType::Virtual(virtual_function) => virtual_function.comb_randomness,
         };
 
-        let global_common_factors = query_sets
-            .iter()
-            .map(|query_set| query_set.iter().map(|x| F::ONE - common_factor_scale * x));
+        let global_common_factors = query_sets.iter().map(|query_set| {
+            query_set
+                .iter()
+                .map(|x| F::ONE - metrics::mul(common_factor_scale, *x))
+        });
 
         let global_denominators =
             query_sets
                 .iter()
                 .map(|query_set| match &verification_state.oracle {
-                    OracleType::Initial => vec![F::ONE; query_set.len()],
+                    OracleType::Initial(_) => vec![F::ONE; query_set.len()],
                     OracleType::Virtual(virtual_function) => query_set
                         .iter()
                         .map(|eval_point| {
-                            virtual_function
-                                .quotient_set
-                                .iter()
-                                .map(|x| *eval_point - x)
-                                .product::<F>()
+                            metrics::product(
+                                virtual_function
+                                    .quotient_set
+                                    .iter()
+                                    .map(|x| *eval_point - x),
+                            )
                         })
                         .collect::<Vec<_>>(),
                 });
 
-        // To invert contains a bunch of stuff offsets, generator, size, and common factors
-        let size = F::from(self.parameters.folding_factor as u64);
+        // To invert contains a bunch of stuff offsets, and common factors. The caller inverts it,
+        // in a single batch inversion with the rest of the round
         let mut to_invert = vec![];
-        let global_common_factors_len = global_common_factors.len();
         for common_factors in global_common_factors {
             to_invert.extend(common_factors);
         }
@@ -290,41 +920,65 @@ where
             to_invert.extend(denominators);
         }
         to_invert.extend(coset_offsets.iter());
-        to_invert.push(generator);
-        to_invert.push(size);
-        batch_inversion(&mut to_invert);
-        let size_inv = to_invert.pop().unwrap();
-        let generator_inv = to_invert.pop().unwrap();
-        let coset_offsets_inv = to_invert.split_off(to_invert.len() - coset_offsets.len());
-        let chunked: Vec<Vec<_>> = to_invert
+
+        Folding {
+            coset_offsets,
+            query_sets,
+            points,
+            to_invert,
+        }
+    }
+
+    // Folds the answers of the previous oracle at the points of the folding, given the inverses of
+    // its elements
+    fn fold(
+        &self,
+        verification_state: &VerificationState<F>,
+        domain: &RoundDomain<F>,
+        folding: Folding<F>,
+        mut inverses: Vec<F>,
+        oracle_answers: &[Vec<F>],
+    ) -> Vec<(F, F)> {
+        let Folding {
+            coset_offsets,
+            query_sets,
+            points,
+            ..
+        } = folding;
+
+        let coset_offsets_inv = inverses.split_off(inverses.len() - coset_offsets.len());
+        let chunked: Vec<Vec<_>> = inverses
             .chunks(self.parameters.folding_factor)
             .map(|x| x.to_vec())
             .collect();
 
         // TODO: Could be split_off
-        let common_factors_inv = chunked[0..global_common_factors_len].to_vec();
-        let denominators_inv = chunked[global_common_factors_len..].to_vec();
+        let common_factors_inv = chunked[0..query_sets.len()].to_vec();
+        let denominators_inv = chunked[query_sets.len()..].to_vec();
 
         let evaluations_of_ans: Vec<_> = coset_offsets
             .iter()
             .zip(&coset_offsets_inv)
             .map(
                 |(coset_offset, coset_offset_inv)| match &verification_state.oracle {
-                    OracleType::Initial => vec![F::ONE; self.parameters.folding_factor],
+                    OracleType::Initial(_) => vec![F::ONE; self.parameters.folding_factor],
                     OracleType::Virtual(virtual_function) => {
                         let domain = Radix2EvaluationDomain {
                             size: self.parameters.folding_factor as u64,
                             log_size_of_group: self.parameters.folding_factor.ilog2(),
-                            size_as_field_element: size,
-                            size_inv,
-                            group_gen: generator,
-                            group_gen_inv: generator_inv,
+                            size_as_field_element: F::from(self.parameters.folding_factor as u64),
+                            size_inv: domain.size_inv,
+                            group_gen: domain.generator,
+                            group_gen_inv: domain.generator_inv,
                             offset: *coset_offset,
                             offset_inv: *coset_offset_inv,
-                            offset_pow_size: coset_offset
-                                .pow([self.parameters.folding_factor as u64]),
+                            offset_pow_size: metrics::pow(
+                                *coset_offset,
+                                self.parameters.folding_factor as u64,
+                            ),
                         };
 
+                        metrics::record_fft(self.parameters.folding_factor);
                         virtual_function
                             .interpolating_polynomial
                             .clone()
@@ -335,12 +989,8 @@ where
             )
             .collect();
 
-        let scaled_offset = verification_state
-            .domain_offset
-            .pow([self.parameters.folding_factor as u64]);
-
         izip!(
-            stir_randomness_indexes.iter(),
+            points,
             coset_offsets,
             coset_offsets_inv,
             query_sets,
@@ -353,7 +1003,7 @@ where
             |(
                 i,
                 (
-                    stir_randomness_index,
+                    stir_randomness,
                     coset_offset,
                     coset_offset_inv,
                     query_set,
@@ -362,12 +1012,6 @@ where
                     evaluation_of_ans,
                 ),
             )| {
-                // This is the point that we are querying at
-                let stir_randomness = scaled_offset
-                    * verification_state
-                        .domain_gen
-                        .pow([(self.parameters.folding_factor * stir_randomness_index) as u64]);
-
                 let f_answers: Vec<_> = query_set
                     .into_iter()
                     .enumerate()
@@ -383,15 +1027,17 @@ where
                     .collect();
 
                 // This is the folding
-                let folded_answer = poly_utils::interpolation::fft_interpolate(
-                    generator,
-                    coset_offset,
-                    generator_inv,
-                    coset_offset_inv,
-                    size_inv,
-                    &f_answers,
-                )
-                .evaluate(&verification_state.folding_randomness);
+                let folded_answer = metrics::evaluate(
+                    &poly_utils::interpolation::fft_interpolate(
+                        domain.generator,
+                        coset_offset,
+                        domain.generator_inv,
+                        coset_offset_inv,
+                        domain.size_inv,
+                        &f_answers,
+                    ),
+                    &verification_state.folding_randomness,
+                );
 
                 // Return the folded answer
                 (stir_randomness, folded_answer)
@@ -400,53 +1046,89 @@ where
         .collect()
     }
 
-    fn round(
+    // Replays the transcript of a round, and prepares the folding of the previous oracle
+    fn open_round(
         &self,
-        sponge: &mut impl CryptographicSponge,
+        transcript: &mut Transcript<FSConfig>,
         round_proof: &RoundProof<F, MerkleConfig>,
-        verification_state: VerificationState<F>,
-    ) -> Option<VerificationState<F>> {
+        verification_state: &VerificationState<F>,
+        domain: &RoundDomain<F>,
+    ) -> Result<OpenedRound<F>, VerifierError> {
         // Redo FS
-        sponge.absorb(&round_proof.g_root);
-        let ood_randomness = sponge.squeeze_field_elements(self.parameters.ood_samples);
-        sponge.absorb(&round_proof.betas);
-        let comb_randomness = sponge.squeeze_field_elements(1)[0];
-        let new_folding_randomness = sponge.squeeze_field_elements(1)[0];
+        transcript.append_commitment(&round_proof.g_root)?;
+        let ood_randomness: Vec<F> = transcript.challenge_scalars(self.parameters.ood_samples)?;
+        transcript.append_field_elements(&round_proof.betas)?;
+        let comb_randomness = transcript.challenge_scalar()?;
+        let folding_randomness = transcript.challenge_scalar()?;
         let scaling_factor = verification_state.domain_size / self.parameters.folding_factor;
 
         let num_repetitions = self.parameters.repetitions[verification_state.num_round];
-        let stir_randomness_indexes = utils::dedup(
-            (0..num_repetitions).map(|_| utils::squeeze_integer(sponge, scaling_factor)),
-        );
+        let stir_randomness_indexes =
+            transcript.challenge_indices(num_repetitions, scaling_factor)?;
 
         // PoW verification
-        if !utils::proof_of_work_verify(
-            sponge,
+        transcript.check_grind(
             self.parameters.pow_bits[verification_state.num_round],
             round_proof.pow_nonce,
-        ) {
-            return None;
-        }
+        )?;
 
-        let shake_randomness = sponge.squeeze_field_elements(1)[0];
+        let shake_randomness = transcript.challenge_scalar()?;
 
         // Now, we are starting to define the next function.
         // First, we need to query the previous oracle (which is either f_0 or g_i)
         // At the indexes B_i for i in stir_randomness_indexes
-        // Since we previously verified the Merkle paths, this is easy
-        // TODO: We should probably check the indexes
-        let oracle_answers = round_proof.queries_to_prev.0.clone();
+        // Since we previously verified the Merkle paths, this is easy once we check that they
+        // open the indexes we asked for
+        if !self.check_queries(&round_proof.queries_to_prev, &stir_randomness_indexes) {
+            return Err(VerifierError::Rejected);
+        }
+
+        // The shake check also divides by the distance of each point of the quotient to the
+        // shake randomness, so we invert these along with the folding
+        let mut folding =
+            self.prepare_folding(verification_state, domain, &stir_randomness_indexes);
+        folding.to_invert.extend(
+            ood_randomness
+                .iter()
+                .chain(&folding.points)
+                .map(|x| shake_randomness - x),
+        );
+
+        Ok(OpenedRound {
+            ood_randomness,
+            comb_randomness,
+            folding_randomness,
+            shake_randomness,
+            folding,
+        })
+    }
+
+    // Checks a round given the inverses of the elements it opened with, and moves on to the next
+    // function
+    fn close_round(
+        &self,
+        round_proof: &RoundProof<F, MerkleConfig>,
+        verification_state: VerificationState<F>,
+        domain: &RoundDomain<F>,
+        round: OpenedRound<F>,
+        mut inverses: Vec<F>,
+    ) -> Result<VerificationState<F>, VerifierError> {
+        let denoms = inverses
+            .split_off(inverses.len() - round.ood_randomness.len() - round.folding.points.len());
 
         // Now, for each of the selected random points, we need to compute the folding of the
         // previous oracle
-        let folded_answers = self.compute_folded_evaluations(
+        let folded_answers = self.fold(
             &verification_state,
-            stir_randomness_indexes,
-            oracle_answers,
+            domain,
+            round.folding,
+            inverses,
+            &round_proof.queries_to_prev.0,
         );
 
         // The quotient definining the function
-        let quotient_answers: Vec<_> = ood_randomness
+        let quotient_answers: Vec<_> = round
+            .ood_randomness
             .into_iter()
             .zip(&round_proof.betas)
             .map(|(alpha, beta)| (alpha, *beta))
@@ -454,24 +1136,18 @@ where
             .collect();
         let interpolating_polynomial = round_proof.ans_polynomial.clone();
 
-        let ans_eval = interpolating_polynomial.evaluate(&shake_randomness);
-        let shake_eval = round_proof.shake_polynomial.evaluate(&shake_randomness);
-
-        let mut denoms: Vec<_> = quotient_answers
-            .iter()
-            .map(|(x, _)| shake_randomness - x)
-            .collect();
+        let ans_eval = metrics::evaluate(&interpolating_polynomial, &round.shake_randomness);
+        let shake_eval = metrics::evaluate(&round_proof.shake_polynomial, &round.shake_randomness);
 
-        batch_inversion(&mut denoms);
         // TODO: This maybe should be better
         if shake_eval
             != quotient_answers
                 .iter()
                 .zip(denoms)
-                .map(|((_, y), d)| (ans_eval - y) * d)
+                .map(|((_, y), d)| metrics::mul(ans_eval - y, d))
                 .sum()
         {
-            return None;
+            return Err(VerifierError::Rejected);
         }
 
         let quotient_set = quotient_answers
@@ -479,21 +1155,554 @@ where
             .map(|(x, _)| x)
             .collect::<Vec<_>>();
 
-        Some(VerificationState {
+        // The next domain generator and offset
+        let (domain_gen, domain_size, domain_offset) = next_domain(
+            verification_state.domain_gen,
+            verification_state.domain_size,
+            verification_state.domain_offset,
+            verification_state.root_of_unity,
+        );
+        Ok(VerificationState {
             oracle: OracleType::Virtual(VirtualFunction {
-                comb_randomness,
+                comb_randomness: round.comb_randomness,
                 quotient_set,
                 interpolating_polynomial,
             }),
-            // TODO: We can optimize
-            domain_size: verification_state.domain_size / 2,
-            domain_gen: verification_state.domain_gen * verification_state.domain_gen,
-            domain_offset: verification_state.domain_offset
-                * verification_state.domain_offset
-                * verification_state.root_of_unity,
+            domain_size,
+            domain_gen,
+            domain_offset,
             root_of_unity: verification_state.root_of_unity,
-            folding_randomness: new_folding_randomness,
+            folding_randomness: round.folding_randomness,
             num_round: verification_state.num_round + 1,
         })
     }
+
+    // Replays the end of the transcript, and prepares the folding of the last oracle. In
+    // FinalPhase::SkipLastCommit, this is the oracle the last round queries.
+    fn open_final(
+        &self,
+        mut transcript: Transcript<FSConfig>,
+        proof: &Proof<F, MerkleConfig>,
+        verification_state: &VerificationState<F>,
+        domain: &RoundDomain<F>,
+    ) -> Result<Folding<F>, VerifierError> {
+        // Now, we sample the last points that we want to check consisntency at
+        let final_repetitions = self.parameters.repetitions[self.parameters.num_rounds];
+        let scaling_factor = verification_state.domain_size / self.parameters.folding_factor;
+        let final_randomness_indexes = match self.parameters.final_phase {
+            FinalPhase::Coefficients | FinalPhase::Evaluations => {
+                let indexes = transcript.challenge_indices(final_repetitions, scaling_factor)?;
+                transcript.check_grind(
+                    self.parameters.pow_bits[self.parameters.num_rounds],
+                    proof.pow_nonce,
+                )?;
+                indexes
+            }
+            // Or we read the whole last oracle
+            FinalPhase::Direct => (0..scaling_factor).collect(),
+            // Or the last round sends the folding, padded as the prover did
+            FinalPhase::SkipLastCommit => {
+                let num_round = verification_state.num_round;
+                let round_proof = &proof.round_proofs[num_round];
+                let mut coefficients = round_proof.ans_polynomial.coeffs.clone();
+                coefficients.resize(self.parameters.degrees[self.parameters.num_rounds], F::ZERO);
+                transcript.append_field_elements(&coefficients)?;
+                let indexes = transcript
+                    .challenge_indices(self.parameters.repetitions[num_round], scaling_factor)?;
+                transcript
+                    .check_grind(self.parameters.pow_bits[num_round], round_proof.pow_nonce)?;
+                indexes
+            }
+        };
+        transcript.finish()?;
+
+        // First, we want to query back the last oracle at this point, which is, again, just a
+        // lookup
+        if !self.check_queries(
+            proof.queries_to_last(self.parameters.final_phase),
+            &final_randomness_indexes,
+        ) {
+            return Err(VerifierError::Rejected);
+        }
+
+        Ok(self.prepare_folding(verification_state, domain, &final_randomness_indexes))
+    }
+}
+
+// A StirVerifier with the domains of every round, the values derived from them, and tables of the
+// powers of their generators precomputed, so that verifying many proofs against the same
+// parameters only pays for them once
+pub struct PreparedStirVerifier<F, MerkleConfig, FSConfig>
+where
+    F: FftField,
+    MerkleConfig: Config,
+    FSConfig: CryptographicSponge,
+    FSConfig::Config: Clone,
+{
+    verifier: StirVerifier<F, MerkleConfig, FSConfig>,
+    domains: Vec<RoundDomain<F>>,
+}
+
+impl<F, MerkleConfig, FSConfig> PreparedStirVerifier<F, MerkleConfig, FSConfig>
+where
+    F: FftField + PrimeField + Absorb,
+    MerkleConfig: Config<Leaf = Vec<F>>,
+    MerkleConfig::InnerDigest: Absorb,
+    FSConfig: CryptographicSponge + Send,
+    FSConfig::Config: Clone + Send + Sync,
+{
+    pub fn new(verifier: StirVerifier<F, MerkleConfig, FSConfig>) -> Self {
+        let domains = verifier.prepare_domains();
+        Self { verifier, domains }
+    }
+
+    pub fn verifier(&self) -> &StirVerifier<F, MerkleConfig, FSConfig> {
+        &self.verifier
+    }
+
+    pub fn verify_prepared(
+        &self,
+        commitment: &Commitment<MerkleConfig>,
+        proof: &Proof<F, MerkleConfig>,
+    ) -> bool {
+        self.try_verify_prepared(commitment, proof).is_ok()
+    }
+
+    pub fn try_verify_prepared(
+        &self,
+        commitment: &Commitment<MerkleConfig>,
+        proof: &Proof<F, MerkleConfig>,
+    ) -> Result<(), VerifierError> {
+        self.verifier.verify_and_report(
+            Some(&self.domains),
+            commitment,
+            proof,
+            &mut VerifierCostReport::new(self.verifier.parameters.num_rounds + 1),
+        )
+    }
+
+    pub fn verify_prepared_with_cost_report(
+        &self,
+        commitment: &Commitment<MerkleConfig>,
+        proof: &Proof<F, MerkleConfig>,
+    ) -> (bool, VerifierCostReport) {
+        let mut report = VerifierCostReport::new(self.verifier.parameters.num_rounds + 1);
+        let (result, total) = metrics::with_counter(|| {
+            self.verifier
+                .verify_and_report(Some(&self.domains), commitment, proof, &mut report)
+        });
+        report.total = total;
+        (result.is_ok(), report)
+    }
+
+    // Verifies many proofs at once, and returns the indexes of those that were rejected
+    pub fn verify_batch(
+        &self,
+        instances: &[(Commitment<MerkleConfig>, Proof<F, MerkleConfig>)],
+    ) -> Result<(), Vec<usize>> {
+        self.verifier
+            .verify_batch_prepared(&self.domains, instances)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::{fields::Field64 as TestField, fs, merkle_tree::blake3 as mt},
+        ldt::{assert_batch_rejects, Prover},
+        parameters::SoundnessType,
+        stir::prover::StirProver,
+        transcript::TranscriptError,
+    };
+
+    type MerkleConfig = mt::MerkleTreeParams<TestField>;
+
+    fn parameters() -> Parameters<TestField, MerkleConfig, fs::blake3::Sponge> {
+        let mut rng = ark_std::test_rng();
+        let (leaf_hash_params, two_to_one_params) = mt::default_config::<TestField>(&mut rng, 4);
+        Parameters {
+            security_level: 32,
+            protocol_security_level: 32,
+            starting_degree: 256,
+            stopping_degree: 4,
+            folding_factor: 4,
+            starting_rate: 2,
+            soundness_type: SoundnessType::Conjecture,
+            leaf_hash_params,
+            two_to_one_params,
+            fiat_shamir_config: fs::blake3::default_fs_config(),
+            _field: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_power_table() {
+        let base = Domain::<TestField>::new(32, 0).unwrap().element(1);
+        for bits in [0, 1, 4, 5] {
+            let table = PowerTable::new(base, bits);
+            for exponent in 0..1 << bits {
+                assert_eq!(table.pow(exponent), base.pow([exponent as u64]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_final_phases() {
+        let mut rng = ark_std::test_rng();
+        let full_parameters = FullParameters::from(parameters());
+        // A small last oracle is cheaper to send whole than to query
+        let mut small = parameters();
+        small.starting_degree = 16;
+        assert_eq!(FullParameters::from(small).final_phase, FinalPhase::Direct);
+
+        for final_phase in [
+            FinalPhase::Coefficients,
+            FinalPhase::Evaluations,
+            FinalPhase::Direct,
+        ] {
+            let full_parameters = full_parameters.clone().with_final_phase(final_phase);
+            let prover = StirProver::new_full(full_parameters.clone());
+            let verifier = StirVerifier::new_full(full_parameters);
+
+            let (commitment, witness) = prover.commit(DensePolynomial::rand(255, &mut rng));
+            let proof = prover.prove(witness);
+            assert!(verifier.verify(&commitment, &proof), "{:?}", final_phase);
+            let (other_commitment, _) = prover.commit(DensePolynomial::rand(255, &mut rng));
+            assert!(!verifier.verify(&other_commitment, &proof));
+
+            let mut tampered = proof.clone();
+            match final_phase {
+                FinalPhase::Direct => tampered.final_polynomial.push(TestField::ONE),
+                _ => tampered.final_polynomial[0] += TestField::ONE,
+            }
+            assert!(!verifier.verify(&commitment, &tampered));
+
+            let verifier = verifier.prepare();
+            assert!(verifier.verify_prepared(&commitment, &proof));
+            let mut tampered = proof;
+            tampered.queries_to_final.0[0][0] += TestField::ONE;
+            assert!(!verifier.verify_prepared(&commitment, &tampered));
+        }
+    }
+
+    #[test]
+    fn test_skipped_round() {
+        let mut rng = ark_std::test_rng();
+        // Sending the folding of the oracle of the last round is cheaper than committing to the
+        // next one and querying it
+        let full_parameters = FullParameters::from(parameters());
+        assert_eq!(full_parameters.final_phase, FinalPhase::SkipLastCommit);
+        assert_eq!(full_parameters.committed_rounds(), 1);
+        let last_round = full_parameters.num_rounds - 1;
+
+        // With more queries than the folding has coefficients, the repetitions of the last round
+        // are no longer bounded by them, and its grinding follows them
+        let mut more_queries = parameters();
+        more_queries.protocol_security_level = 64;
+        let skipping = FullParameters::from(more_queries);
+        assert_eq!(skipping.final_phase, FinalPhase::SkipLastCommit);
+        let (log_inv_rate, repetitions) =
+            (skipping.rates[last_round], skipping.repetitions[last_round]);
+        assert_eq!(repetitions, skipping.parameters.repetitions(log_inv_rate));
+        assert_eq!(
+            skipping.pow_bits[last_round],
+            skipping
+                .parameters
+                .pow_bits_for_repetitions(log_inv_rate, repetitions)
+        );
+        let committed = skipping.clone().with_final_phase(FinalPhase::Coefficients);
+        assert_eq!(
+            committed.repetitions[last_round],
+            committed.degrees[last_round] / committed.folding_factor
+        );
+        assert!(committed.repetitions[last_round] < repetitions);
+        assert_eq!(
+            committed
+                .with_final_phase(FinalPhase::SkipLastCommit)
+                .repetitions,
+            skipping.repetitions
+        );
+
+        let prover = StirProver::new_full(full_parameters.clone());
+        let verifier = StirVerifier::new_full(full_parameters.clone());
+        let (commitment, witness) = prover.commit(DensePolynomial::rand(255, &mut rng));
+        let proof = prover.prove(witness);
+        // The last round commits to no oracle, and sends the folding of the one it queries as its
+        // ans polynomial, which is all that is checked after it, with no final polynomial or
+        // queries
+        let round_proof = &proof.round_proofs[last_round];
+        assert_eq!(round_proof.g_root, Default::default());
+        assert!(
+            round_proof.ans_polynomial.coeffs.len()
+                <= full_parameters.degrees[full_parameters.num_rounds]
+        );
+        assert!(proof.final_polynomial.is_empty());
+        assert!(proof.queries_to_final.0.is_empty());
+        assert!(proof.queries_to_final.1.leaf_indexes.is_empty());
+        assert!(proof.pow_nonce.is_none());
+        assert!(verifier.verify(&commitment, &proof));
+
+        let mut tampered = proof.clone();
+        tampered.round_proofs[last_round].ans_polynomial.coeffs[0] += TestField::ONE;
+        assert!(!verifier.verify(&commitment, &tampered));
+        let mut tampered = proof.clone();
+        tampered.round_proofs[last_round].queries_to_prev.0[0][0] += TestField::ONE;
+        assert!(!verifier.verify(&commitment, &tampered));
+        let mut tampered = proof.clone();
+        tampered.round_proofs[last_round].g_root = proof.round_proofs[0].g_root;
+        assert!(!verifier.verify(&commitment, &tampered));
+        let mut tampered = proof.clone();
+        tampered.queries_to_final = round_proof.queries_to_prev.clone();
+        assert!(!verifier.verify(&commitment, &tampered));
+
+        let verifier = verifier.prepare();
+        assert!(verifier.verify_prepared(&commitment, &proof));
+    }
+
+    #[test]
+    fn test_try_verify() {
+        let mut rng = ark_std::test_rng();
+        let mut parameters = parameters();
+        // A few bits of proof of work in every round
+        parameters.security_level = 40;
+        let prover = StirProver::new(parameters.clone());
+        let mut verifier = StirVerifier::new(parameters);
+        assert!(verifier.parameters.pow_bits[0] > 0);
+
+        let (commitment, witness) = prover.commit(DensePolynomial::rand(255, &mut rng));
+        let proof = prover.prove(witness);
+        assert_eq!(verifier.try_verify(&commitment, &proof), Ok(()));
+
+        let mut tampered = proof.clone();
+        tampered.round_proofs[0].shake_polynomial.coeffs[0] += TestField::ONE;
+        assert_eq!(
+            verifier.try_verify(&commitment, &tampered),
+            Err(VerifierError::Rejected)
+        );
+        let mut tampered = proof.clone();
+        tampered.round_proofs[0].pow_nonce = None;
+        assert_eq!(
+            verifier.try_verify(&commitment, &tampered),
+            Err(VerifierError::Transcript(
+                TranscriptError::InvalidProofOfWork
+            ))
+        );
+
+        // Answers the transcript has no room for make a proof malformed, rather than the
+        // transcript diverge from its pattern
+        let mut tampered = proof.clone();
+        tampered.round_proofs[0].betas.push(TestField::ONE);
+        assert_eq!(
+            verifier.try_verify(&commitment, &tampered),
+            Err(VerifierError::Rejected)
+        );
+        let mut tampered = proof.clone();
+        let max_answers = verifier.parameters.ood_samples + verifier.parameters.repetitions[0];
+        tampered.round_proofs[0].shake_polynomial.coeffs = vec![TestField::ONE; max_answers + 1];
+        assert_eq!(
+            verifier.try_verify(&commitment, &tampered),
+            Err(VerifierError::Rejected)
+        );
+        verifier.parameters.ood_samples += 1;
+        assert_eq!(
+            verifier.try_verify(&commitment, &proof),
+            Err(VerifierError::Rejected)
+        );
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng = ark_std::test_rng();
+        let parameters = parameters();
+        let prover = StirProver::new(parameters.clone());
+        let verifier = StirVerifier::new(parameters);
+
+        let mut instances: Vec<_> = (0..8)
+            .map(|_| {
+                let (commitment, witness) = prover.commit(DensePolynomial::rand(255, &mut rng));
+                (commitment, prover.prove(witness))
+            })
+            .collect();
+        assert_batch_rejects(&verifier, &instances, &[]);
+        assert_batch_rejects(&verifier, &instances[..0], &[]);
+
+        // A Merkle path, a consistency check and the shape of the proof fail respectively
+        let (commitment, proof) = &mut instances[1];
+        std::mem::swap(&mut commitment.root, &mut proof.round_proofs[0].g_root);
+        instances[4].1.round_proofs[0].betas[0] += TestField::ONE;
+        instances[5].1.round_proofs.pop();
+        assert_batch_rejects(&verifier, &instances, &[1, 4, 5]);
+        assert_eq!(
+            verifier.prepare().verify_batch(&instances),
+            Err(vec![1, 4, 5])
+        );
+    }
+
+    #[test]
+    fn test_verify_aggregate() {
+        let mut rng = ark_std::test_rng();
+        let parameters = parameters();
+        let prover = StirProver::new(parameters.clone());
+        let verifier = StirVerifier::new(parameters);
+
+        let (commitments, witnesses): (Vec<_>, Vec<_>) = (0..3)
+            .map(|_| prover.commit(DensePolynomial::rand(255, &mut rng)))
+            .unzip();
+        let proof = prover.prove_aggregate(witnesses.clone());
+        assert!(verifier.verify_aggregate(&commitments, &proof));
+        assert!(!verifier.verify_aggregate(&commitments[..2], &proof));
+        assert!(!verifier.verify_aggregate(&[], &proof));
+
+        // The commitments are bound in order
+        let mut swapped = commitments.clone();
+        swapped.swap(1, 2);
+        assert!(!verifier.verify_aggregate(&swapped, &proof));
+
+        // An opening of one of the other commitments is changed, or its authentication path
+        let mut tampered = proof.clone();
+        tampered.queries_to_commitments[1].0[0][0] += TestField::ONE;
+        assert!(!verifier.verify_aggregate(&commitments, &tampered));
+        let mut tampered = proof.clone();
+        tampered.queries_to_commitments[1].1.leaf_siblings_hashes[0] = Default::default();
+        assert_eq!(
+            verifier.try_verify_aggregate(&commitments, &tampered),
+            Err(VerifierError::Rejected)
+        );
+        let mut tampered = proof.clone();
+        tampered.queries_to_commitments[1].1 = proof.queries_to_commitments[0].1.clone();
+        assert!(!verifier.verify_aggregate(&commitments, &tampered));
+
+        // The first commitment is opened at the sampled leaves only
+        let mut extra = proof.clone();
+        let answers = &mut extra.proof.queries_to_initial_mut().0;
+        answers.push(answers[0].clone());
+        assert!(!verifier.verify_aggregate(&commitments, &extra));
+
+        // The proof of a single commitment is not the proof of the function alone
+        let single = prover.prove_aggregate(witnesses[..1].to_vec());
+        assert!(verifier.verify_aggregate(&commitments[..1], &single));
+        assert!(!verifier.verify(&commitments[0], &single.proof));
+    }
+
+    // A prover that corrects the degree of its function by less than the verifier does, so that
+    // the polynomial it proves is of low degree while the committed one is above the bound
+    #[test]
+    fn test_degree_above_corrected_bound() {
+        let mut rng = ark_std::test_rng();
+        let mut parameters = parameters();
+        parameters.starting_degree = 3 << 6;
+        let verifier = StirVerifier::new(parameters.clone());
+        let mut cheating_parameters = FullParameters::from(parameters);
+        cheating_parameters.degree_bound += 8;
+        let prover = StirProver::new_full(cheating_parameters);
+
+        for _ in 0..4 {
+            let (commitment, witness) = prover.commit(DensePolynomial::rand(3 << 6, &mut rng));
+            assert!(!verifier.verify(&commitment, &prover.prove(witness)));
+        }
+    }
+
+    #[test]
+    fn test_prepared_domains() {
+        let parameters = parameters();
+        let verifier = StirVerifier::new(parameters.clone()).prepare();
+        assert_eq!(
+            verifier.domains.len(),
+            verifier.verifier.parameters.num_rounds + 1
+        );
+
+        let domain = Domain::<TestField>::new(256, 2).unwrap();
+        let first = &verifier.domains[0];
+        assert_eq!(first.domain_gen, domain.element(1));
+        assert_eq!(first.domain_size, domain.size());
+        assert_eq!(first.domain_offset, TestField::ONE);
+        for (previous, next) in verifier.domains.iter().zip(&verifier.domains[1..]) {
+            assert_eq!(next.domain_size, previous.domain_size / 2);
+            assert_eq!(next.generator.pow([4]), TestField::ONE);
+            assert_eq!(next.generator * next.generator_inv, TestField::ONE);
+            assert_eq!(next.scaled_offset, next.domain_offset.pow([4]));
+        }
+    }
+
+    #[test]
+    fn test_verify_prepared() {
+        let mut rng = ark_std::test_rng();
+        let parameters = parameters();
+        let prover = StirProver::new(parameters.clone());
+        let verifier = StirVerifier::new(parameters.clone());
+        let prepared = StirVerifier::new(parameters).prepare();
+
+        let (commitment, witness) = prover.commit(DensePolynomial::rand(255, &mut rng));
+        let proof = prover.prove(witness);
+
+        let (accepted, report) = verifier.verify_with_cost_report(&commitment, &proof);
+        let (prepared_accepted, prepared_report) =
+            prepared.verify_prepared_with_cost_report(&commitment, &proof);
+        assert!(accepted);
+        assert!(prepared_accepted);
+        assert_eq!(prepared_report.total.hashes(), report.total.hashes());
+        assert_eq!(prepared_report.rounds.len(), report.rounds.len());
+        #[cfg(feature = "metrics")]
+        assert!(prepared_report.total.field_multiplications < report.total.field_multiplications);
+
+        // The folding the last round sends, in place of a final polynomial
+        assert_eq!(verifier.parameters.final_phase, FinalPhase::SkipLastCommit);
+        let mut tampered = proof.clone();
+        let last_round = tampered.round_proofs.last_mut().unwrap();
+        last_round.ans_polynomial = DensePolynomial::rand(3, &mut rng);
+        assert!(!verifier.verify(&commitment, &tampered));
+        assert!(!prepared.verify_prepared(&commitment, &tampered));
+
+        let mut tampered = proof;
+        tampered.round_proofs[0].betas[0] += TestField::ONE;
+        assert!(!verifier.verify(&commitment, &tampered));
+        assert!(!prepared.verify_prepared(&commitment, &tampered));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_cost_report() {
+        let mut rng = ark_std::test_rng();
+        // Every round commits to an oracle, which FinalPhase::SkipLastCommit would not
+        let full_parameters =
+            FullParameters::from(parameters()).with_final_phase(FinalPhase::Coefficients);
+        let prover = StirProver::new_full(full_parameters.clone());
+        let verifier = StirVerifier::new_full(full_parameters);
+
+        let (commitment, witness) = prover.commit(DensePolynomial::rand(255, &mut rng));
+        let proof = prover.prove(witness);
+
+        let ((accepted, report), counters) =
+            crate::metrics::with_counter(|| verifier.verify_with_cost_report(&commitment, &proof));
+        assert!(accepted);
+        assert_eq!(report.total, counters);
+        // Pinned for these parameters, so that changes to what is counted are deliberate
+        let multiplications: Vec<_> = report
+            .rounds
+            .iter()
+            .map(|round| round.field_multiplications)
+            .collect();
+        assert_eq!(multiplications, [1335, 2163, 1518]);
+        assert_eq!(
+            report.total,
+            crate::metrics::Counters {
+                leaf_hashes: 33,
+                compressions: 132,
+                permutations: 96,
+                field_multiplications: 5016,
+                field_inversions: 6,
+                pow_checks: 0,
+            }
+        );
+        assert_eq!(report.rounds.len(), verifier.parameters.num_rounds + 1);
+        for round in &report.rounds {
+            assert!(round.leaf_hashes > 0 && round.compressions > 0);
+            assert!(round.field_multiplications > 0 && round.permutations > 0);
+            assert_eq!(round.pow_checks, 0);
+        }
+        // The rounds check the quotients, and the last one only folds
+        assert!(report.rounds[0].field_inversions > 0);
+        assert_eq!(report.shared().hashes(), 0);
+    }
 }
@@ -0,0 +1,262 @@
+// Sweeps of the prover and verifier binaries over every combination of ranges of parameters, run
+// in a single process
+
+use std::{
+    fmt::Display,
+    fs::{File, OpenOptions},
+    path::PathBuf,
+    str::FromStr,
+    time::Instant,
+};
+
+use clap::Args;
+
+// A list of values, given as comma separated numbers and ranges a..b or a..=b, optionally with a
+// step, as in 18..=30:2,32
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Values(pub Vec<usize>);
+
+impl FromStr for Values {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .map_err(|e| format!("{:?}: {}", n, e))
+        };
+
+        let mut values = vec![];
+        for item in s.split(',') {
+            let (range, step) = match item.split_once(':') {
+                Some((range, step)) => (range, parse(step)?),
+                None => (item, 1),
+            };
+            if step == 0 {
+                return Err(format!("{:?}: the step must be positive", item));
+            }
+
+            if let Some((start, end)) = range.split_once("..=") {
+                values.extend((parse(start)?..=parse(end)?).step_by(step));
+            } else if let Some((start, end)) = range.split_once("..") {
+                values.extend((parse(start)?..parse(end)?).step_by(step));
+            } else {
+                values.push(parse(range)?);
+            }
+        }
+
+        if values.is_empty() {
+            return Err(format!("{:?} contains no values", s));
+        }
+        Ok(Values(values))
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SweepArgs {
+    // Runs every combination of the values below, instead of the single point given by the other
+    // arguments, and writes all the results to outputs/<binary>_sweep.json
+    #[arg(long)]
+    pub sweep: bool,
+
+    // Logs of the starting degree
+    #[arg(long, default_value = "18..=30:2")]
+    pub sweep_degrees: Values,
+
+    #[arg(long, default_value = "1..=4")]
+    pub sweep_rates: Values,
+
+    #[arg(long, default_value = "16")]
+    pub sweep_stir_folding_factors: Values,
+
+    #[arg(long, default_value = "8")]
+    pub sweep_fri_folding_factors: Values,
+
+    #[arg(long, default_value = "128")]
+    pub sweep_security_levels: Values,
+}
+
+// The parameters that change along a sweep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Point {
+    pub security_level: usize,
+    // Log of the starting degree
+    pub initial_degree: usize,
+    pub rate: usize,
+    pub stir_folding_factor: usize,
+    pub fri_folding_factor: usize,
+}
+
+impl Point {
+    // Identifies the point in file names
+    pub fn slug(&self) -> String {
+        format!(
+            "l{}_d{}_r{}_sk{}_fk{}",
+            self.security_level,
+            self.initial_degree,
+            self.rate,
+            self.stir_folding_factor,
+            self.fri_folding_factor
+        )
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "security level {}, degree 2^{}, rate 2^-{}, folding factors {} (STIR) {} (FRI)",
+            self.security_level,
+            self.initial_degree,
+            self.rate,
+            self.stir_folding_factor,
+            self.fri_folding_factor
+        )
+    }
+}
+
+impl SweepArgs {
+    // Every combination of the values, with the degree varying fastest
+    pub fn points(&self) -> Vec<Point> {
+        itertools::iproduct!(
+            &self.sweep_security_levels.0,
+            &self.sweep_rates.0,
+            &self.sweep_stir_folding_factors.0,
+            &self.sweep_fri_folding_factors.0,
+            &self.sweep_degrees.0
+        )
+        .map(
+            |(
+                &security_level,
+                &rate,
+                &stir_folding_factor,
+                &fri_folding_factor,
+                &initial_degree,
+            )| {
+                Point {
+                    security_level,
+                    initial_degree,
+                    rate,
+                    stir_folding_factor,
+                    fri_folding_factor,
+                }
+            },
+        )
+        .collect()
+    }
+
+    // Where the prover writes the proofs of a point for the verifier. Each point of a sweep has
+    // its own directory, so that the verifier can sweep over the same points afterwards.
+    pub fn artifacts_dir(&self, point: &Point) -> PathBuf {
+        let dir = PathBuf::from("artifacts");
+        if self.sweep {
+            dir.join(point.slug())
+        } else {
+            dir
+        }
+    }
+
+    // The file the results are written to, one JSON record per line. A sweep starts a new file,
+    // while single runs are appended to outputs/<binary>_output.json.
+    pub fn output(&self, binary: &str) -> File {
+        std::fs::create_dir_all("outputs").unwrap();
+        if self.sweep {
+            File::create(format!("outputs/{}_sweep.json", binary)).unwrap()
+        } else {
+            OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(format!("outputs/{}_output.json", binary))
+                .unwrap()
+        }
+    }
+}
+
+// Reports on stderr which point of a sweep is running, and an estimate of the time left
+pub struct Progress {
+    total: usize,
+    done: usize,
+    start: Instant,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Self {
+        Self {
+            total,
+            done: 0,
+            start: Instant::now(),
+        }
+    }
+
+    pub fn start(&mut self, point: &Point) {
+        let elapsed = self.start.elapsed();
+        let remaining = match self.done {
+            0 => String::from("unknown"),
+            done => format!(
+                "{:.0?}",
+                elapsed.mul_f64((self.total - done) as f64 / done as f64)
+            ),
+        };
+        eprintln!(
+            "[{}/{}] {} (elapsed {:.0?}, remaining {})",
+            self.done + 1,
+            self.total,
+            point,
+            elapsed,
+            remaining
+        );
+        self.done += 1;
+    }
+
+    pub fn finish(self) {
+        eprintln!("Ran {} points in {:.0?}", self.total, self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values() {
+        assert_eq!("16".parse(), Ok(Values(vec![16])));
+        assert_eq!("4,8,16".parse(), Ok(Values(vec![4, 8, 16])));
+        assert_eq!("1..4".parse(), Ok(Values(vec![1, 2, 3])));
+        assert_eq!("1..=4".parse(), Ok(Values(vec![1, 2, 3, 4])));
+        assert_eq!(
+            "18..=30:2,32".parse(),
+            Ok(Values(vec![18, 20, 22, 24, 26, 28, 30, 32]))
+        );
+
+        assert!("".parse::<Values>().is_err());
+        assert!("4..4".parse::<Values>().is_err());
+        assert!("1..=4:0".parse::<Values>().is_err());
+        assert!("a..=4".parse::<Values>().is_err());
+    }
+
+    #[test]
+    fn test_points() {
+        let sweep = SweepArgs {
+            sweep: true,
+            sweep_degrees: Values(vec![18, 20]),
+            sweep_rates: Values(vec![1, 2]),
+            sweep_stir_folding_factors: Values(vec![16]),
+            sweep_fri_folding_factors: Values(vec![8]),
+            sweep_security_levels: Values(vec![128]),
+        };
+
+        let points = sweep.points();
+        assert_eq!(points.len(), 4);
+        assert_eq!(
+            points
+                .iter()
+                .map(|p| (p.rate, p.initial_degree))
+                .collect::<Vec<_>>(),
+            vec![(1, 18), (1, 20), (2, 18), (2, 20)]
+        );
+        assert_eq!(
+            sweep.artifacts_dir(&points[0]),
+            PathBuf::from("artifacts/l128_d18_r1_sk16_fk8")
+        );
+    }
+}
@@ -0,0 +1,341 @@
+// Known-answer tests for STIR and FRI.
+//
+// Each vector in `test_vectors/` fixes a polynomial, a parameter set, a Merkle tree hash and a
+// Fiat-Shamir sponge, and records the compressed serialization of the resulting commitment and
+// proof. Any change to the transcript or to the encoding of proofs makes these tests fail.
+// If the change is intended, regenerate the vectors with
+//
+//     STIR_UPDATE_TEST_VECTORS=1 cargo test test_vectors
+//
+// and commit the updated files.
+
+use std::path::PathBuf;
+
+use ark_crypto_primitives::{merkle_tree::Config, sponge::CryptographicSponge};
+use ark_ff::{FftField, PrimeField};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crypto::{fields::Field64 as TestField, fs, merkle_tree},
+    fri::Fri,
+    ldt::{LowDegreeTest, Prover, Verifier},
+    parameters::{Parameters, SoundnessType},
+    stir::Stir,
+};
+
+const UPDATE_ENV_VAR: &str = "STIR_UPDATE_TEST_VECTORS";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct TestVector {
+    protocol: String,
+    field: String,
+    merkle_tree: String,
+    fiat_shamir: String,
+    security_level: usize,
+    protocol_security_level: usize,
+    starting_degree: usize,
+    stopping_degree: usize,
+    folding_factor: usize,
+    starting_rate: usize,
+    soundness_type: String,
+    // Compressed serialization of the coefficients of the witness polynomial
+    polynomial: String,
+    commitment: String,
+    proof: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Setup {
+    security_level: usize,
+    protocol_security_level: usize,
+    starting_degree: usize,
+    stopping_degree: usize,
+    folding_factor: usize,
+    starting_rate: usize,
+}
+
+// Small enough to run quickly, large enough to have more than one STIR round.
+// The gap between the two security levels forces a few bits of proof of work.
+const SETUP: Setup = Setup {
+    security_level: 32,
+    protocol_security_level: 24,
+    starting_degree: 1 << 8,
+    stopping_degree: 1 << 2,
+    folding_factor: 4,
+    starting_rate: 2,
+};
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn serialize_hex(value: &impl CanonicalSerialize) -> String {
+    let mut bytes = vec![];
+    value.serialize_compressed(&mut bytes).unwrap();
+    to_hex(&bytes)
+}
+
+// A fixed polynomial with all coefficients non-zero: f(X) = sum_i (i^2 + 1) X^i
+fn test_polynomial<F: PrimeField>(degree: usize) -> DensePolynomial<F> {
+    DensePolynomial::from_coefficients_vec((0..degree as u64).map(|i| F::from(i * i + 1)).collect())
+}
+
+fn vector_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("test_vectors")
+        .join(format!("{}.json", name))
+}
+
+fn check_vector<F, MerkleConfig, FSConfig, LDT>(
+    name: &str,
+    protocol: &str,
+    merkle_tree: &str,
+    fiat_shamir: &str,
+    parameters: Parameters<F, MerkleConfig, FSConfig>,
+) where
+    F: FftField + PrimeField,
+    MerkleConfig: Config,
+    FSConfig: CryptographicSponge,
+    FSConfig::Config: Clone,
+    LDT: LowDegreeTest<F, MerkleConfig, FSConfig>,
+    <LDT::Verifier as Verifier<F, MerkleConfig, FSConfig>>::Commitment:
+        CanonicalSerialize + CanonicalDeserialize,
+    <LDT::Verifier as Verifier<F, MerkleConfig, FSConfig>>::Proof: CanonicalDeserialize,
+{
+    let polynomial = test_polynomial::<F>(parameters.starting_degree);
+    let mut computed = TestVector {
+        protocol: protocol.to_string(),
+        field: "Field64".to_string(),
+        merkle_tree: merkle_tree.to_string(),
+        fiat_shamir: fiat_shamir.to_string(),
+        security_level: parameters.security_level,
+        protocol_security_level: parameters.protocol_security_level,
+        starting_degree: parameters.starting_degree,
+        stopping_degree: parameters.stopping_degree,
+        folding_factor: parameters.folding_factor,
+        starting_rate: parameters.starting_rate,
+        soundness_type: format!("{:?}", parameters.soundness_type),
+        polynomial: serialize_hex(&polynomial.coeffs),
+        commitment: String::new(),
+        proof: String::new(),
+    };
+
+    let (prover, verifier) = LDT::instantiate(parameters);
+    let (commitment, witness) = prover.commit(polynomial);
+    let proof = prover.prove(witness);
+    assert!(verifier.verify(&commitment, &proof));
+
+    computed.commitment = serialize_hex(&commitment);
+    computed.proof = serialize_hex(&proof);
+
+    let path = vector_path(name);
+    if std::env::var(UPDATE_ENV_VAR).is_ok() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let contents = serde_json::to_string_pretty(&computed).unwrap();
+        std::fs::write(&path, contents + "\n").unwrap();
+        return;
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing test vector {}, run with {}=1 to generate it",
+            path.display(),
+            UPDATE_ENV_VAR
+        )
+    });
+    let expected: TestVector = serde_json::from_str(&contents).unwrap();
+
+    // The recorded proof must still deserialize and verify
+    let recorded_commitment =
+        <LDT::Verifier as Verifier<F, MerkleConfig, FSConfig>>::Commitment::deserialize_compressed(
+            &from_hex(&expected.commitment)[..],
+        )
+        .unwrap();
+    let recorded_proof =
+        <LDT::Verifier as Verifier<F, MerkleConfig, FSConfig>>::Proof::deserialize_compressed(
+            &from_hex(&expected.proof)[..],
+        )
+        .unwrap();
+    assert!(
+        verifier.verify(&recorded_commitment, &recorded_proof),
+        "recorded proof in {} does not verify",
+        name
+    );
+
+    assert_eq!(
+        expected.polynomial, computed.polynomial,
+        "{}: polynomial changed",
+        name
+    );
+    assert_eq!(
+        expected.commitment, computed.commitment,
+        "{}: commitment changed",
+        name
+    );
+    assert_eq!(expected.proof, computed.proof, "{}: proof changed", name);
+    assert_eq!(expected, computed, "{}: parameters changed", name);
+}
+
+fn parameters<MerkleConfig, FSConfig>(
+    leaf_hash_params: ark_crypto_primitives::merkle_tree::LeafParam<MerkleConfig>,
+    two_to_one_params: ark_crypto_primitives::merkle_tree::TwoToOneParam<MerkleConfig>,
+    fiat_shamir_config: FSConfig::Config,
+) -> Parameters<TestField, MerkleConfig, FSConfig>
+where
+    MerkleConfig: Config,
+    FSConfig: CryptographicSponge,
+    FSConfig::Config: Clone,
+{
+    Parameters {
+        security_level: SETUP.security_level,
+        protocol_security_level: SETUP.protocol_security_level,
+        starting_degree: SETUP.starting_degree,
+        stopping_degree: SETUP.stopping_degree,
+        folding_factor: SETUP.folding_factor,
+        starting_rate: SETUP.starting_rate,
+        soundness_type: SoundnessType::Conjecture,
+        leaf_hash_params,
+        two_to_one_params,
+        fiat_shamir_config,
+        _field: Default::default(),
+    }
+}
+
+macro_rules! byte_hash_vectors {
+    ($stir_test:ident, $fri_test:ident, $merkle:ident, $fs:ident) => {
+        #[test]
+        fn $stir_test() {
+            use merkle_tree::$merkle as mt;
+            let mut rng = ark_std::test_rng();
+            let (leaf_hash_params, two_to_one_params) =
+                mt::default_config::<TestField>(&mut rng, SETUP.folding_factor);
+            let params = parameters::<mt::MerkleTreeParams<TestField>, fs::$fs::Sponge>(
+                leaf_hash_params,
+                two_to_one_params,
+                fs::$fs::default_fs_config(),
+            );
+            check_vector::<_, _, _, Stir<_, _, _>>(
+                concat!("stir_", stringify!($merkle), "_", stringify!($fs)),
+                "stir",
+                stringify!($merkle),
+                stringify!($fs),
+                params,
+            );
+        }
+
+        #[test]
+        fn $fri_test() {
+            use merkle_tree::$merkle as mt;
+            let mut rng = ark_std::test_rng();
+            let (leaf_hash_params, two_to_one_params) =
+                mt::default_config::<TestField>(&mut rng, SETUP.folding_factor);
+            let params = parameters::<mt::MerkleTreeParams<TestField>, fs::$fs::Sponge>(
+                leaf_hash_params,
+                two_to_one_params,
+                fs::$fs::default_fs_config(),
+            );
+            check_vector::<_, _, _, Fri<_, _, _>>(
+                concat!("fri_", stringify!($merkle), "_", stringify!($fs)),
+                "fri",
+                stringify!($merkle),
+                stringify!($fs),
+                params,
+            );
+        }
+    };
+}
+
+byte_hash_vectors!(stir_blake3, fri_blake3, blake3, blake3);
+byte_hash_vectors!(stir_sha3, fri_sha3, sha3, blake3);
+byte_hash_vectors!(stir_sha3_sha3, fri_sha3_sha3, sha3, sha3);
+byte_hash_vectors!(stir_blake2_blake2, fri_blake2_blake2, blake2, blake2);
+byte_hash_vectors!(stir_keccak_keccak, fri_keccak_keccak, keccak, keccak);
+
+#[test]
+fn stir_poseidon() {
+    use merkle_tree::poseidon as mt;
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) =
+        mt::default_config::<TestField>(&mut rng, SETUP.folding_factor);
+    let params = parameters::<mt::MerkleTreeParams<TestField>, fs::poseidon::Sponge<TestField>>(
+        leaf_hash_params,
+        two_to_one_params,
+        fs::poseidon::default_fs_config(),
+    );
+    check_vector::<_, _, _, Stir<_, _, _>>(
+        "stir_poseidon_poseidon",
+        "stir",
+        "poseidon",
+        "poseidon",
+        params,
+    );
+}
+
+#[test]
+fn fri_poseidon() {
+    use merkle_tree::poseidon as mt;
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) =
+        mt::default_config::<TestField>(&mut rng, SETUP.folding_factor);
+    let params = parameters::<mt::MerkleTreeParams<TestField>, fs::poseidon::Sponge<TestField>>(
+        leaf_hash_params,
+        two_to_one_params,
+        fs::poseidon::default_fs_config(),
+    );
+    check_vector::<_, _, _, Fri<_, _, _>>(
+        "fri_poseidon_poseidon",
+        "fri",
+        "poseidon",
+        "poseidon",
+        params,
+    );
+}
+
+#[test]
+fn stir_poseidon2() {
+    use merkle_tree::poseidon2 as mt;
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) =
+        mt::default_config::<TestField>(&mut rng, SETUP.folding_factor);
+    let params = parameters::<mt::MerkleTreeParams<TestField>, fs::poseidon2::Sponge<TestField>>(
+        leaf_hash_params,
+        two_to_one_params,
+        fs::poseidon2::default_fs_config(),
+    );
+    check_vector::<_, _, _, Stir<_, _, _>>(
+        "stir_poseidon2_poseidon2",
+        "stir",
+        "poseidon2",
+        "poseidon2",
+        params,
+    );
+}
+
+#[test]
+fn fri_poseidon2() {
+    use merkle_tree::poseidon2 as mt;
+    let mut rng = ark_std::test_rng();
+    let (leaf_hash_params, two_to_one_params) =
+        mt::default_config::<TestField>(&mut rng, SETUP.folding_factor);
+    let params = parameters::<mt::MerkleTreeParams<TestField>, fs::poseidon2::Sponge<TestField>>(
+        leaf_hash_params,
+        two_to_one_params,
+        fs::poseidon2::default_fs_config(),
+    );
+    check_vector::<_, _, _, Fri<_, _, _>>(
+        "fri_poseidon2_poseidon2",
+        "fri",
+        "poseidon2",
+        "poseidon2",
+        params,
+    );
+}
@@ -0,0 +1,358 @@
+use std::fmt::Display;
+
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge};
+use ark_ff::PrimeField;
+
+use crate::utils;
+
+// A single interaction with the Fiat-Shamir transcript
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    // Prover message: a Merkle root
+    Commitment,
+    // Prover message: the given number of field elements
+    FieldElements(usize),
+    // Verifier message: the given number of field elements
+    ChallengeScalars(usize),
+    // Verifier message: (at most) the given number of indexes, deduplicated and sorted
+    ChallengeIndices(usize),
+    // Proof of work with the given number of bits
+    Grind(usize),
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Op::Commitment => write!(f, "commitment"),
+            Op::FieldElements(n) => write!(f, "field_elements({})", n),
+            Op::ChallengeScalars(n) => write!(f, "challenge_scalars({})", n),
+            Op::ChallengeIndices(n) => write!(f, "challenge_indices({})", n),
+            Op::Grind(bits) => write!(f, "grind({})", bits),
+        }
+    }
+}
+
+// The sequence of operations that prover and verifier are expected to perform on the transcript.
+// Both sides build it from the same parameters, so any drift between them is reported as an error
+// rather than a failed verification.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IOPattern(Vec<Op>);
+
+impl IOPattern {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn commitment(mut self) -> Self {
+        self.0.push(Op::Commitment);
+        self
+    }
+
+    pub fn field_elements(mut self, count: usize) -> Self {
+        self.0.push(Op::FieldElements(count));
+        self
+    }
+
+    pub fn challenge_scalars(mut self, count: usize) -> Self {
+        self.0.push(Op::ChallengeScalars(count));
+        self
+    }
+
+    pub fn challenge_indices(mut self, count: usize) -> Self {
+        self.0.push(Op::ChallengeIndices(count));
+        self
+    }
+
+    pub fn grind(mut self, bits: usize) -> Self {
+        self.0.push(Op::Grind(bits));
+        self
+    }
+
+    pub fn ops(&self) -> &[Op] {
+        &self.0
+    }
+
+    // Checks that op is the operation at position, and moves past it
+    pub(crate) fn expect(&self, position: &mut usize, op: Op) -> Result<(), TranscriptError> {
+        if self.0.get(*position) != Some(&op) {
+            return Err(TranscriptError::UnexpectedOp {
+                pattern: self.clone(),
+                position: *position,
+                found: op,
+            });
+        }
+        *position += 1;
+        Ok(())
+    }
+
+    // Checks that all operations were performed
+    pub(crate) fn finish(&self, position: usize) -> Result<(), TranscriptError> {
+        if position != self.0.len() {
+            return Err(TranscriptError::Unfinished {
+                pattern: self.clone(),
+                position,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscriptError {
+    // The operation at position does not match the declared pattern
+    UnexpectedOp {
+        pattern: IOPattern,
+        position: usize,
+        found: Op,
+    },
+    // The transcript was finished before the declared pattern was exhausted
+    Unfinished {
+        pattern: IOPattern,
+        position: usize,
+    },
+    // The proof of work nonce is missing or invalid
+    InvalidProofOfWork,
+}
+
+impl TranscriptError {
+    // Prints the declared pattern, marking where the transcript diverged from it
+    fn write_diff(
+        f: &mut std::fmt::Formatter<'_>,
+        pattern: &IOPattern,
+        position: usize,
+        found: Option<Op>,
+    ) -> std::fmt::Result {
+        for (i, op) in pattern.ops().iter().enumerate() {
+            match (i == position, found) {
+                (true, Some(found)) => {
+                    writeln!(f, "- {:>4}: {}", i, op)?;
+                    writeln!(f, "+ {:>4}: {}", i, found)?;
+                }
+                (true, None) => writeln!(f, "- {:>4}: {}", i, op)?,
+                _ if i < position => writeln!(f, "  {:>4}: {}", i, op)?,
+                _ => writeln!(f, "  {:>4}: {} (not reached)", i, op)?,
+            }
+        }
+        if position >= pattern.ops().len() {
+            if let Some(found) = found {
+                writeln!(f, "+ {:>4}: {}", position, found)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for TranscriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranscriptError::UnexpectedOp {
+                pattern,
+                position,
+                found,
+            } => {
+                writeln!(
+                    f,
+                    "transcript diverged from the IO pattern at operation {}:",
+                    position
+                )?;
+                Self::write_diff(f, pattern, *position, Some(*found))
+            }
+            TranscriptError::Unfinished { pattern, position } => {
+                writeln!(
+                    f,
+                    "transcript finished after {} of {} operations:",
+                    position,
+                    pattern.ops().len()
+                )?;
+                Self::write_diff(f, pattern, *position, None)
+            }
+            TranscriptError::InvalidProofOfWork => write!(f, "invalid proof of work"),
+        }
+    }
+}
+
+impl std::error::Error for TranscriptError {}
+
+// Fiat-Shamir transcript shared by the provers and verifiers. Every operation is checked against
+// the declared IO pattern before it touches the sponge.
+#[derive(Clone)]
+pub struct Transcript<FSConfig: CryptographicSponge> {
+    sponge: FSConfig,
+    pattern: IOPattern,
+    position: usize,
+}
+
+impl<FSConfig> Transcript<FSConfig>
+where
+    FSConfig: CryptographicSponge,
+{
+    pub fn new(fiat_shamir_config: &FSConfig::Config, pattern: IOPattern) -> Self {
+        Self {
+            sponge: FSConfig::new(fiat_shamir_config),
+            pattern,
+            position: 0,
+        }
+    }
+
+    fn expect(&mut self, op: Op) -> Result<(), TranscriptError> {
+        self.pattern.expect(&mut self.position, op)
+    }
+
+    pub fn append_commitment(&mut self, root: &impl Absorb) -> Result<(), TranscriptError> {
+        self.expect(Op::Commitment)?;
+        self.sponge.absorb(root);
+        Ok(())
+    }
+
+    pub fn append_field_elements<F: PrimeField + Absorb>(
+        &mut self,
+        elements: &[F],
+    ) -> Result<(), TranscriptError> {
+        self.expect(Op::FieldElements(elements.len()))?;
+        self.sponge.absorb(&elements);
+        Ok(())
+    }
+
+    pub fn challenge_scalar<F: PrimeField>(&mut self) -> Result<F, TranscriptError> {
+        Ok(self.challenge_scalars(1)?[0])
+    }
+
+    pub fn challenge_scalars<F: PrimeField>(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<F>, TranscriptError> {
+        self.expect(Op::ChallengeScalars(count))?;
+        Ok(self.sponge.squeeze_field_elements(count))
+    }
+
+    // Samples count indexes in 0..range (which must be a power of two), deduplicated and sorted
+    pub fn challenge_indices(
+        &mut self,
+        count: usize,
+        range: usize,
+    ) -> Result<Vec<usize>, TranscriptError> {
+        self.expect(Op::ChallengeIndices(count))?;
+        let sponge = &mut self.sponge;
+        Ok(utils::dedup(
+            (0..count).map(|_| utils::squeeze_integer(sponge, range)),
+        ))
+    }
+
+    // Prover side of the proof of work
+    pub fn grind(&mut self, bits: usize) -> Result<Option<usize>, TranscriptError> {
+        self.expect(Op::Grind(bits))?;
+        Ok(utils::proof_of_work(&mut self.sponge, bits))
+    }
+
+    // Verifier side of the proof of work
+    pub fn check_grind(
+        &mut self,
+        bits: usize,
+        pow_nonce: Option<usize>,
+    ) -> Result<(), TranscriptError> {
+        self.expect(Op::Grind(bits))?;
+        if !utils::proof_of_work_verify(&mut self.sponge, bits, pow_nonce) {
+            return Err(TranscriptError::InvalidProofOfWork);
+        }
+        Ok(())
+    }
+
+    // Checks that the whole IO pattern was used
+    pub fn finish(self) -> Result<(), TranscriptError> {
+        self.pattern.finish(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{fields::Field64 as TestField, fs};
+
+    fn pattern() -> IOPattern {
+        IOPattern::new()
+            .commitment()
+            .challenge_scalars(1)
+            .field_elements(2)
+            .challenge_indices(4)
+    }
+
+    #[test]
+    fn test_matches_sponge() {
+        let config = fs::blake3::default_fs_config();
+        let root = vec![1u8; 32];
+        let elements = vec![TestField::from(2), TestField::from(3)];
+
+        let mut transcript = Transcript::<fs::blake3::Sponge>::new(&config, pattern());
+        transcript.append_commitment(&root).unwrap();
+        let scalar: TestField = transcript.challenge_scalar().unwrap();
+        transcript.append_field_elements(&elements).unwrap();
+        let indexes = transcript.challenge_indices(4, 16).unwrap();
+        transcript.finish().unwrap();
+
+        let mut sponge = fs::blake3::Sponge::new(&config);
+        sponge.absorb(&root);
+        assert_eq!(scalar, sponge.squeeze_field_elements::<TestField>(1)[0]);
+        sponge.absorb(&elements);
+        let expected_indexes =
+            utils::dedup((0..4).map(|_| utils::squeeze_integer(&mut sponge, 16)));
+        assert_eq!(indexes, expected_indexes);
+    }
+
+    #[test]
+    fn test_pattern_mismatch() {
+        let config = fs::blake3::default_fs_config();
+        let mut transcript = Transcript::<fs::blake3::Sponge>::new(&config, pattern());
+        transcript.append_commitment(&vec![0u8; 32]).unwrap();
+
+        let err = transcript.challenge_scalars::<TestField>(2).unwrap_err();
+        assert_eq!(
+            err,
+            TranscriptError::UnexpectedOp {
+                pattern: pattern(),
+                position: 1,
+                found: Op::ChallengeScalars(2),
+            }
+        );
+        let message = err.to_string();
+        assert!(message.contains("-    1: challenge_scalars(1)"));
+        assert!(message.contains("+    1: challenge_scalars(2)"));
+    }
+
+    #[test]
+    fn test_unfinished() {
+        let config = fs::blake3::default_fs_config();
+        let mut transcript = Transcript::<fs::blake3::Sponge>::new(&config, pattern());
+        transcript.append_commitment(&vec![0u8; 32]).unwrap();
+
+        assert_eq!(
+            transcript.finish(),
+            Err(TranscriptError::Unfinished {
+                pattern: pattern(),
+                position: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_grind() {
+        let config = fs::blake3::default_fs_config();
+        let pattern = IOPattern::new().commitment().grind(8);
+
+        let mut prover = Transcript::<fs::blake3::Sponge>::new(&config, pattern.clone());
+        prover.append_commitment(&vec![0u8; 32]).unwrap();
+        let nonce = prover.grind(8).unwrap();
+        assert!(nonce.is_some());
+
+        let mut verifier = Transcript::<fs::blake3::Sponge>::new(&config, pattern.clone());
+        verifier.append_commitment(&vec![0u8; 32]).unwrap();
+        verifier.check_grind(8, nonce).unwrap();
+        verifier.finish().unwrap();
+
+        let mut verifier = Transcript::<fs::blake3::Sponge>::new(&config, pattern);
+        verifier.append_commitment(&vec![0u8; 32]).unwrap();
+        assert_eq!(
+            verifier.check_grind(8, None),
+            Err(TranscriptError::InvalidProofOfWork)
+        );
+    }
+}
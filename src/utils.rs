@@ -1,6 +1,9 @@
 use std::collections::BTreeSet;
 
 use ark_crypto_primitives::sponge::CryptographicSponge;
+use ark_ff::Field;
+
+use crate::metrics::{self, Metric};
 
 pub fn is_power_of_two(n: usize) -> bool {
     n & (n - 1) == 0
@@ -57,6 +60,7 @@ pub fn proof_of_work_verify(
         return true;
     }
 
+    metrics::record(Metric::ProofOfWorkCheck, 1);
     if pow_nonce.is_none() {
         return false;
     }
@@ -84,6 +88,25 @@ pub fn dedup<T: Ord>(v: impl IntoIterator<Item = T>) -> Vec<T> {
     Vec::from_iter(BTreeSet::from_iter(v))
 }
 
+// The combination sum_i randomness^i * vectors[i], where shorter vectors are padded with zeros
+pub fn linear_combination<'a, F: Field>(
+    vectors: impl IntoIterator<Item = &'a [F]>,
+    randomness: F,
+) -> Vec<F> {
+    let mut combination: Vec<F> = vec![];
+    let mut scale = F::ONE;
+    for vector in vectors {
+        if combination.len() < vector.len() {
+            combination.resize(vector.len(), F::ZERO);
+        }
+        for (sum, x) in combination.iter_mut().zip(vector) {
+            *sum += metrics::mul(scale, *x);
+        }
+        scale = metrics::mul(scale, randomness);
+    }
+    combination
+}
+
 // Takes the vector of evaluations (assume that evals[i] = f(omega^i))
 // and folds them into a vector of such that folded_evals[i] = [f(omega^(i + k * j)) for j in 0..folding_factor]
 pub fn stack_evaluations<F: Copy>(evals: Vec<F>, folding_factor: usize) -> Vec<Vec<F>> {
@@ -101,3 +124,20 @@ pub fn stack_evaluations<F: Copy>(evals: Vec<F>, folding_factor: usize) -> Vec<V
 
     stacked_evaluations
 }
+
+// Inverse of stack_evaluations: takes folded_evals[i] = [f(omega^(i + k * j)) for j in 0..folding_factor]
+// and returns evals such that evals[i] = f(omega^i)
+pub fn unstack_evaluations<F: Copy>(stacked_evaluations: &[Vec<F>]) -> Vec<F> {
+    assert!(!stacked_evaluations.is_empty());
+    let size_of_new_domain = stacked_evaluations.len();
+    let folding_factor = stacked_evaluations[0].len();
+
+    let mut evals = Vec::with_capacity(size_of_new_domain * folding_factor);
+    for j in 0..folding_factor {
+        for stacked in stacked_evaluations {
+            evals.push(stacked[j]);
+        }
+    }
+
+    evals
+}